@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::texture::ImageTexture;
+
+type TextureGenerator = fn() -> ImageTexture;
+
+// Texturas procedurales para los archivos que falten en disco, buscadas por su ruta exacta.
+// Cualquier otra ruta que no cargue usa la pared de ladrillos.
+const FALLBACKS: &[(&str, TextureGenerator)] = &[
+    ("assets/wall.png", ImageTexture::generate_wall_texture),
+    ("assets/exit.png", ImageTexture::generate_exit_texture),
+    ("assets/door.png", ImageTexture::generate_door_texture),
+    ("assets/switch.png", || ImageTexture::generate_switch_texture(false)),
+    ("assets/switch_used.png", || ImageTexture::generate_switch_texture(true)),
+    ("assets/gate.png", ImageTexture::generate_gate_texture),
+    ("assets/gasoline_can_1.png", || ImageTexture::generate_gasoline_can_texture(1)),
+    ("assets/gasoline_can_2.png", || ImageTexture::generate_gasoline_can_texture(2)),
+    ("assets/gasoline_can_3.png", || ImageTexture::generate_gasoline_can_texture(3)),
+    ("assets/taylor.png", ImageTexture::generate_taylor_fallback),
+    ("assets/car.png", ImageTexture::generate_car_texture),
+    ("assets/player.png", ImageTexture::generate_player_texture),
+    ("assets/decoy.png", ImageTexture::generate_player_texture),
+    ("assets/smoke.png", ImageTexture::generate_smoke_texture),
+    ("assets/checkpoint.png", || ImageTexture::generate_checkpoint_texture(false)),
+    ("assets/checkpoint_active.png", || ImageTexture::generate_checkpoint_texture(true)),
+    ("assets/item_decoy.png", || ImageTexture::generate_item_texture([230, 120, 200])),
+    ("assets/item_smoke.png", || ImageTexture::generate_item_texture([170, 170, 180])),
+    ("assets/item_speed.png", || ImageTexture::generate_item_texture([60, 200, 255])),
+    ("assets/item_map.png", || ImageTexture::generate_item_texture([240, 220, 80])),
+];
+
+fn load_texture(path: &str) -> ImageTexture {
+    ImageTexture::from_file(path).unwrap_or_else(|e| {
+        println!("Error cargando {}: {}, generando textura de fallback...", path, e);
+        FALLBACKS
+            .iter()
+            .find(|(name, _)| *name == path)
+            .map_or_else(ImageTexture::generate_wall_texture, |(_, generate)| generate())
+    })
+}
+
+// Copiar un identificador solo incrementa un contador; los píxeles se comparten.
+pub type TextureHandle = Arc<ImageTexture>;
+
+// Carga cada archivo una sola vez y entrega identificadores compartidos a quien lo pida.
+pub struct AssetManager {
+    textures: HashMap<String, TextureHandle>,
+}
+
+impl AssetManager {
+    pub fn new() -> Self {
+        Self {
+            textures: HashMap::new(),
+        }
+    }
+
+    pub fn texture(&mut self, path: &str) -> TextureHandle {
+        if let Some(handle) = self.textures.get(path) {
+            return handle.clone();
+        }
+
+        let handle = Arc::new(load_texture(path));
+        self.textures.insert(path.to_string(), handle.clone());
+        handle
+    }
+
+    // Los identificadores repartidos antes siguen apuntando a la versión vieja; quien los
+    // guarda debe pedirlos de nuevo tras una recarga.
+    pub fn reload(&mut self, path: &str) -> bool {
+        if !self.textures.contains_key(path) {
+            return false;
+        }
+        self.textures.insert(path.to_string(), Arc::new(load_texture(path)));
+        true
+    }
+
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        self.textures.values().map(|texture| texture.memory_usage()).sum()
+    }
+
+    pub fn print_usage(&self) {
+        println!(
+            "Texturas cargadas: {} ({:.1} KiB)",
+            self.texture_count(),
+            self.memory_usage() as f32 / 1024.0,
+        );
+    }
+}
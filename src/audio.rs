@@ -0,0 +1,28 @@
+use rodio::{Decoder, OutputStreamHandle, Sink, Source};
+use std::fs::File;
+use std::io::BufReader;
+
+pub const MUSIC_FILE: &str = "audio/getaway_car.mp3";
+
+pub fn start_music(stream_handle: &OutputStreamHandle, file_path: &str, volume: f32) -> Option<Sink> {
+    let file = File::open(file_path).ok()?;
+    let source = Decoder::new(BufReader::new(file)).ok()?;
+    let sink = Sink::try_new(stream_handle).ok()?;
+    sink.append(source.repeat_infinite());
+    sink.set_volume(volume);
+    sink.play();
+    Some(sink)
+}
+
+pub fn play_effect(stream_handle: &OutputStreamHandle, file_path: &str, volume: f32) {
+    if let Ok(file) = File::open(file_path) {
+        if let Ok(source) = Decoder::new(BufReader::new(file)) {
+            if let Ok(effect_sink) = Sink::try_new(stream_handle) {
+                effect_sink.append(source);
+                effect_sink.set_volume(volume);
+                effect_sink.play();
+                effect_sink.detach();
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use raylib::prelude::Vector2;
+use crate::player::Player;
+
+// Altura de las paredes relativa al tamaño de una celda; el `scale` de las entidades y el
+// tamaño de Taylor se miden en esta misma unidad.
+pub const WALL_HEIGHT: f32 = 0.55;
+const NEAR_PLANE: f32 = 10.0;
+
+// Cámara compartida por paredes y billboards. Cada columna de pantalla corresponde a un punto
+// del plano de cámara, así que todo lo que se proyecta usa la misma distancia focal y un sprite
+// queda fijo respecto a las paredes al girar.
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    pub position: Vector2,
+    pub angle: f32,
+    pub direction: Vector2,
+    pub plane: Vector2,
+    pub focal_length: f32,
+    pub half_width: f32,
+    pub horizon: f32,
+    pub wall_height: f32,
+}
+
+pub struct Projection {
+    pub screen_x: f32,
+    pub depth: f32,
+}
+
+// Rectángulo en pantalla de un sprite proyectado, sin recortar a la pantalla.
+pub struct Billboard {
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+    pub depth: f32,
+}
+
+impl Camera {
+    pub fn new(player: &Player, screen_width: u32, screen_height: u32, block_size: usize) -> Self {
+        let direction = Vector2::new(player.a.cos(), player.a.sin());
+        let plane_length = (player.fov / 2.0).tan();
+        let half_width = screen_width as f32 / 2.0;
+
+        Self {
+            position: player.pos,
+            angle: player.a,
+            direction,
+            plane: Vector2::new(-direction.y, direction.x) * plane_length,
+            focal_length: half_width / plane_length,
+            half_width,
+            horizon: screen_height as f32 / 2.0,
+            wall_height: block_size as f32 * WALL_HEIGHT,
+        }
+    }
+
+    // Ángulo del rayo que pasa por el centro de la columna; no quedan espaciados por igual,
+    // sino repartidos a lo largo del plano de cámara.
+    pub fn ray_angle(&self, column: u32) -> f32 {
+        let camera_x = (column as f32 + 0.5) / self.half_width - 1.0;
+        let ray = self.direction + self.plane * camera_x;
+        ray.y.atan2(ray.x)
+    }
+
+    // Distancia perpendicular al plano de cámara, la que se guarda en el z-buffer.
+    pub fn depth(&self, distance: f32, ray_angle: f32) -> f32 {
+        distance * (ray_angle - self.angle).cos()
+    }
+
+    pub fn project(&self, point: Vector2) -> Option<Projection> {
+        let relative = point - self.position;
+        let depth = relative.x * self.direction.x + relative.y * self.direction.y;
+        if depth <= NEAR_PLANE {
+            return None;
+        }
+
+        let lateral = relative.x * -self.direction.y + relative.y * self.direction.x;
+        Some(Projection {
+            screen_x: self.half_width + lateral / depth * self.focal_length,
+            depth,
+        })
+    }
+
+    pub fn projected_height(&self, world_height: f32, depth: f32) -> f32 {
+        world_height * self.focal_length / depth
+    }
+
+    // `world_height` en las mismas unidades que el mundo; `vertical_offset` en alturas del
+    // propio sprite, positivo hacia el suelo.
+    pub fn billboard(&self, position: Vector2, world_height: f32, aspect: f32, vertical_offset: f32) -> Option<Billboard> {
+        let projection = self.project(position)?;
+        let height = self.projected_height(world_height, projection.depth);
+        let width = height * aspect;
+        let center_y = self.horizon + height * vertical_offset;
+
+        Some(Billboard {
+            left: projection.screen_x - width / 2.0,
+            top: center_y - height / 2.0,
+            width,
+            height,
+            depth: projection.depth,
+        })
+    }
+}
+
+impl Billboard {
+    // Píxeles visibles junto con su coordenada de textura.
+    pub fn pixels(&self, screen_width: u32, screen_height: u32) -> impl Iterator<Item = (u32, u32, f32, f32)> + '_ {
+        let start_x = self.left.max(0.0) as u32;
+        let end_x = (self.left + self.width).clamp(0.0, screen_width as f32) as u32;
+        let start_y = self.top.max(0.0) as u32;
+        let end_y = (self.top + self.height).clamp(0.0, screen_height as f32) as u32;
+
+        (start_y..end_y).flat_map(move |y| {
+            (start_x..end_x).map(move |x| {
+                let tex_x = (x as f32 + 0.5 - self.left) / self.width;
+                let tex_y = (y as f32 + 0.5 - self.top) / self.height;
+                (x, y, tex_x, tex_y)
+            })
+        })
+    }
+}
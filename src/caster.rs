@@ -1,10 +1,20 @@
 use raylib::color::Color;
-use crate::framebuffer::Framebuffer;
+use raylib::prelude::Vector2;
+use crate::framebuffer::{blend, Framebuffer, RowBand};
 use crate::maze::Maze;
 use crate::player::Player;
 use crate::texture::TextureManager;
 use crate::sprites::SpriteManager;
 use crate::taylor_sprite::TaylorSprite;
+use crate::camera::Camera;
+use crate::depth_buffer::{DepthBuffer, WallSlice};
+use crate::ghost::GhostFrame;
+
+const MAX_WALL_LAYERS: usize = 4;
+// Ancho de los sprites respecto a su alto, y tamaño del fantasma en alturas de pared.
+const SPRITE_ASPECT: f32 = 0.8;
+const GHOST_SCALE: f32 = 1.1;
+const GHOST_ASPECT: f32 = 0.5;
 
 pub struct Intersect {
     pub distance: f32,
@@ -14,189 +24,284 @@ pub struct Intersect {
     pub side: bool,
 }
 
-pub fn cast_ray(
-    framebuffer: &mut Framebuffer,
+// Marcha el rayo por el laberinto atravesando las paredes con textura translúcida (rejas,
+// vidrios) y entrega a `on_hit` todos los impactos, del más cercano hasta el primero opaco.
+fn cast_wall_layers(
     maze: &Maze,
     player: &Player,
     a: f32,
     block_size: usize,
-    draw_line: bool,
-) -> Intersect {
+    texture_manager: &TextureManager,
+    mut on_hit: impl FnMut(Intersect),
+) {
+    let mut layers = 0;
+    let mut current_cell: Option<(usize, usize)> = None;
     let mut d = 0.0;
     let step = 0.5;
-    framebuffer.set_current_color(Color::WHITESMOKE);
-    
-    loop {
-        let cos = d * a.cos();
-        let sin = d * a.sin();
-        let x = (player.pos.x + cos) as usize;
-        let y = (player.pos.y + sin) as usize;
-        let i = x / block_size;
-        let j = y / block_size;
-        
-        if maze.is_empty() || j >= maze.len() {
-            return Intersect {
-                distance: d,
-                impact: '+',
-                hit_x: player.pos.x + cos,
-                hit_y: player.pos.y + sin,
-                side: false,
-            };
-        }
-        
-        if i >= maze.get(j).map_or(0, |row| row.len()) {
-            return Intersect {
-                distance: d,
-                impact: '+',
-                hit_x: player.pos.x + cos,
-                hit_y: player.pos.y + sin,
-                side: false,
-            };
-        }
-        
-        let cell = maze.get(j).and_then(|row| row.get(i)).unwrap_or(&'+');
-        if *cell != ' ' {
-            let hit_x = player.pos.x + cos;
-            let hit_y = player.pos.y + sin;
-            
+
+    while d <= 2000.0 {
+        let hit_x = player.pos.x + d * a.cos();
+        let hit_y = player.pos.y + d * a.sin();
+        let i = hit_x as usize / block_size;
+        let j = hit_y as usize / block_size;
+
+        let Some(&cell) = maze.get(j).and_then(|row| row.get(i)) else {
+            break;
+        };
+        if cell != ' ' && current_cell != Some((i, j)) {
             let cell_x = (hit_x / block_size as f32).fract();
             let cell_y = (hit_y / block_size as f32).fract();
-            
-            let side = cell_x.min(1.0 - cell_x) < cell_y.min(1.0 - cell_y);
-            
-            return Intersect {
+            on_hit(Intersect {
                 distance: d,
-                impact: *cell,
+                impact: cell,
                 hit_x,
                 hit_y,
-                side,
-            };
-        }
-        
-        if draw_line {
-            framebuffer.set_pixel(x as u32, y as u32);
+                side: cell_x.min(1.0 - cell_x) < cell_y.min(1.0 - cell_y),
+            });
+            layers += 1;
+            if !texture_manager.get_texture(cell).translucent || layers >= MAX_WALL_LAYERS {
+                return;
+            }
         }
-        
+        current_cell = Some((i, j));
         d += step;
-        
-        if d > 2000.0 {
-            return Intersect {
-                distance: d,
-                impact: '+',
-                hit_x: player.pos.x + cos,
-                hit_y: player.pos.y + sin,
-                side: false,
-            };
+    }
+
+    on_hit(Intersect {
+        distance: d,
+        impact: '+',
+        hit_x: player.pos.x + d * a.cos(),
+        hit_y: player.pos.y + d * a.sin(),
+        side: false,
+    });
+}
+
+// Capas de la columna de la más cercana a la más lejana, escritas en `slices`.
+fn project_column(
+    maze: &Maze,
+    player: &Player,
+    camera: &Camera,
+    column: u32,
+    block_size: usize,
+    texture_manager: &TextureManager,
+    slices: &mut Vec<WallSlice>,
+) {
+    let a = camera.ray_angle(column);
+    cast_wall_layers(maze, player, a, block_size, texture_manager, |intersect| {
+        let depth = camera.depth(intersect.distance, a);
+        let height = camera.projected_height(camera.wall_height, depth);
+        let tex_x = if intersect.side {
+            (intersect.hit_y / block_size as f32).fract()
+        } else {
+            (intersect.hit_x / block_size as f32).fract()
+        };
+        slices.push(WallSlice {
+            depth,
+            top: camera.horizon - height / 2.0,
+            height,
+            tex_x,
+            impact: intersect.impact,
+            side: intersect.side,
+            translucent: texture_manager.get_texture(intersect.impact).translucent,
+        });
+    });
+}
+
+fn background_color(x: u32, y: u32, horizon: f32) -> Color {
+    if y < horizon as u32 {
+        let gradient_factor = y as f32 / horizon;
+        Color::new(
+            (20.0 + gradient_factor * 30.0) as u8,
+            (20.0 + gradient_factor * 50.0) as u8,
+            (40.0 + gradient_factor * 80.0) as u8,
+            255
+        )
+    } else if ((x / 4) + (y / 4)).is_multiple_of(2) {
+        Color::new(40, 20, 20, 255)
+    } else {
+        Color::new(35, 18, 18, 255)
+    }
+}
+
+// Cada píxel se calcula solo a partir de su columna, así el resultado no depende de cómo se
+// repartan las bandas entre los hilos.
+fn draw_band(band: &mut RowBand, depth_buffer: &DepthBuffer, horizon: f32, screen_height: u32, texture_manager: &TextureManager) {
+    let columns = depth_buffer.columns();
+    for y in band.rows() {
+        for x in 0..band.width {
+            let mut color = background_color(x, y, horizon);
+
+            for slice in columns[x as usize].iter().rev() {
+                let stake_top = slice.top.max(0.0) as u32;
+                let stake_bottom = (slice.top + slice.height).min(screen_height as f32) as u32;
+                if y < stake_top || y >= stake_bottom {
+                    continue;
+                }
+
+                let texture = texture_manager.get_texture(slice.impact);
+                let tex_y = (y as f32 + 0.5 - slice.top) / slice.height;
+                let texels_per_pixel = texture.height as f32 / slice.height;
+                let texel = texture.sample(slice.tex_x, tex_y, texels_per_pixel, texture_manager.filter);
+                if texel[3] == 0 {
+                    continue;
+                }
+
+                let shade = if slice.side { 0.7 } else { 1.0 };
+                let wall_color = Color::new(
+                    (texel[0] as f32 * shade) as u8,
+                    (texel[1] as f32 * shade) as u8,
+                    (texel[2] as f32 * shade) as u8,
+                    255,
+                );
+                color = blend(wall_color, color, texel[3]);
+            }
+
+            *band.pixel_mut(x, y) = color;
         }
     }
 }
 
-fn render_single_sprite_with_zbuffer_textured(
+// Fondo y paredes: primero se proyectan las columnas por tramos de columnas y luego se pintan
+// los píxeles por bandas de filas, repartidos entre los hilos del framebuffer.
+fn render_walls(
     framebuffer: &mut Framebuffer,
-    sprite: &crate::sprites::Sprite,
+    maze: &Maze,
+    block_size: usize,
     player: &Player,
-    z_buffer: &[f32],
-    distance: f32
+    camera: &Camera,
+    texture_manager: &TextureManager,
 ) {
-    if distance < 20.0 || distance > 800.0 { return; }
-    
-    let dx = sprite.x - player.pos.x;
-    let dy = sprite.y - player.pos.y;
-    
-    let cos_angle = player.a.cos();
-    let sin_angle = player.a.sin();
-    
-    let transformed_x = -dx * sin_angle + dy * cos_angle;
-    let transformed_y = dx * cos_angle + dy * sin_angle;
+    let height = framebuffer.height;
+    framebuffer.project_columns(|x, slices| {
+        project_column(maze, player, camera, x, block_size, texture_manager, slices);
+    });
+    framebuffer.draw_rows(|band, depth_buffer| {
+        draw_band(band, depth_buffer, camera.horizon, height, texture_manager);
+    });
+}
 
-    if transformed_y <= 10.0 { return; }
-    
-    let hh = framebuffer.height as f32 / 2.0;
-    
-    let actual_distance = (dx * dx + dy * dy).sqrt();
-    let distance_to_projection_plane = 200.0;
-    let sprite_height = (hh / actual_distance) * distance_to_projection_plane;
-    let wall_height_factor = 0.5; 
-    let adjusted_sprite_height = sprite_height * wall_height_factor;
-    
-    let fov = player.fov;
-    let screen_center = framebuffer.width as f32 / 2.0;
-    
-    let sprite_angle = (transformed_x / transformed_y).atan();
-    let angle_from_center = sprite_angle;
-    
-    let pixels_per_radian = framebuffer.width as f32 / fov;
-    let screen_x = screen_center + (angle_from_center * pixels_per_radian);
-    
-    let sprite_width = adjusted_sprite_height * 0.8;
-    let start_x = (screen_x - sprite_width / 2.0) as i32;
-    let end_x = (screen_x + sprite_width / 2.0) as i32;
-    let start_y = (hh - adjusted_sprite_height / 2.0).max(0.0) as i32;
-    let end_y = (hh + adjusted_sprite_height / 2.0).min(framebuffer.height as f32) as i32;
+fn render_sprite_billboard(
+    framebuffer: &mut Framebuffer,
+    sprite: &crate::sprites::Sprite,
+    camera: &Camera,
+    distance: f32,
+    texture_manager: &TextureManager,
+) {
+    if !(20.0..=800.0).contains(&distance) { return; }
     
-    if start_x >= framebuffer.width as i32 || end_x < 0 ||
-       start_y >= framebuffer.height as i32 || end_y < 0 { return; }
+    let Some(billboard) = camera.billboard(
+        Vector2::new(sprite.x, sprite.y),
+        camera.wall_height * sprite.entity.scale,
+        SPRITE_ASPECT,
+        sprite.entity.vertical_offset,
+    ) else {
+        return;
+    };
     
     let current_texture = &sprite.texture_frames[sprite.animation_frame];
+    let texels_per_pixel = current_texture.height as f32 / billboard.height;
+    let distance_factor = (1.0 - (distance / 600.0)).max(0.3);
+    let glow_factor = 1.0 + (sprite.animation_timer * std::f32::consts::TAU).sin() * 0.15;
     
-    for screen_y in start_y.max(0)..end_y.min(framebuffer.height as i32) {
-        for screen_x in start_x.max(0)..end_x.min(framebuffer.width as i32) {
-            let buffer_index = screen_x as usize + screen_y as usize * framebuffer.width as usize;
-            
-            if buffer_index < z_buffer.len() && actual_distance < z_buffer[buffer_index] {
-                let tex_x = if end_x > start_x {
-                    (screen_x - start_x) as f32 / (end_x - start_x) as f32
-                } else { 0.5 };
-                
-                let tex_y = if end_y > start_y {
-                    (screen_y - start_y) as f32 / (end_y - start_y) as f32
-                } else { 0.5 };
-                
-                let texture_color = current_texture.get_color(tex_x, tex_y);
-                
-                if texture_color[0] > 15 || texture_color[1] > 15 || texture_color[2] > 15 {
-                    let mut final_color = Color::new(texture_color[0], texture_color[1], texture_color[2], 255);
-                    
-                    let distance_factor = (1.0 - (actual_distance / 600.0)).max(0.3);
-                    final_color.r = (final_color.r as f32 * distance_factor) as u8;
-                    final_color.g = (final_color.g as f32 * distance_factor) as u8;
-                    final_color.b = (final_color.b as f32 * distance_factor) as u8;
-                    
-                    let glow_factor = 1.0 + (sprite.animation_timer * 6.28).sin() * 0.15;
-                    final_color.r = (final_color.r as f32 * glow_factor).min(255.0) as u8;
-                    final_color.g = (final_color.g as f32 * glow_factor).min(255.0) as u8;
-                    final_color.b = (final_color.b as f32 * glow_factor).min(255.0) as u8;
-                    
-                    framebuffer.set_current_color(final_color);
-                    framebuffer.set_pixel(screen_x as u32, screen_y as u32);
-                }
-            }
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let (mut canvas, depth_buffer) = framebuffer.viewport_band();
+    for (screen_x, screen_y, tex_x, tex_y) in billboard.pixels(width, height) {
+        if !depth_buffer.is_visible(screen_x, screen_y, billboard.depth, texture_manager) {
+            continue;
+        }
+        
+        let texture_color = current_texture.sample(tex_x, tex_y, texels_per_pixel, texture_manager.filter);
+        if texture_color[3] == 0 {
+            continue;
         }
+        
+        let shade = |channel: u8| (channel as f32 * distance_factor * glow_factor).min(255.0) as u8;
+        let color = Color::new(shade(texture_color[0]), shade(texture_color[1]), shade(texture_color[2]), 255);
+        canvas.blend_pixel(screen_x, screen_y, color, texture_color[3]);
     }
 }
 
-fn render_sprites_with_zbuffer_textured(
-    framebuffer: &mut Framebuffer,
-    player: &Player,
-    _maze: &Maze,
-    _block_size: usize,
+#[derive(Clone, Copy)]
+enum BillboardKind {
+    Sprite(usize),
+    Ghost(GhostFrame),
+    Taylor(Vector2),
+}
+
+// Sprites, fantasma y Taylor se ordenan juntos por profundidad para que el más cercano
+// siempre quede encima, sin importar de qué tipo sea.
+fn billboards_by_depth(
+    camera: &Camera,
     sprite_manager: &SpriteManager,
-    z_buffer: &[f32],
+    taylor_positions: &[Vector2],
+    ghost: Option<GhostFrame>,
+) -> Vec<(BillboardKind, f32)> {
+    let depth = |position: Vector2| {
+        let relative = position - camera.position;
+        relative.x * camera.direction.x + relative.y * camera.direction.y
+    };
+
+    let mut billboards: Vec<(BillboardKind, f32)> = sprite_manager
+        .sprites
+        .iter()
+        .enumerate()
+        .filter(|(_, sprite)| !sprite.collected)
+        .map(|(index, sprite)| (BillboardKind::Sprite(index), depth(Vector2::new(sprite.x, sprite.y))))
+        .collect();
+    billboards.extend(ghost.map(|frame| (BillboardKind::Ghost(frame), depth(frame.position()))));
+    billboards.extend(taylor_positions.iter().map(|&position| (BillboardKind::Taylor(position), depth(position))));
+    billboards.sort_by(|a, b| b.1.total_cmp(&a.1));
+    billboards
+}
+
+// El fantasma es una silueta procedural; el tramado en damero lo deja translúcido
+// sin tener que leer de vuelta el framebuffer para mezclar colores. Los ojos siguen el
+// ángulo grabado: solo se ven cuando mira hacia la cámara y se corren hacia donde gira.
+fn render_ghost_billboard(
+    framebuffer: &mut Framebuffer,
+    ghost: GhostFrame,
+    camera: &Camera,
+    texture_manager: &TextureManager,
 ) {
-    let mut sprites_with_distance: Vec<(usize, f32)> = Vec::new();
-    for (index, sprite) in sprite_manager.sprites.iter().enumerate() {
-        if !sprite.collected {
-            let dx = sprite.x - player.pos.x;
-            let dy = sprite.y - player.pos.y;
-            let distance = (dx * dx + dy * dy).sqrt();
-            sprites_with_distance.push((index, distance));
+    let ghost_position = ghost.position();
+    let heading = Vector2::new(ghost.a.cos(), ghost.a.sin());
+    let facing_camera = heading.x * camera.direction.x + heading.y * camera.direction.y < 0.0;
+    let eye_shift = (heading.y * camera.direction.x - heading.x * camera.direction.y) * 0.08;
+    let eye_color = Color::new(20, 40, 70, 255);
+
+    let distance = ghost_position.distance_to(camera.position);
+    if !(20.0..=1000.0).contains(&distance) { return; }
+
+    let Some(billboard) = camera.billboard(ghost_position, camera.wall_height * GHOST_SCALE, GHOST_ASPECT, 0.0) else {
+        return;
+    };
+
+    let distance_factor = (1.0 - (distance / 800.0)).max(0.4);
+    let color = Color::new(
+        (150.0 * distance_factor) as u8,
+        (220.0 * distance_factor) as u8,
+        (255.0 * distance_factor) as u8,
+        255,
+    );
+
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    let (mut canvas, depth_buffer) = framebuffer.viewport_band();
+    for (screen_x, screen_y, tex_x, tex_y) in billboard.pixels(width, height) {
+        if !depth_buffer.is_visible(screen_x, screen_y, billboard.depth, texture_manager) {
+            continue;
+        }
+
+        let tex_x = tex_x - 0.5;
+        let in_head = tex_x * tex_x + (tex_y - 0.15) * (tex_y - 0.15) < 0.02;
+        let in_body = tex_y > 0.28 && tex_x.abs() < 0.3 - (tex_y - 0.28) * 0.1;
+        let eye_x = (tex_x - eye_shift).abs();
+        let in_eye = facing_camera && (0.03..0.08).contains(&eye_x) && (0.11..0.16).contains(&tex_y);
+
+        if in_eye {
+            canvas.set_pixel(screen_x, screen_y, eye_color);
+        } else if (in_head || in_body) && (screen_x + screen_y) % 2 == 0 {
+            canvas.set_pixel(screen_x, screen_y, color);
         }
-    }
-    sprites_with_distance.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-    
-    for (sprite_index, distance) in sprites_with_distance {
-        let sprite = &sprite_manager.sprites[sprite_index];
-        render_single_sprite_with_zbuffer_textured(framebuffer, sprite, player, z_buffer, distance);
     }
 }
 
@@ -208,96 +313,25 @@ pub fn render_world_with_textures_sprites_and_taylor_textured(
     texture_manager: &TextureManager,
     sprite_manager: &SpriteManager,
     taylor_sprite: &TaylorSprite,
-    taylor_position: raylib::prelude::Vector2,
+    taylor_positions: &[Vector2],
+    ghost: Option<GhostFrame>,
 ) {
-    let num_rays = framebuffer.width;
-    let hh = framebuffer.height as f32 / 2.0;
+    let camera = Camera::new(player, framebuffer.width, framebuffer.height, block_size);
+    render_walls(framebuffer, maze, block_size, player, &camera, texture_manager);
     
-    let mut z_buffer = vec![f32::INFINITY; (framebuffer.width * framebuffer.height) as usize];
-    
-    for y in 0..framebuffer.height {
-        for x in 0..framebuffer.width {
-            if y < hh as u32 {
-                // Sky gradient
-                let gradient_factor = y as f32 / hh;
-                framebuffer.set_current_color(Color::new(
-                    (20.0 + gradient_factor * 30.0) as u8,
-                    (20.0 + gradient_factor * 50.0) as u8,
-                    (40.0 + gradient_factor * 80.0) as u8,
-                    255
-                ));
-            } else {
-                let floor_pattern = ((x / 4) + (y / 4)) % 2;
-                if floor_pattern == 0 {
-                    framebuffer.set_current_color(Color::new(40, 20, 20, 255));
-                } else {
-                    framebuffer.set_current_color(Color::new(35, 18, 18, 255));
-                }
+    for (billboard, _) in billboards_by_depth(&camera, sprite_manager, taylor_positions, ghost) {
+        match billboard {
+            BillboardKind::Sprite(index) => {
+                let sprite = &sprite_manager.sprites[index];
+                let distance = Vector2::new(sprite.x, sprite.y).distance_to(camera.position);
+                render_sprite_billboard(framebuffer, sprite, &camera, distance, texture_manager);
             }
-            framebuffer.set_pixel(x, y);
-        }
-    }
-    
-    for i in 0..num_rays {
-        let current_ray = i as f32 / num_rays as f32;
-        let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-        let wall_intersect = cast_ray(framebuffer, &maze, &player, a, block_size, false);
-        
-        if wall_intersect.impact == ' ' {
-            continue;
-        }
-        
-        let corrected_distance = wall_intersect.distance * (a - player.a).cos();
-        
-        let distance_to_projection_plane = 250.0;
-        let stake_height = (hh / corrected_distance) * distance_to_projection_plane;
-        let wall_height_factor = 0.65;
-        let adjusted_stake_height = stake_height * wall_height_factor;
-        let stake_top = (hh - (adjusted_stake_height / 2.0)).max(0.0) as usize;
-        let stake_bottom = (hh + (adjusted_stake_height / 2.0)).min(framebuffer.height as f32) as usize;
-        
-        if stake_bottom > stake_top {
-            let texture = texture_manager.get_texture(wall_intersect.impact);
-            
-            let tex_x = if wall_intersect.side {
-                (wall_intersect.hit_y / block_size as f32).fract()
-            } else {
-                (wall_intersect.hit_x / block_size as f32).fract()
-            };
-            
-            for y in stake_top..stake_bottom {
-                let tex_y = if stake_bottom > stake_top {
-                    (y - stake_top) as f32 / (stake_bottom - stake_top) as f32
-                } else {
-                    0.0
-                };
-                let color = texture.get_color(tex_x, tex_y);
-                
-                let final_color = if wall_intersect.side {
-                    Color::new(
-                        (color[0] as f32 * 0.7) as u8,
-                        (color[1] as f32 * 0.7) as u8,
-                        (color[2] as f32 * 0.7) as u8,
-                        255
-                    )
-                } else {
-                    Color::new(color[0], color[1], color[2], 255)
-                };
-                
-                framebuffer.set_current_color(final_color);
-                framebuffer.set_pixel(i as u32, y as u32);
-                
-                let buffer_index = i as usize + y * framebuffer.width as usize;
-                if buffer_index < z_buffer.len() {
-                    z_buffer[buffer_index] = corrected_distance;
-                }
+            BillboardKind::Ghost(frame) => render_ghost_billboard(framebuffer, frame, &camera, texture_manager),
+            BillboardKind::Taylor(position) => {
+                taylor_sprite.render_3d(framebuffer, position, &camera, texture_manager)
             }
         }
     }
-    
-    render_sprites_with_zbuffer_textured(framebuffer, player, maze, block_size, sprite_manager, &z_buffer);
-    
-    taylor_sprite.render_3d(framebuffer, taylor_position, player, &z_buffer, maze, block_size);
 }
 
 pub fn render_world_with_textures_sprites_and_taylor(
@@ -308,7 +342,8 @@ pub fn render_world_with_textures_sprites_and_taylor(
     texture_manager: &TextureManager,
     sprite_manager: &SpriteManager,
     taylor_sprite: &TaylorSprite,
-    taylor_position: raylib::prelude::Vector2,
+    taylor_positions: &[Vector2],
+    ghost: Option<GhostFrame>,
 ) {
     render_world_with_textures_sprites_and_taylor_textured(
         framebuffer,
@@ -318,6 +353,51 @@ pub fn render_world_with_textures_sprites_and_taylor(
         texture_manager,
         sprite_manager,
         taylor_sprite,
-        taylor_position,
+        taylor_positions,
+        ghost,
     );
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::AssetManager;
+    use crate::texture::TextureFilter;
+
+    fn render_scene(threads: usize) -> Vec<[u8; 4]> {
+        let maze: Maze = ["+++++++", "+  G  +", "+     +", "+ D  E+", "+++++++"]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+        let mut assets = AssetManager::new();
+        let texture_manager = TextureManager::new(&mut assets, TextureFilter::Trilinear);
+        let mut player = Player::new(std::f32::consts::FRAC_PI_3);
+        player.pos = Vector2::new(150.0, 350.0);
+        player.a = -0.6;
+
+        let mut framebuffer = Framebuffer::new(160, 100);
+        framebuffer.set_render_threads(threads);
+        framebuffer.clear();
+        let camera = Camera::new(&player, framebuffer.width, framebuffer.height, 100);
+        render_walls(&mut framebuffer, &maze, 100, &player, &camera, &texture_manager);
+        render_ghost_billboard(&mut framebuffer, GhostFrame { x: 350.0, y: 250.0, a: 2.5 }, &camera, &texture_manager);
+
+        let mut pixels = Vec::new();
+        for y in 0..100 {
+            for x in 0..160 {
+                let color = framebuffer.color_buffer.get_color(x, y);
+                pixels.push([color.r, color.g, color.b, color.a]);
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn threaded_render_matches_single_thread() {
+        let single = render_scene(1);
+        assert!(single.iter().any(|pixel| *pixel != single[0]));
+        for threads in [2, 3, 8] {
+            assert!(render_scene(threads) == single, "{} hilos", threads);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use raylib::prelude::Vector2;
+use crate::inventory::Inventory;
+use crate::maze::Maze;
+use crate::player::{Player, PlayerStatus};
+use crate::sprites::SpriteManager;
+use crate::taylor_ai::TaylorAI;
+use crate::GameData;
+
+pub const CHECKPOINT_RADIUS: f32 = 50.0;
+
+// Estado guardado al tocar un punto de control; reintentar tras ser atrapado vuelve aquí
+// en lugar de recargar el nivel entero.
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    pub index: usize,
+    pub player_positions: Vec<(Vector2, f32)>,
+    pub pickups_collected: Vec<bool>,
+    pub inventories: Vec<Inventory>,
+    pub gasoline_collected: usize,
+    pub taylor_position: Vector2,
+    pub chaser_positions: Vec<Vector2>,
+    pub game_timer: f32,
+    pub endless_time: Option<f32>,
+    // Puertas, palancas y rejas tal como estaban; al volver se deshace lo abierto después.
+    pub maze: Maze,
+}
+
+impl Checkpoint {
+    pub fn capture(index: usize, game_data: &GameData, players: &[Player], maze: &Maze, sprite_manager: &SpriteManager) -> Self {
+        Self {
+            index,
+            player_positions: players.iter().map(|player| (player.pos, player.a)).collect(),
+            pickups_collected: sprite_manager
+                .sprites
+                .iter()
+                .filter(|sprite| sprite.is_pickup())
+                .map(|sprite| sprite.collected)
+                .collect(),
+            inventories: players.iter().map(|player| player.inventory.clone()).collect(),
+            gasoline_collected: game_data.gasoline_collected,
+            taylor_position: game_data.taylor_position,
+            chaser_positions: game_data.extra_chasers.iter().map(|chaser| chaser.position).collect(),
+            game_timer: game_data.game_timer,
+            endless_time: game_data.endless.as_ref().map(|run| run.time_remaining),
+            maze: maze.clone(),
+        }
+    }
+
+    pub fn restore(&self, game_data: &mut GameData, players: &mut [Player], maze: &mut Maze, sprite_manager: &mut SpriteManager) {
+        let checkpoint_position = game_data.checkpoints[self.index];
+        for (index, player) in players.iter_mut().enumerate() {
+            let (pos, a) = self.player_positions.get(index).copied().unwrap_or((checkpoint_position, player.a));
+            player.pos = pos;
+            player.a = a;
+            player.respawn = checkpoint_position;
+            player.status = PlayerStatus::Active;
+            player.lives = game_data.max_lives;
+            player.invulnerable = 0.0;
+            player.stamina = 1.0;
+            player.exhausted = 0.0;
+            player.inventory = self.inventories.get(index).cloned().unwrap_or_default();
+            player.speed_boost = 0.0;
+            player.map_reveal = 0.0;
+        }
+
+        let pickups = sprite_manager.sprites.iter_mut().filter(|sprite| sprite.is_pickup());
+        for (sprite, &collected) in pickups.zip(&self.pickups_collected) {
+            sprite.collected = collected;
+        }
+        sprite_manager.mark_checkpoint(Some(self.index));
+
+        game_data.gasoline_collected = self.gasoline_collected;
+        game_data.car_reached = false;
+        game_data.game_timer = self.game_timer;
+        if let (Some(run), Some(time)) = (&mut game_data.endless, self.endless_time) {
+            run.time_remaining = time;
+        }
+        // Las celdas revertidas se anotan como cambios nuevos para que los clientes en red
+        // también las vuelvan a cerrar.
+        for (y, row) in maze.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                let Some(&saved) = self.maze.get(y).and_then(|row| row.get(x)) else {
+                    continue;
+                };
+                if *cell != saved {
+                    *cell = saved;
+                    game_data.changed_cells.push((x, y, saved));
+                }
+            }
+        }
+        game_data.taylor_position = self.taylor_position;
+        game_data.taylor_target = self.taylor_position;
+        game_data.taylor_ai = TaylorAI::new();
+        for (chaser, &position) in game_data.extra_chasers.iter_mut().zip(&self.chaser_positions) {
+            chaser.position = position;
+            chaser.ai = TaylorAI::new();
+        }
+        game_data.world_effects.clear();
+        sprite_manager.set_world_effects(&[]);
+        game_data.resumed_from_checkpoint = true;
+    }
+}
+
+pub fn touched_checkpoint(checkpoints: &[Vector2], position: Vector2) -> Option<usize> {
+    checkpoints
+        .iter()
+        .position(|checkpoint| checkpoint.distance_to(position) < CHECKPOINT_RADIUS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::AssetManager;
+    use crate::endless::EndlessRun;
+
+    #[test]
+    fn restore_reverts_cells_and_endless_timer() {
+        let mut assets = AssetManager::new();
+        let mut sprite_manager = SpriteManager::new(&mut assets);
+        let mut game_data = GameData::new();
+        game_data.endless = Some(EndlessRun::new(5, 100));
+        game_data.checkpoints = vec![Vector2::new(150.0, 150.0)];
+        let mut players = vec![Player::new(1.0)];
+        let mut maze: Maze = ["+++++", "+ D +", "+++++"].iter().map(|row| row.chars().collect()).collect();
+
+        let checkpoint = Checkpoint::capture(0, &game_data, &players, &maze, &sprite_manager);
+        maze[1][2] = ' ';
+        game_data.changed_cells.push((2, 1, ' '));
+        if let Some(run) = &mut game_data.endless {
+            run.time_remaining = 0.0;
+        }
+
+        checkpoint.restore(&mut game_data, &mut players, &mut maze, &mut sprite_manager);
+        assert_eq!(maze[1][2], 'D');
+        assert_eq!(game_data.changed_cells.last(), Some(&(2, 1, 'D')));
+        assert_eq!(game_data.endless.as_ref().map(|run| run.time_remaining), Some(crate::endless::START_TIME));
+    }
+}
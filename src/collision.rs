@@ -0,0 +1,148 @@
+use raylib::prelude::Vector2;
+use crate::maze::Maze;
+
+pub const PLAYER_RADIUS: f32 = 18.0;
+pub const TAYLOR_RADIUS: f32 = 15.0;
+// La captura se mide aparte de los cuerpos: Taylor atrapa un poco antes de tocar al jugador.
+pub const CATCH_RADIUS: f32 = 40.0;
+
+const RESOLVE_ITERATIONS: usize = 3;
+
+// Las filas del laberinto pueden tener distinto largo; todo lo que cae fuera cuenta como pared.
+pub fn is_walkable(maze: &Maze, grid_x: i32, grid_y: i32) -> bool {
+    if grid_x < 0 || grid_y < 0 {
+        return false;
+    }
+    maze.get(grid_y as usize)
+        .and_then(|row| row.get(grid_x as usize))
+        .is_some_and(|&cell| cell == ' ')
+}
+
+// Recorre las celdas que toca el círculo y devuelve, para cada pared, el punto más cercano al centro.
+fn wall_contacts(maze: &Maze, pos: Vector2, radius: f32, block_size: usize) -> Vec<Vector2> {
+    let bs = block_size as f32;
+    let min_x = ((pos.x - radius) / bs).floor() as i32;
+    let max_x = ((pos.x + radius) / bs).floor() as i32;
+    let min_y = ((pos.y - radius) / bs).floor() as i32;
+    let max_y = ((pos.y + radius) / bs).floor() as i32;
+
+    let mut contacts = Vec::new();
+    for grid_y in min_y..=max_y {
+        for grid_x in min_x..=max_x {
+            if is_walkable(maze, grid_x, grid_y) {
+                continue;
+            }
+            let left = grid_x as f32 * bs;
+            let top = grid_y as f32 * bs;
+            let closest = Vector2::new(pos.x.clamp(left, left + bs), pos.y.clamp(top, top + bs));
+            if closest.distance_to(pos) < radius {
+                contacts.push(closest);
+            }
+        }
+    }
+    contacts
+}
+
+pub fn circle_fits(maze: &Maze, pos: Vector2, radius: f32, block_size: usize) -> bool {
+    wall_contacts(maze, pos, radius, block_size).is_empty()
+}
+
+// Empuja el círculo fuera de las paredes que atraviesa. Falla si el centro quedó dentro de una.
+fn resolve(maze: &Maze, mut pos: Vector2, radius: f32, block_size: usize) -> Option<Vector2> {
+    for _ in 0..RESOLVE_ITERATIONS {
+        let contacts = wall_contacts(maze, pos, radius, block_size);
+        if contacts.is_empty() {
+            return Some(pos);
+        }
+        for closest in contacts {
+            let offset = pos - closest;
+            let distance = offset.length();
+            if distance <= f32::EPSILON {
+                return None;
+            }
+            pos += offset / distance * (radius - distance);
+        }
+    }
+    circle_fits(maze, pos, radius, block_size).then_some(pos)
+}
+
+// Mueve un círculo por el laberinto deslizándolo a lo largo de paredes y esquinas. El movimiento
+// se divide en pasos más cortos que el radio para no atravesar paredes a velocidades altas.
+pub fn move_and_slide(maze: &Maze, pos: Vector2, delta: Vector2, radius: f32, block_size: usize) -> Vector2 {
+    let steps = (delta.length() / (radius * 0.5)).ceil().max(1.0) as usize;
+    let step = delta / steps as f32;
+
+    let mut pos = pos;
+    for _ in 0..steps {
+        let candidates = [
+            pos + step,
+            Vector2::new(pos.x + step.x, pos.y),
+            Vector2::new(pos.x, pos.y + step.y),
+        ];
+        match candidates.iter().find_map(|&candidate| resolve(maze, candidate, radius, block_size)) {
+            Some(next) => pos = next,
+            None => break,
+        }
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK: usize = 100;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    fn corridor() -> Maze {
+        maze(&["+++++++", "+     +", "+     +", "+++++++"])
+    }
+
+    #[test]
+    fn stops_at_radius_from_wall() {
+        let pos = move_and_slide(&corridor(), Vector2::new(150.0, 150.0), Vector2::new(0.0, -200.0), PLAYER_RADIUS, BLOCK);
+
+        assert_eq!(pos.x, 150.0);
+        assert!((pos.y - (100.0 + PLAYER_RADIUS)).abs() < 0.01, "{:?}", pos);
+    }
+
+    #[test]
+    fn slides_along_wall() {
+        let start = Vector2::new(150.0, 100.0 + PLAYER_RADIUS + 1.0);
+        let pos = move_and_slide(&corridor(), start, Vector2::new(40.0, -40.0), PLAYER_RADIUS, BLOCK);
+
+        assert!(pos.x > 185.0, "{:?}", pos);
+        assert!(pos.y >= 100.0 + PLAYER_RADIUS - 0.01, "{:?}", pos);
+    }
+
+    #[test]
+    fn fast_moves_do_not_tunnel_through_walls() {
+        let maze = maze(&["+++++++", "+  +  +", "+  +  +", "+++++++"]);
+        let pos = move_and_slide(&maze, Vector2::new(150.0, 150.0), Vector2::new(400.0, 0.0), TAYLOR_RADIUS, BLOCK);
+
+        assert!(pos.x <= 300.0 - TAYLOR_RADIUS + 0.01, "{:?}", pos);
+        assert!(circle_fits(&maze, pos, TAYLOR_RADIUS, BLOCK));
+    }
+
+    #[test]
+    fn outside_and_ragged_rows_are_walls() {
+        let maze = maze(&["+  ", "+ "]);
+
+        assert!(is_walkable(&maze, 1, 0));
+        assert!(is_walkable(&maze, 2, 0));
+        assert!(!is_walkable(&maze, 2, 1));
+        assert!(!is_walkable(&maze, -1, 0));
+        assert!(!is_walkable(&maze, 0, 5));
+    }
+
+    #[test]
+    fn circle_fits_only_clear_of_walls() {
+        let maze = corridor();
+
+        assert!(circle_fits(&maze, Vector2::new(150.0, 150.0), PLAYER_RADIUS, BLOCK));
+        assert!(!circle_fits(&maze, Vector2::new(150.0, 110.0), PLAYER_RADIUS, BLOCK));
+    }
+}
@@ -0,0 +1,209 @@
+use raylib::prelude::*;
+use crate::input::{Action, Binding, InputMap, ALL_ACTIONS, STICK_AXES};
+use crate::settings::Language;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CaptureMode {
+    Replace,
+    Add,
+}
+
+pub enum ControlsEvent {
+    Close,
+}
+
+pub struct ControlsMenu {
+    pub selected: usize,
+    capture: Option<CaptureMode>,
+}
+
+impl ControlsMenu {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            capture: None,
+        }
+    }
+
+    pub fn update(&mut self, rl: &mut RaylibHandle, controls: &mut InputMap) -> Option<ControlsEvent> {
+        let action = ALL_ACTIONS[self.selected];
+
+        if let Some(mode) = self.capture {
+            if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                self.capture = None;
+                return None;
+            }
+
+            if let Some(binding) = capture_binding(rl, controls.gamepad) {
+                let bindings = controls.bindings.entry(action).or_default();
+                if mode == CaptureMode::Replace {
+                    bindings.clear();
+                }
+                if !bindings.contains(&binding) {
+                    bindings.push(binding);
+                }
+                println!("Acción {} asignada a {}", action.config_name(), binding.name());
+                self.capture = None;
+            }
+            return None;
+        }
+
+        // Teclas y botones fijos, igual que en el menú de opciones: si dependieran del mapa de
+        // controles, una asignación equivocada podría dejar este menú inutilizable.
+        let gamepad = rl.is_gamepad_available(controls.gamepad);
+        let pressed = |key: KeyboardKey, button: GamepadButton| {
+            rl.is_key_pressed(key) || (gamepad && rl.is_gamepad_button_pressed(controls.gamepad, button))
+        };
+
+        if pressed(KeyboardKey::KEY_BACKSPACE, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT) {
+            return Some(ControlsEvent::Close);
+        }
+        if pressed(KeyboardKey::KEY_DOWN, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN) {
+            self.selected = (self.selected + 1) % ALL_ACTIONS.len();
+        }
+        if pressed(KeyboardKey::KEY_UP, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP) {
+            self.selected = (self.selected + ALL_ACTIONS.len() - 1) % ALL_ACTIONS.len();
+        }
+        if pressed(KeyboardKey::KEY_ENTER, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN) {
+            self.capture = Some(CaptureMode::Replace);
+        }
+        if pressed(KeyboardKey::KEY_TAB, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT) {
+            self.capture = Some(CaptureMode::Add);
+        }
+        if pressed(KeyboardKey::KEY_DELETE, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP) {
+            controls.bindings.insert(action, Vec::new());
+        }
+        if pressed(KeyboardKey::KEY_F5, GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT) {
+            controls.bindings = InputMap::default().bindings;
+        }
+
+        None
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle, controls: &InputMap, lang: Language, width: i32, height: i32) {
+        let center_x = width / 2;
+
+        d.draw_text(lang.pick("CONTROLES", "CONTROLS"), center_x - 120, 50, 48, Color::WHITE);
+
+        // Hay más acciones de las que caben: se muestra una ventana de filas centrada en la
+        // seleccionada, que termina sobre la línea de ayuda.
+        let row_height = 30;
+        let visible_rows = ((height - 210) / row_height).max(1) as usize;
+        let first = self
+            .selected
+            .saturating_sub(visible_rows / 2)
+            .min(ALL_ACTIONS.len().saturating_sub(visible_rows));
+
+        if first > 0 {
+            d.draw_text("...", center_x - 380, 105, 20, Color::GRAY);
+        }
+        if first + visible_rows < ALL_ACTIONS.len() {
+            d.draw_text("...", center_x - 380, 130 + visible_rows as i32 * row_height, 20, Color::GRAY);
+        }
+
+        for (index, action) in ALL_ACTIONS.iter().enumerate().skip(first).take(visible_rows) {
+            let y = 130 + (index - first) as i32 * row_height;
+            let selected = index == self.selected;
+            let color = if selected { Color::YELLOW } else { Color::LIGHTGRAY };
+
+            let bindings = if selected && self.capture.is_some() {
+                lang.pick("Presiona una tecla, botón o stick...", "Press a key, button or stick...").to_string()
+            } else {
+                let names: Vec<String> = controls.bindings_for(*action).iter().map(Binding::name).collect();
+                if names.is_empty() { "-".to_string() } else { names.join(", ") }
+            };
+
+            if selected {
+                d.draw_text(">", center_x - 420, y, 24, color);
+            }
+            d.draw_text(action_label(*action, lang), center_x - 380, y, 24, color);
+            d.draw_text(&bindings, center_x - 80, y, 24, color);
+        }
+
+        d.draw_text(
+            lang.pick(
+                "Enter/A - Reasignar, Tab/X - Añadir, Supr/Y - Borrar",
+                "Enter/A - Rebind, Tab/X - Add, Del/Y - Clear",
+            ),
+            center_x - 380,
+            height - 60,
+            20,
+            Color::GRAY,
+        );
+        d.draw_text(
+            lang.pick(
+                "F5/Select - Por defecto, Retroceso/B - Volver",
+                "F5/Select - Defaults, Backspace/B - Back",
+            ),
+            center_x - 380,
+            height - 35,
+            20,
+            Color::GRAY,
+        );
+    }
+}
+
+fn capture_binding(rl: &mut RaylibHandle, gamepad: i32) -> Option<Binding> {
+    if let Some(key) = rl.get_key_pressed() {
+        return Some(Binding::Key(key));
+    }
+
+    if !rl.is_gamepad_available(gamepad) {
+        return None;
+    }
+
+    if let Some(button) = rl.get_gamepad_button_pressed() {
+        return Some(Binding::Button(button));
+    }
+
+    STICK_AXES.iter().find_map(|(axis, _)| {
+        let value = rl.get_gamepad_axis_movement(gamepad, *axis);
+        if value.abs() > 0.6 {
+            Some(Binding::Axis(*axis, value.signum()))
+        } else {
+            None
+        }
+    })
+}
+
+fn action_label(action: Action, lang: Language) -> &'static str {
+    match action {
+        Action::MoveForward => lang.pick("Avanzar", "Move forward"),
+        Action::MoveBackward => lang.pick("Retroceder", "Move backward"),
+        Action::StrafeLeft => lang.pick("Paso izquierda", "Strafe left"),
+        Action::StrafeRight => lang.pick("Paso derecha", "Strafe right"),
+        Action::TurnLeft => lang.pick("Girar izquierda", "Turn left"),
+        Action::TurnRight => lang.pick("Girar derecha", "Turn right"),
+        Action::Interact => lang.pick("Interactuar", "Interact"),
+        Action::Sprint => lang.pick("Correr", "Sprint"),
+        Action::NextItem => lang.pick("Cambiar objeto", "Next item"),
+        Action::UseItem => lang.pick("Usar objeto", "Use item"),
+        Action::Pause => lang.pick("Pausa", "Pause"),
+        Action::Continue => lang.pick("Continuar", "Continue"),
+        Action::Retry => lang.pick("Reintentar", "Retry"),
+        Action::Level1 => lang.pick("Nivel 1", "Level 1"),
+        Action::Level2 => lang.pick("Nivel 2", "Level 2"),
+        Action::Level3 => lang.pick("Nivel 3", "Level 3"),
+        Action::Back => lang.pick("Volver / Salir", "Back / Leave"),
+        Action::BackToMenu => lang.pick("Volver al menú", "Back to menu"),
+        Action::RestartLevel => lang.pick("Reiniciar nivel", "Restart level"),
+        Action::SaveReplay => lang.pick("Guardar repetición", "Save replay"),
+        Action::OpenSettings => lang.pick("Opciones", "Settings"),
+        Action::SwitchProfile => lang.pick("Cambiar perfil", "Switch profile"),
+        Action::NewProfile => lang.pick("Nuevo perfil", "New profile"),
+        Action::UnlockAll => lang.pick("Desbloquear todo", "Unlock all"),
+        Action::CycleDifficulty => lang.pick("Dificultad", "Difficulty"),
+        Action::ToggleCoop => lang.pick("Cooperativo", "Co-op"),
+        Action::ToggleVersus => lang.pick("Versus", "Versus"),
+        Action::HostLan => lang.pick("Hospedar LAN", "Host LAN"),
+        Action::JoinLan => lang.pick("Unirse a LAN", "Join LAN"),
+        Action::EndlessMode => lang.pick("Modo infinito", "Endless mode"),
+        Action::WatchReplay => lang.pick("Ver repetición", "Watch replay"),
+        Action::ReplayPause => lang.pick("Repetición: pausa", "Replay: pause"),
+        Action::ReplayFaster => lang.pick("Repetición: más rápido", "Replay: faster"),
+        Action::ReplaySlower => lang.pick("Repetición: más lento", "Replay: slower"),
+        Action::ReplaySeekBack => lang.pick("Repetición: -5s", "Replay: -5s"),
+        Action::ReplaySeekForward => lang.pick("Repetición: +5s", "Replay: +5s"),
+        Action::ReplayRestart => lang.pick("Repetición: reiniciar", "Replay: restart"),
+    }
+}
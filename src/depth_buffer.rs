@@ -0,0 +1,59 @@
+use crate::texture::{TextureFilter, TextureManager};
+
+// Una pared que ve el rayo de una columna, ya proyectada en pantalla. Las translúcidas (reja,
+// vidrio) quedan delante de la opaca que cierra la columna.
+#[derive(Clone, Copy, Debug)]
+pub struct WallSlice {
+    pub depth: f32,
+    pub top: f32,
+    pub height: f32,
+    pub tex_x: f32,
+    pub impact: char,
+    pub side: bool,
+    pub translucent: bool,
+}
+
+// Profundidad por columna de pantalla, siempre medida perpendicular al plano de cámara. Se
+// guardan las paredes que ve cada columna, así un sprite se recorta píxel a píxel contra bordes
+// de pared y barrotes sin un buffer por píxel. Vive dentro del framebuffer y se reutiliza de un
+// cuadro a otro sin volver a reservar memoria.
+#[derive(Clone, Debug, Default)]
+pub struct DepthBuffer {
+    columns: Vec<Vec<WallSlice>>,
+}
+
+impl DepthBuffer {
+    pub fn reset(&mut self, width: u32) {
+        self.columns.resize_with(width as usize, Vec::new);
+        for column in &mut self.columns {
+            column.clear();
+        }
+    }
+
+    pub fn columns(&self) -> &[Vec<WallSlice>] {
+        &self.columns
+    }
+
+    pub fn columns_mut(&mut self) -> &mut [Vec<WallSlice>] {
+        &mut self.columns
+    }
+
+    pub fn is_visible(&self, column: u32, y: u32, depth: f32, texture_manager: &TextureManager) -> bool {
+        let Some(slices) = self.columns.get(column as usize) else {
+            return false;
+        };
+        let center_y = y as f32 + 0.5;
+
+        !slices.iter().any(|slice| {
+            let tex_y = (center_y - slice.top) / slice.height;
+            if !(0.0..1.0).contains(&tex_y) {
+                return false;
+            }
+            if !slice.translucent {
+                return depth >= slice.depth;
+            }
+            slice.depth < depth
+                && texture_manager.get_texture(slice.impact).sample(slice.tex_x, tex_y, 1.0, TextureFilter::Nearest)[3] >= 128
+        })
+    }
+}
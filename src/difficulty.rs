@@ -0,0 +1,82 @@
+use crate::settings::Language;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SprintTuning {
+    pub speed_multiplier: f32,
+    pub drain_per_second: f32,
+    pub regen_per_second: f32,
+    pub exhaustion_time: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn label(self, lang: Language) -> &'static str {
+        match self {
+            Difficulty::Easy => lang.pick("Fácil", "Easy"),
+            Difficulty::Normal => lang.pick("Normal", "Normal"),
+            Difficulty::Hard => lang.pick("Difícil", "Hard"),
+        }
+    }
+
+    pub fn config_name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn from_config_name(name: &str) -> Option<Self> {
+        match name {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn taylor_speed_multiplier(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.85,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.2,
+        }
+    }
+
+    pub fn sprint_tuning(self) -> SprintTuning {
+        match self {
+            Difficulty::Easy => SprintTuning {
+                speed_multiplier: 1.7,
+                drain_per_second: 0.25,
+                regen_per_second: 0.2,
+                exhaustion_time: 1.0,
+            },
+            Difficulty::Normal => SprintTuning {
+                speed_multiplier: 1.6,
+                drain_per_second: 0.33,
+                regen_per_second: 0.15,
+                exhaustion_time: 1.5,
+            },
+            Difficulty::Hard => SprintTuning {
+                speed_multiplier: 1.5,
+                drain_per_second: 0.45,
+                regen_per_second: 0.12,
+                exhaustion_time: 2.0,
+            },
+        }
+    }
+}
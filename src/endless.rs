@@ -0,0 +1,292 @@
+use std::collections::VecDeque;
+use raylib::prelude::Vector2;
+use crate::maze::Maze;
+use crate::rng::Rng;
+use crate::GameLevel;
+
+pub const START_TIME: f32 = 90.0;
+const MAX_CHASERS: usize = 6;
+const MAX_CANS: usize = 12;
+
+pub struct EndlessRun {
+    pub seed: u64,
+    pub stage: usize,
+    pub time_remaining: f32,
+    pub maze: Maze,
+    pub(crate) level: GameLevel,
+    pub chaser_spawns: Vec<Vector2>,
+}
+
+pub fn time_bonus(stage: usize) -> f32 {
+    (45.0 - 3.0 * (stage as f32 - 1.0)).max(15.0)
+}
+
+pub fn chaser_count(stage: usize) -> usize {
+    (1 + stage.saturating_sub(1) / 2).min(MAX_CHASERS)
+}
+
+pub fn can_count(stage: usize) -> usize {
+    (3 + stage).min(MAX_CANS)
+}
+
+pub fn chaser_speed(stage: usize) -> f32 {
+    (2.6 + 0.2 * (stage as f32 - 1.0)).min(5.0)
+}
+
+pub fn parse_seed(text: &str) -> Option<u64> {
+    let text = text.trim();
+    text.parse()
+        .ok()
+        .or_else(|| u64::from_str_radix(text.trim_start_matches("0x"), 16).ok())
+}
+
+impl EndlessRun {
+    pub fn new(seed: u64, block_size: usize) -> Self {
+        Self::at_stage(seed, 1, START_TIME, block_size)
+    }
+
+    pub fn at_stage(seed: u64, stage: usize, time_remaining: f32, block_size: usize) -> Self {
+        let (maze, level, chaser_spawns) = generate_stage(seed, stage, block_size);
+        Self {
+            seed,
+            stage,
+            time_remaining,
+            maze,
+            level,
+            chaser_spawns,
+        }
+    }
+
+    pub fn advance(&mut self, block_size: usize) {
+        self.time_remaining += time_bonus(self.stage);
+        self.stage += 1;
+
+        let (maze, level, chaser_spawns) = generate_stage(self.seed, self.stage, block_size);
+        self.maze = maze;
+        self.level = level;
+        self.chaser_spawns = chaser_spawns;
+    }
+
+    pub fn stages_survived(&self) -> usize {
+        self.stage.saturating_sub(1)
+    }
+}
+
+fn cell_center(cell: (usize, usize), block_size: usize) -> Vector2 {
+    Vector2::new(
+        (cell.0 * block_size) as f32 + block_size as f32 / 2.0,
+        (cell.1 * block_size) as f32 + block_size as f32 / 2.0,
+    )
+}
+
+// Distancia en pasos desde (1, 1) a cada casilla libre del laberinto.
+fn flood_distances(maze: &Maze) -> Vec<((usize, usize), usize)> {
+    let mut distances = vec![vec![usize::MAX; maze[0].len()]; maze.len()];
+    let mut queue = VecDeque::from([(1usize, 1usize)]);
+    distances[1][1] = 0;
+    let mut cells = Vec::new();
+
+    while let Some((x, y)) = queue.pop_front() {
+        let distance = distances[y][x];
+        cells.push(((x, y), distance));
+        for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+            if maze[ny][nx] == ' ' && distances[ny][nx] == usize::MAX {
+                distances[ny][nx] = distance + 1;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    cells
+}
+
+// Backtracker recursivo sobre una rejilla de celdas impares, más unas cuantas paredes
+// abiertas al azar para que haya bucles y los perseguidores no bloqueen el único camino.
+fn generate_stage(seed: u64, stage: usize, block_size: usize) -> (Maze, GameLevel, Vec<Vector2>) {
+    let mut rng = Rng::new(seed ^ (stage as u64).wrapping_mul(0xD1B54A32D192ED03));
+
+    let cols = (10 + stage).min(30);
+    let rows = (7 + stage / 2).min(16);
+    let width = cols * 2 + 1;
+    let height = rows * 2 + 1;
+    let mut maze: Maze = vec![vec!['+'; width]; height];
+
+    let mut visited = vec![vec![false; cols]; rows];
+    let mut stack = vec![(0usize, 0usize)];
+    visited[0][0] = true;
+    maze[1][1] = ' ';
+
+    while let Some(&(cx, cy)) = stack.last() {
+        let mut neighbors = Vec::new();
+        if cx > 0 && !visited[cy][cx - 1] { neighbors.push((cx - 1, cy)); }
+        if cx + 1 < cols && !visited[cy][cx + 1] { neighbors.push((cx + 1, cy)); }
+        if cy > 0 && !visited[cy - 1][cx] { neighbors.push((cx, cy - 1)); }
+        if cy + 1 < rows && !visited[cy + 1][cx] { neighbors.push((cx, cy + 1)); }
+
+        if neighbors.is_empty() {
+            stack.pop();
+            continue;
+        }
+
+        let (nx, ny) = neighbors[rng.range(neighbors.len())];
+        visited[ny][nx] = true;
+        maze[ny * 2 + 1][nx * 2 + 1] = ' ';
+        maze[cy + ny + 1][cx + nx + 1] = ' ';
+        stack.push((nx, ny));
+    }
+
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let horizontal = maze[y][x - 1] == ' ' && maze[y][x + 1] == ' ';
+            let vertical = maze[y - 1][x] == ' ' && maze[y + 1][x] == ' ';
+            if maze[y][x] == '+' && (horizontal ^ vertical) && rng.chance(0.08) {
+                maze[y][x] = ' ';
+            }
+        }
+    }
+
+    let mut cells = flood_distances(&maze);
+    cells.retain(|((x, y), _)| x % 2 == 1 && y % 2 == 1);
+    let max_distance = cells.last().map_or(0, |(_, distance)| *distance);
+
+    let exit_cell = cells.last().map_or((1, 1), |(cell, _)| *cell);
+    if let Some((wall_x, wall_y)) = [
+        (exit_cell.0 + 1, exit_cell.1),
+        (exit_cell.0 - 1, exit_cell.1),
+        (exit_cell.0, exit_cell.1 + 1),
+        (exit_cell.0, exit_cell.1 - 1),
+    ]
+    .into_iter()
+    .find(|&(x, y)| maze[y][x] == '+')
+    {
+        maze[wall_y][wall_x] = 'E';
+    }
+    maze[exit_cell.1][exit_cell.0] = 'A';
+
+    let mut far_cells: Vec<(usize, usize)> = cells
+        .iter()
+        .filter(|(cell, distance)| *distance * 2 >= max_distance && *cell != exit_cell)
+        .map(|(cell, _)| *cell)
+        .collect();
+    let mut chaser_spawns = Vec::new();
+    for _ in 0..chaser_count(stage) {
+        if far_cells.is_empty() {
+            break;
+        }
+        let cell = far_cells.swap_remove(rng.range(far_cells.len()));
+        chaser_spawns.push(cell_center(cell, block_size));
+    }
+
+    let mut can_cells: Vec<(usize, usize)> = cells
+        .iter()
+        .filter(|(cell, distance)| *distance >= 4 && *cell != exit_cell)
+        .map(|(cell, _)| *cell)
+        .collect();
+    let mut cans_placed = 0;
+    for _ in 0..can_count(stage) {
+        if can_cells.is_empty() {
+            break;
+        }
+        let (x, y) = can_cells.swap_remove(rng.range(can_cells.len()));
+        maze[y][x] = '.';
+        cans_placed += 1;
+    }
+
+    let taylor_spawn = chaser_spawns.first().copied().unwrap_or_else(|| cell_center((width - 2, height - 2), block_size));
+    let exit_position = cell_center(exit_cell, block_size);
+
+    let level = GameLevel {
+        maze_file: String::new(),
+        level_name: format!("Endless Stage {}", stage),
+        required_cans: cans_placed,
+        taylor_speed: chaser_speed(stage),
+        taylor_spawn_x: taylor_spawn.x,
+        taylor_spawn_y: taylor_spawn.y,
+        exit_x: exit_position.x,
+        exit_y: exit_position.y,
+    };
+
+    let extra_spawns = chaser_spawns.into_iter().skip(1).collect();
+    (maze, level, extra_spawns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK: usize = 100;
+
+    fn reachable(maze: &Maze) -> Vec<Vec<bool>> {
+        let mut seen = vec![vec![false; maze[0].len()]; maze.len()];
+        let mut queue = VecDeque::from([(1usize, 1usize)]);
+        seen[1][1] = true;
+        while let Some((x, y)) = queue.pop_front() {
+            for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                if !matches!(maze[ny][nx], '+' | 'E') && !seen[ny][nx] {
+                    seen[ny][nx] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn same_seed_generates_same_stage() {
+        let first = EndlessRun::at_stage(1234, 3, START_TIME, BLOCK);
+        let second = EndlessRun::at_stage(1234, 3, START_TIME, BLOCK);
+
+        assert_eq!(first.maze, second.maze);
+        assert_eq!(first.chaser_spawns, second.chaser_spawns);
+        assert_eq!((first.level.exit_x, first.level.exit_y), (second.level.exit_x, second.level.exit_y));
+        assert_ne!(first.maze, EndlessRun::at_stage(1235, 3, START_TIME, BLOCK).maze);
+    }
+
+    #[test]
+    fn every_open_cell_is_reachable() {
+        for seed in 0..20 {
+            for stage in [1, 5, 25] {
+                let maze = EndlessRun::at_stage(seed, stage, START_TIME, BLOCK).maze;
+                let seen = reachable(&maze);
+                for (y, row) in maze.iter().enumerate() {
+                    for (x, &cell) in row.iter().enumerate() {
+                        assert!(matches!(cell, '+' | 'E') || seen[y][x], "semilla {} etapa {}: ({}, {})", seed, stage, x, y);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stage_has_exit_cans_and_chasers() {
+        let run = EndlessRun::at_stage(42, 4, START_TIME, BLOCK);
+        let count = |glyph: char| run.maze.iter().flatten().filter(|&&cell| cell == glyph).count();
+
+        assert_eq!(count('E'), 1);
+        assert_eq!(count('A'), 1);
+        assert_eq!(count('.'), run.level.required_cans);
+        assert_eq!(run.level.required_cans, can_count(4));
+        assert_eq!(run.chaser_spawns.len() + 1, chaser_count(4));
+        for spawn in &run.chaser_spawns {
+            let (x, y) = (spawn.x as usize / BLOCK, spawn.y as usize / BLOCK);
+            assert_ne!(run.maze[y][x], '+');
+        }
+    }
+
+    #[test]
+    fn advance_adds_time_bonus() {
+        let mut run = EndlessRun::new(7, BLOCK);
+        run.advance(BLOCK);
+
+        assert_eq!(run.stage, 2);
+        assert_eq!(run.stages_survived(), 1);
+        assert_eq!(run.time_remaining, START_TIME + time_bonus(1));
+    }
+
+    #[test]
+    fn parses_decimal_and_hex_seeds() {
+        assert_eq!(parse_seed(" 1234 "), Some(1234));
+        assert_eq!(parse_seed("0xff"), Some(255));
+        assert_eq!(parse_seed("beef"), Some(0xbeef));
+        assert_eq!(parse_seed("semilla"), None);
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs;
+use crate::config::{parse_bool, parse_key_values};
+use crate::inventory::ItemKind;
+
+pub const ENTITIES_FILE: &str = "assets/entities.txt";
+const DEFAULT_ENTITIES: &str = include_str!("../assets/entities.txt");
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PickupEffect {
+    None,
+    Gasoline,
+    Escape,
+    Item(ItemKind),
+}
+
+impl PickupEffect {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "none" => Some(PickupEffect::None),
+            "gasoline" => Some(PickupEffect::Gasoline),
+            "escape" => Some(PickupEffect::Escape),
+            _ => value.strip_prefix("item:").and_then(ItemKind::from_config_name).map(PickupEffect::Item),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EntityDef {
+    pub name: String,
+    pub glyph: Option<char>,
+    pub frames: Vec<String>,
+    pub animation_speed: f32,
+    pub scale: f32,
+    pub vertical_offset: f32,
+    pub pickup_radius: f32,
+    pub on_pickup: PickupEffect,
+    pub interact: bool,
+}
+
+impl EntityDef {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            glyph: None,
+            frames: Vec::new(),
+            animation_speed: 0.0,
+            scale: 1.0,
+            vertical_offset: 0.0,
+            pickup_radius: 0.0,
+            on_pickup: PickupEffect::None,
+            interact: false,
+        }
+    }
+
+    fn apply_entry(&mut self, key: &str, value: &str) -> bool {
+        match key {
+            "glyph" => value.chars().next().map(|glyph| self.glyph = Some(glyph)).is_some(),
+            "frames" => {
+                self.frames = value.split(',').map(|frame| frame.trim().to_string()).filter(|frame| !frame.is_empty()).collect();
+                true
+            }
+            "animation_speed" => value.parse().map(|v: f32| self.animation_speed = v.max(0.0)).is_ok(),
+            "scale" => value.parse().map(|v: f32| self.scale = v.max(0.05)).is_ok(),
+            "vertical_offset" => value.parse().map(|v| self.vertical_offset = v).is_ok(),
+            "pickup_radius" => value.parse().map(|v: f32| self.pickup_radius = v.max(0.0)).is_ok(),
+            "on_pickup" => PickupEffect::parse(value).map(|effect| self.on_pickup = effect).is_some(),
+            "interact" => parse_bool(value).map(|v| self.interact = v).is_some(),
+            _ => false,
+        }
+    }
+
+    pub fn is_pickup(&self) -> bool {
+        self.on_pickup != PickupEffect::None
+    }
+}
+
+// Secciones "[nombre]" seguidas de líneas "clave = valor", como el resto de archivos del juego.
+pub fn parse_entities(contents: &str) -> Vec<EntityDef> {
+    let mut definitions: Vec<EntityDef> = Vec::new();
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            definitions.push(EntityDef::new(name.trim()));
+            continue;
+        }
+        let Some(definition) = definitions.last_mut() else {
+            continue;
+        };
+        for (key, value) in parse_key_values(line) {
+            if !definition.apply_entry(&key, &value) {
+                println!("Entidad {}: valor inválido para {} ({})", definition.name, key, value);
+            }
+        }
+    }
+    definitions.retain(|definition| {
+        if definition.frames.is_empty() {
+            println!("Entidad {} sin texturas, se ignora", definition.name);
+        }
+        !definition.frames.is_empty()
+    });
+    definitions
+}
+
+pub fn load_entities() -> Vec<EntityDef> {
+    match fs::read_to_string(ENTITIES_FILE) {
+        Ok(contents) => {
+            let definitions = parse_entities(&contents);
+            println!("Entidades cargadas de {}: {}", ENTITIES_FILE, definitions.len());
+            definitions
+        }
+        Err(e) => {
+            println!("Error cargando {}: {}, usando las entidades por defecto", ENTITIES_FILE, e);
+            parse_entities(DEFAULT_ENTITIES)
+        }
+    }
+}
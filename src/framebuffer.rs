@@ -1,11 +1,67 @@
 use raylib::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use crate::depth_buffer::{DepthBuffer, WallSlice};
 
+// `width` y `height` son las del viewport activo: el raycaster dibuja siempre en
+// coordenadas locales y set_pixel las desplaza a su mitad de pantalla en pantalla dividida.
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
     pub color_buffer: Image,
+    pub depth_buffer: DepthBuffer,
+    full_width: u32,
+    full_height: u32,
+    viewport_x: u32,
+    viewport_y: u32,
     background_color: Color,
-    current_color: Color,
+    render_threads: usize,
+    // Hilos que viven mientras viva el framebuffer; con un solo hilo no se crea y todo se
+    // dibuja en el hilo principal.
+    pool: Option<ThreadPool>,
+}
+
+// Un grupo de filas completas del viewport, separado del resto para que un hilo pueda
+// dibujarlo sin compartir píxeles con los demás.
+pub struct RowBand<'a> {
+    pub first_row: u32,
+    pub width: u32,
+    pixels: &'a mut [Color],
+    stride: usize,
+    viewport_x: usize,
+}
+
+impl RowBand<'_> {
+    pub fn rows(&self) -> std::ops::Range<u32> {
+        self.first_row..self.first_row + (self.pixels.len() / self.stride) as u32
+    }
+
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> &mut Color {
+        &mut self.pixels[(y - self.first_row) as usize * self.stride + self.viewport_x + x as usize]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if x < self.width && self.rows().contains(&y) {
+            *self.pixel_mut(x, y) = color;
+        }
+    }
+
+    // Mezcla `color` con lo que ya hay en el píxel según `alpha` (0 a 255).
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: Color, alpha: u8) {
+        if alpha != 0 && x < self.width && self.rows().contains(&y) {
+            let pixel = self.pixel_mut(x, y);
+            *pixel = blend(color, *pixel, alpha);
+        }
+    }
+}
+
+// Mezcla `top` sobre `bottom` según `alpha` (0 a 255).
+pub fn blend(top: Color, bottom: Color, alpha: u8) -> Color {
+    if alpha == 255 {
+        return top;
+    }
+    let t = alpha as f32 / 255.0;
+    let mix = |top: u8, bottom: u8| (top as f32 * t + bottom as f32 * (1.0 - t)) as u8;
+    Color::new(mix(top.r, bottom.r), mix(top.g, bottom.g), mix(top.b, bottom.b), 255)
 }
 
 impl Framebuffer {
@@ -16,35 +72,167 @@ impl Framebuffer {
             width,
             height,
             color_buffer,
+            depth_buffer: DepthBuffer::default(),
+            full_width: width,
+            full_height: height,
+            viewport_x: 0,
+            viewport_y: 0,
             background_color: Color::BLACK,
-            current_color: Color::WHITE,
+            render_threads: 1,
+            pool: None,
         }
     }
 
     pub fn clear(&mut self) {
-        self.color_buffer = Image::gen_image_color(self.width as i32, self.height as i32, self.background_color);
+        let color = self.background_color;
+        pixels_mut(&mut self.color_buffer, self.full_width, self.full_height).fill(color);
     }
 
-    pub fn set_pixel(&mut self, x: u32, y: u32) {
-        if x < self.width && y < self.height {
-            self.color_buffer.draw_pixel(x as i32, y as i32, self.current_color);
+    // 0 usa todos los núcleos disponibles.
+    pub fn set_render_threads(&mut self, threads: usize) {
+        self.render_threads = threads;
+        let threads = self.render_threads();
+        if self.pool.as_ref().map_or(1, |pool| pool.current_num_threads()) == threads {
+            return;
         }
+        self.pool = if threads > 1 {
+            ThreadPoolBuilder::new().num_threads(threads).build().ok()
+        } else {
+            None
+        };
     }
 
-    pub fn set_background_color(&mut self, color: Color) {
-        self.background_color = color;
+    pub fn render_threads(&self) -> usize {
+        match self.render_threads {
+            0 => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            threads => threads,
+        }
+    }
+
+    // Vacía la profundidad y llena las columnas del viewport con `project`, repartidas en
+    // tramos entre los hilos del pool.
+    pub fn project_columns<F>(&mut self, project: F)
+    where
+        F: Fn(u32, &mut Vec<WallSlice>) + Sync,
+    {
+        self.depth_buffer.reset(self.width);
+        let threads = self.render_threads();
+        run_chunks(self.pool.as_ref(), self.depth_buffer.columns_mut(), threads, 1, |first, columns| {
+            for (offset, column) in columns.iter_mut().enumerate() {
+                project((first + offset) as u32, column);
+            }
+        });
+    }
+
+    // Parte las filas del viewport en bandas disjuntas, una por hilo, y dibuja cada una con
+    // `draw` leyendo las columnas ya proyectadas.
+    pub fn draw_rows<F>(&mut self, draw: F)
+    where
+        F: Fn(&mut RowBand, &DepthBuffer) + Sync,
+    {
+        let stride = self.full_width as usize;
+        let viewport_x = self.viewport_x as usize;
+        let width = self.width;
+        let threads = self.render_threads();
+        let depth_buffer = &self.depth_buffer;
+        let start = self.viewport_y as usize * stride;
+        let end = (self.viewport_y + self.height) as usize * stride;
+        let pixels = &mut pixels_mut(&mut self.color_buffer, self.full_width, self.full_height)[start..end];
+
+        run_chunks(self.pool.as_ref(), pixels, threads, stride, |first_row, pixels| {
+            let mut band = RowBand { first_row: first_row as u32, width, pixels, stride, viewport_x };
+            draw(&mut band, depth_buffer);
+        });
+    }
+
+    // Todo el viewport como una sola banda, para que los sprites dibujen sin rehacer el acceso
+    // a los píxeles en cada uno.
+    pub fn viewport_band(&mut self) -> (RowBand<'_>, &DepthBuffer) {
+        let stride = self.full_width as usize;
+        let start = self.viewport_y as usize * stride;
+        let end = (self.viewport_y + self.height) as usize * stride;
+        let band = RowBand {
+            first_row: 0,
+            width: self.width,
+            pixels: &mut pixels_mut(&mut self.color_buffer, self.full_width, self.full_height)[start..end],
+            stride,
+            viewport_x: self.viewport_x as usize,
+        };
+        (band, &self.depth_buffer)
     }
 
-    pub fn set_current_color(&mut self, color: Color) {
-        self.current_color = color;
+    pub fn set_viewport(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        self.viewport_x = x.min(self.full_width);
+        self.viewport_y = y.min(self.full_height);
+        self.width = width.min(self.full_width - self.viewport_x);
+        self.height = height.min(self.full_height - self.viewport_y);
+    }
+
+    pub fn reset_viewport(&mut self) {
+        self.set_viewport(0, 0, self.full_width, self.full_height);
+    }
+
+    pub fn full_size(&self) -> (i32, i32) {
+        (self.full_width as i32, self.full_height as i32)
+    }
+
+    pub fn set_background_color(&mut self, color: Color) {
+        self.background_color = color;
     }
 
     pub fn _render_to_file(&self, file_path: &str) {
         self.color_buffer.export_image(file_path);
     }
 
-    pub fn get_texture(&self, window: &mut RaylibHandle, raylib_thread: &RaylibThread) -> Result<Texture2D, String> {
-        window.load_texture_from_image(raylib_thread, &self.color_buffer)
-            .map_err(|_| "Failed to create texture".to_string())
+    // `smooth` elige el filtro con que se estira la imagen si la resolución interna es menor.
+    pub fn get_texture(&self, window: &mut RaylibHandle, raylib_thread: &RaylibThread, smooth: bool) -> Result<Texture2D, String> {
+        let mut texture = window.load_texture_from_image(raylib_thread, &self.color_buffer)
+            .map_err(|_| "Failed to create texture".to_string())?;
+        let filter = if smooth { TextureFilter::TEXTURE_FILTER_BILINEAR } else { TextureFilter::TEXTURE_FILTER_POINT };
+        texture.set_texture_filter(raylib_thread, filter);
+        Ok(texture)
     }
+}
+
+fn pixels_mut(image: &mut Image, width: u32, height: u32) -> &mut [Color] {
+    debug_assert!(matches!(image.format(), PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8));
+    debug_assert_eq!((image.width(), image.height()), (width as i32, height as i32));
+    let len = (width * height) as usize;
+    // SAFETY: gen_image_color reserva `width * height` píxeles en R8G8B8A8, que es la misma
+    // disposición que Color (repr(C), cuatro u8). El slice toma prestada la imagen en exclusiva,
+    // así que nadie la reemplaza ni la libera mientras se usa.
+    unsafe { std::slice::from_raw_parts_mut(image.data() as *mut Color, len) }
+}
+
+// Reparte `items` en hasta `count` tramos contiguos de `unit` elementos enteros y llama a
+// `job(primera_unidad, tramo)` por cada uno en el pool. Sin pool todo va de una vez en este
+// hilo, y como cada unidad se calcula por separado el resultado es el mismo.
+fn run_chunks<T, F>(pool: Option<&ThreadPool>, items: &mut [T], count: usize, unit: usize, job: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Sync,
+{
+    let Some(pool) = pool else {
+        job(0, items);
+        return;
+    };
+    let units_per_chunk = (items.len() / unit.max(1)).div_ceil(count.max(1)).max(1);
+    let job = &job;
+    pool.scope(|scope| {
+        for (index, chunk) in items.chunks_mut(units_per_chunk * unit.max(1)).enumerate() {
+            scope.spawn(move |_| job(index * units_per_chunk, chunk));
+        }
+    });
+}
+
+// Dibuja la imagen del framebuffer estirada a toda la ventana.
+pub fn draw_scaled(d: &mut RaylibDrawHandle, texture: &Texture2D, window_width: i32, window_height: i32) {
+    d.draw_texture_pro(
+        texture,
+        Rectangle::new(0.0, 0.0, texture.width as f32, texture.height as f32),
+        Rectangle::new(0.0, 0.0, window_width as f32, window_height as f32),
+        Vector2::zero(),
+        0.0,
+        Color::WHITE,
+    );
 }
\ No newline at end of file
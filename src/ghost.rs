@@ -0,0 +1,128 @@
+use std::f32::consts::{PI, TAU};
+use std::path::PathBuf;
+use raylib::prelude::Vector2;
+use crate::config::{config_dir, read_key_values, write_file};
+use crate::difficulty::Difficulty;
+use crate::player::Player;
+use crate::TICK_DT;
+
+pub const GHOST_SAMPLE_TICKS: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct GhostFrame {
+    pub x: f32,
+    pub y: f32,
+    pub a: f32,
+}
+
+impl GhostFrame {
+    pub fn position(&self) -> Vector2 {
+        Vector2::new(self.x, self.y)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GhostRun {
+    pub time: f32,
+    pub frames: Vec<GhostFrame>,
+}
+
+impl GhostRun {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Igual que las tablas de récords, el fantasma va atado al trazado exacto del laberinto:
+    // si se edita el nivel, el recorrido viejo deja de cargarse.
+    fn file_path(profile_name: &str, maze_hash: u64, difficulty: Difficulty) -> PathBuf {
+        let profile: String = profile_name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        config_dir()
+            .join("ghosts")
+            .join(format!("{}_{:016x}_{}.ghost", profile, maze_hash, difficulty.config_name()))
+    }
+
+    pub fn load(profile_name: &str, maze_hash: u64, difficulty: Difficulty) -> Option<Self> {
+        let pairs = read_key_values(&Self::file_path(profile_name, maze_hash, difficulty))?;
+
+        let mut ghost = GhostRun::new();
+        for (key, value) in pairs {
+            match key.as_str() {
+                "time" => ghost.time = value.parse().ok()?,
+                "frame" => {
+                    let fields: Vec<f32> = value.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+                    if let [x, y, a] = fields[..] {
+                        ghost.frames.push(GhostFrame { x, y, a });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        (!ghost.frames.is_empty()).then_some(ghost)
+    }
+
+    pub fn save(&self, profile_name: &str, maze_hash: u64, difficulty: Difficulty) {
+        let mut contents = String::from("# Fantasma del mejor recorrido\n");
+        contents.push_str(&format!("time = {}\n", self.time));
+        contents.push_str(&format!("sample_ticks = {}\n", GHOST_SAMPLE_TICKS));
+        for frame in &self.frames {
+            contents.push_str(&format!("frame = {:.2} {:.2} {:.3}\n", frame.x, frame.y, frame.a));
+        }
+
+        let path = Self::file_path(profile_name, maze_hash, difficulty);
+        if let Err(e) = write_file(&path, &contents) {
+            println!("Error guardando fantasma {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn record(&mut self, tick: usize, player: &Player) {
+        if tick.is_multiple_of(GHOST_SAMPLE_TICKS) {
+            self.frames.push(GhostFrame {
+                x: player.pos.x,
+                y: player.pos.y,
+                a: player.a,
+            });
+        }
+    }
+
+    pub fn is_faster_than(&self, other: Option<&GhostRun>) -> bool {
+        other.is_none_or(|other| self.time < other.time)
+    }
+
+    // El ángulo se interpola por el giro más corto para que no dé la vuelta al cruzar ±π.
+    pub fn frame_at(&self, time: f32) -> Option<GhostFrame> {
+        let sample = time / (TICK_DT * GHOST_SAMPLE_TICKS as f32);
+        let index = sample as usize;
+        let current = self.frames.get(index)?;
+        let next = self.frames.get(index + 1).unwrap_or(current);
+        let t = sample.fract();
+        let turn = (next.a - current.a + PI).rem_euclid(TAU) - PI;
+
+        Some(GhostFrame {
+            x: current.x + (next.x - current.x) * t,
+            y: current.y + (next.y - current.y) * t,
+            a: current.a + turn * t,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_at_turns_the_short_way_across_pi() {
+        let ghost = GhostRun {
+            time: 1.0,
+            frames: vec![GhostFrame { x: 0.0, y: 0.0, a: 3.0 }, GhostFrame { x: 8.0, y: 4.0, a: -3.0 }],
+        };
+        let frame = ghost.frame_at(TICK_DT * GHOST_SAMPLE_TICKS as f32 * 0.5).unwrap();
+
+        assert!((frame.x - 4.0).abs() < 1e-3 && (frame.y - 2.0).abs() < 1e-3);
+        assert!(frame.a.cos() < -0.99, "ángulo {}", frame.a);
+        assert!(ghost.frame_at(10.0).is_none());
+    }
+}
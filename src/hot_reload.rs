@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+pub const WATCHED_DIRS: [&str; 2] = ["assets", "levels"];
+const POLL_INTERVAL: f32 = 0.5;
+
+// Modo de desarrollo (--dev): revisa cada medio segundo las fechas de modificación de los
+// archivos en assets/ y levels/ y devuelve los que cambiaron desde la última revisión.
+pub struct FileWatcher {
+    modified: HashMap<String, SystemTime>,
+    timer: f32,
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        let mut watcher = Self {
+            modified: HashMap::new(),
+            timer: 0.0,
+        };
+        watcher.scan();
+        println!("Modo desarrollo: vigilando {} archivos", watcher.modified.len());
+        watcher
+    }
+
+    pub fn poll(&mut self, delta_time: f32) -> Vec<String> {
+        self.timer += delta_time;
+        if self.timer < POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.timer = 0.0;
+        self.scan()
+    }
+
+    fn scan(&mut self) -> Vec<String> {
+        let mut changed = Vec::new();
+        for dir in WATCHED_DIRS {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let Ok(modified) = entry.metadata().and_then(|metadata| metadata.modified()) else {
+                    continue;
+                };
+                // Las rutas quedan como "assets/wall.png", igual que las usa el resto del juego.
+                let path = format!("{}/{}", dir, entry.file_name().to_string_lossy());
+                if self.modified.insert(path.clone(), modified) != Some(modified) {
+                    changed.push(path);
+                }
+            }
+        }
+        changed
+    }
+}
+
+pub fn is_texture(path: &str) -> bool {
+    Path::new(path).extension().is_some_and(|extension| extension == "png")
+}
@@ -0,0 +1,390 @@
+use raylib::prelude::*;
+use crate::GameData;
+use crate::ghost::GhostFrame;
+use crate::input::Action;
+use crate::interaction::{find_target, EXIT_GLYPH};
+use crate::inventory::{is_hidden_by_smoke, ItemKind, ALL_ITEMS};
+use crate::maze::Maze;
+use crate::player::{Player, PlayerStatus};
+use crate::settings::Settings;
+use crate::entities::PickupEffect;
+use crate::sprites::SpriteManager;
+use crate::taylor_ai::{GridPos, TaylorAI};
+use crate::taylor_player::TaylorPlayer;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Viewport {
+    pub fn split(count: usize, width: i32, height: i32) -> Vec<Viewport> {
+        let count = count.max(1) as i32;
+        let viewport_width = width / count;
+        (0..count)
+            .map(|index| Viewport {
+                x: index * viewport_width,
+                y: 0,
+                width: viewport_width,
+                height,
+            })
+            .collect()
+    }
+}
+
+pub fn draw_hud(
+    d: &mut RaylibDrawHandle,
+    game_data: &GameData,
+    players: &[Player],
+    player_index: usize,
+    maze: &Maze,
+    sprite_manager: &SpriteManager,
+    settings: &Settings,
+    block_size: usize,
+    ghost: Option<GhostFrame>,
+    viewport: Viewport,
+) {
+    let lang = settings.language;
+    let player = &players[player_index];
+    let Viewport { x, y, width, height } = viewport;
+
+    let level_text = if players.len() > 1 {
+        format!("{}{} - {}", lang.pick("J", "P"), player_index + 1, game_data.get_current_level().level_name)
+    } else {
+        format!("{}: {}", lang.pick("Nivel", "Level"), game_data.get_current_level().level_name)
+    };
+    d.draw_text(&level_text, x + 20, y + 20, 28, Color::WHITE);
+
+    d.draw_text(
+        &format!("{}: {}/{}",
+                lang.pick("Gasolina", "Gasoline"),
+                game_data.gasoline_collected,
+                game_data.get_current_level().required_cans),
+        x + 20, y + 55, 28, Color::YELLOW,
+    );
+
+    d.draw_text(
+        &format!("{}: {:.1}s", lang.pick("Tiempo", "Time"), game_data.game_timer),
+        x + 20, y + 90, 28, Color::WHITE,
+    );
+
+    if let Some(run) = &game_data.endless {
+        d.draw_text(
+            &format!("{}: {:.1}s", lang.pick("Tiempo restante", "Time left"), run.time_remaining.max(0.0)),
+            x + 20, y + 125, 28,
+            if run.time_remaining < 15.0 { Color::RED } else { Color::ORANGE },
+        );
+    }
+
+    match player.status {
+        PlayerStatus::Caught => {
+            d.draw_text(lang.pick("¡ATRAPADO!", "CAUGHT!"), x + width / 2 - 110, y + height / 2, 40, Color::RED);
+        }
+        PlayerStatus::Escaped => {
+            d.draw_text(lang.pick("¡A SALVO!", "SAFE!"), x + width / 2 - 90, y + height / 2, 40, Color::GREEN);
+        }
+        PlayerStatus::Active => {
+            if game_data.nearest_chaser_distance(player.pos) < 150.0 {
+                d.draw_text(
+                    lang.pick("¡TAYLOR ESTÁ CERCA!", "TAYLOR IS NEAR!"),
+                    x + width / 2 - 200, y + 160,
+                    36, Color::RED,
+                );
+            }
+        }
+    }
+
+    let minimap_size = (width / 4).min(300);
+    draw_minimap(d, game_data, players, player_index, maze, sprite_manager, block_size, ghost, true, x + width - minimap_size - 20, y + 20, minimap_size);
+
+    if game_data.max_lives > 0 {
+        d.draw_text(
+            &format!("{}: {}/{}", lang.pick("Vidas", "Lives"), player.lives, game_data.max_lives),
+            x + width - minimap_size - 20, y + minimap_size + 30,
+            28, if player.lives <= 1 { Color::RED } else { Color::PINK },
+        );
+    }
+
+    if player.is_active() && player.is_invulnerable() {
+        d.draw_text(lang.pick("¡Perdiste una vida!", "You lost a life!"), x + width / 2 - 150, y + height / 2 - 50, 32, Color::PINK);
+    }
+
+    if player_index == 0 {
+        d.draw_fps(x + 20, y + height - 60);
+    }
+
+    draw_stamina_bar(d, player, settings, x + 20, y + height - 160);
+    draw_item_bar(d, player, settings, x + width - 400, y + height - 100);
+
+    let mut status_y = y + height - 140;
+    for (kind, remaining) in [(ItemKind::SpeedBoost, player.speed_boost), (ItemKind::MapReveal, player.map_reveal)] {
+        if remaining > 0.0 {
+            d.draw_text(&format!("{}: {:.1}s", kind.name(lang), remaining), x + width - 400, status_y, 22, Color::SKYBLUE);
+            status_y -= 28;
+        }
+    }
+    if player.is_active() && is_hidden_by_smoke(&game_data.world_effects, player.pos) {
+        d.draw_text(lang.pick("Oculto en el humo", "Hidden in smoke"), x + width - 400, status_y, 22, Color::LIGHTGRAY);
+    }
+
+    d.draw_text(
+        &format!(
+            "{}, {} {}",
+            lang.pick("WASD para moverse", "WASD to move"),
+            settings.controls.label(Action::Sprint),
+            lang.pick("para correr", "to sprint"),
+        ),
+        x + 20, y + height - 120, 24, Color::LIGHTGRAY,
+    );
+
+    let target = if player.is_active() { find_target(maze, sprite_manager, player, block_size) } else { None };
+    if let Some(target) = target {
+        let available = target.is_available(game_data);
+        let prompt = if available {
+            format!("[{}] {}", settings.controls.label(Action::Interact), target.prompt(game_data, lang))
+        } else {
+            target.prompt(game_data, lang)
+        };
+        let prompt_width = d.measure_text(&prompt, 28);
+        d.draw_text(
+            &prompt,
+            x + (width - prompt_width) / 2, y + height / 2 + 60,
+            28, if available { Color::GREEN } else { Color::LIGHTGRAY },
+        );
+    }
+}
+
+pub fn draw_taylor_hud(
+    d: &mut RaylibDrawHandle,
+    game_data: &GameData,
+    taylor: &TaylorPlayer,
+    players: &[Player],
+    maze: &Maze,
+    sprite_manager: &SpriteManager,
+    settings: &Settings,
+    block_size: usize,
+    viewport: Viewport,
+) {
+    let lang = settings.language;
+    let Viewport { x, y, width, height } = viewport;
+
+    d.draw_text(&format!("Taylor - {}", game_data.get_current_level().level_name), x + 20, y + 20, 28, Color::PINK);
+
+    d.draw_text(
+        &format!("{}: {}/{}",
+                lang.pick("Bidones del corredor", "Runner's cans"),
+                game_data.gasoline_collected,
+                game_data.get_current_level().required_cans),
+        x + 20, y + 55, 28, Color::YELLOW,
+    );
+
+    d.draw_text(
+        &format!("{}: {:.1}s", lang.pick("Tiempo", "Time"), game_data.game_timer),
+        x + 20, y + 90, 28, Color::WHITE,
+    );
+
+    let (sense_text, sense_color) = if taylor.is_sensing() {
+        (lang.pick("SENTIDO ACTIVO", "SENSE ACTIVE").to_string(), Color::MAGENTA)
+    } else if taylor.sense_cooldown > 0.0 {
+        (format!("{}: {:.0}s", lang.pick("Sentido en", "Sense in"), taylor.sense_cooldown.ceil()), Color::GRAY)
+    } else {
+        (
+            format!("{} {} {}", lang.pick("Sentido listo:", "Sense ready:"), settings.controls.label(Action::Interact), lang.pick("para usarlo", "to use")),
+            Color::GREEN,
+        )
+    };
+    d.draw_text(&sense_text, x + 20, y + 125, 24, sense_color);
+
+    draw_stamina_bar(d, &taylor.body, settings, x + 20, y + height - 160);
+
+    if players.iter().all(|player| !player.is_active()) {
+        d.draw_text(lang.pick("¡FIN DE LA CAZA!", "HUNT OVER!"), x + width / 2 - 150, y + height / 2, 40, Color::PINK);
+    }
+
+    let minimap_size = (width / 4).min(300);
+    draw_minimap(d, game_data, players, usize::MAX, maze, sprite_manager, block_size, None, taylor.is_sensing(), x + width - minimap_size - 20, y + 20, minimap_size);
+}
+
+pub fn draw_stamina_bar(d: &mut RaylibDrawHandle, player: &Player, settings: &Settings, x: i32, y: i32) {
+    const BAR_WIDTH: i32 = 220;
+    const BAR_HEIGHT: i32 = 18;
+
+    let lang = settings.language;
+    let fill_color = if player.is_exhausted() {
+        Color::RED
+    } else if player.stamina < 0.3 {
+        Color::ORANGE
+    } else {
+        Color::LIME
+    };
+
+    d.draw_rectangle(x, y, BAR_WIDTH, BAR_HEIGHT, Color::new(0, 0, 0, 150));
+    d.draw_rectangle(x, y, (BAR_WIDTH as f32 * player.stamina) as i32, BAR_HEIGHT, fill_color);
+    d.draw_rectangle_lines(x, y, BAR_WIDTH, BAR_HEIGHT, Color::WHITE);
+    d.draw_text(
+        if player.is_exhausted() { lang.pick("¡Agotado!", "Exhausted!") } else { lang.pick("Energía", "Stamina") },
+        x + BAR_WIDTH + 10, y, 18, fill_color,
+    );
+}
+
+pub fn draw_item_bar(d: &mut RaylibDrawHandle, player: &Player, settings: &Settings, x: i32, y: i32) {
+    const SLOT_WIDTH: i32 = 90;
+    const SLOT_HEIGHT: i32 = 50;
+    const SLOT_GAP: i32 = 6;
+
+    let lang = settings.language;
+    for (index, kind) in ALL_ITEMS.iter().enumerate() {
+        let slot_x = x + index as i32 * (SLOT_WIDTH + SLOT_GAP);
+        let count = player.inventory.count(*kind);
+        let selected = index == player.inventory.selected && count > 0;
+        let text_color = if count > 0 { Color::WHITE } else { Color::DARKGRAY };
+
+        d.draw_rectangle(slot_x, y, SLOT_WIDTH, SLOT_HEIGHT, Color::new(0, 0, 0, 150));
+        d.draw_rectangle_lines_ex(
+            Rectangle::new(slot_x as f32, y as f32, SLOT_WIDTH as f32, SLOT_HEIGHT as f32),
+            if selected { 3.0 } else { 1.0 },
+            if selected { Color::YELLOW } else { Color::GRAY },
+        );
+        d.draw_text(kind.name(lang), slot_x + 6, y + 6, 12, text_color);
+        d.draw_text(&format!("x{}", count), slot_x + 6, y + 22, 22, text_color);
+    }
+
+    d.draw_text(
+        &format!(
+            "[{}] {}  [{}] {}",
+            settings.controls.label(Action::NextItem),
+            lang.pick("cambiar", "switch"),
+            settings.controls.label(Action::UseItem),
+            lang.pick("usar", "use"),
+        ),
+        x, y + SLOT_HEIGHT + 6, 18, Color::LIGHTGRAY,
+    );
+}
+
+// Celda libre junto a la salida, o la lata más cercana si aún faltan; es adonde apunta la
+// ruta que muestra el objeto de mapa.
+fn next_objective(
+    game_data: &GameData,
+    player: &Player,
+    maze: &Maze,
+    sprite_manager: &SpriteManager,
+    block_size: usize,
+) -> Option<GridPos> {
+    if game_data.gasoline_collected < game_data.get_current_level().required_cans {
+        return sprite_manager
+            .sprites
+            .iter()
+            .filter(|sprite| !sprite.collected && sprite.entity.on_pickup == PickupEffect::Gasoline)
+            .min_by(|a, b| {
+                let distance_a = Vector2::new(a.x, a.y).distance_to(player.pos);
+                let distance_b = Vector2::new(b.x, b.y).distance_to(player.pos);
+                distance_a.total_cmp(&distance_b)
+            })
+            .map(|sprite| GridPos::from_world_pos(sprite.x, sprite.y, block_size));
+    }
+
+    let ai = TaylorAI::new();
+    maze.iter()
+        .enumerate()
+        .flat_map(|(row_y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, &cell)| cell == EXIT_GLYPH)
+                .map(move |(cell_x, _)| GridPos::new(cell_x as i32, row_y as i32))
+        })
+        .flat_map(|exit| exit.get_neighbors())
+        .find(|cell| ai.is_walkable(cell, maze))
+}
+
+pub fn draw_minimap(
+    d: &mut RaylibDrawHandle,
+    game_data: &GameData,
+    players: &[Player],
+    player_index: usize,
+    maze: &Maze,
+    sprite_manager: &SpriteManager,
+    block_size: usize,
+    ghost: Option<GhostFrame>,
+    reveal_players: bool,
+    offset_x: i32,
+    offset_y: i32,
+    minimap_size: i32,
+) {
+    let map_rows = maze.len();
+    let map_cols = maze.iter().map(|row| row.len()).max().unwrap_or(1);
+    let scale_x = minimap_size as f32 / (map_cols * block_size) as f32;
+    let scale_y = minimap_size as f32 / (map_rows * block_size) as f32;
+
+    d.draw_rectangle(offset_x, offset_y, minimap_size, minimap_size, Color::new(0, 0, 0, 150));
+
+    for (row_index, row) in maze.iter().enumerate() {
+        for (col_index, &cell) in row.iter().enumerate() {
+            if cell != ' ' {
+                let x = offset_x + ((col_index * block_size) as f32 * scale_x) as i32;
+                let y = offset_y + ((row_index * block_size) as f32 * scale_y) as i32;
+                let cell_width = (block_size as f32 * scale_x) as i32;
+                let cell_height = (block_size as f32 * scale_y) as i32;
+                d.draw_rectangle(x, y, cell_width, cell_height, Color::GRAY);
+            }
+        }
+    }
+
+    for sprite in &sprite_manager.sprites {
+        if !sprite.collected && sprite.entity.on_pickup == PickupEffect::Gasoline {
+            let sx = offset_x + (sprite.x * scale_x) as i32;
+            let sy = offset_y + (sprite.y * scale_y) as i32;
+            d.draw_circle(sx, sy, 6.0, Color::ORANGE);
+        }
+    }
+
+    let level = game_data.get_current_level();
+    let exit_position = sprite_manager
+        .sprites
+        .iter()
+        .find(|sprite| sprite.entity.on_pickup == PickupEffect::Escape)
+        .map_or(Vector2::new(level.exit_x, level.exit_y), |sprite| Vector2::new(sprite.x, sprite.y));
+    let exit_x = offset_x + (exit_position.x * scale_x) as i32;
+    let exit_y = offset_y + (exit_position.y * scale_y) as i32;
+    d.draw_rectangle(exit_x - 8, exit_y - 8, 16, 16, Color::BLUE);
+    d.draw_text("E", exit_x - 4, exit_y - 6, 12, Color::WHITE);
+
+    if let Some(ghost) = ghost {
+        let gx = offset_x + (ghost.x * scale_x) as i32;
+        let gy = offset_y + (ghost.y * scale_y) as i32;
+        d.draw_circle_lines(gx, gy, 6.0, Color::SKYBLUE);
+        d.draw_line(gx, gy, gx + (ghost.a.cos() * 10.0) as i32, gy + (ghost.a.sin() * 10.0) as i32, Color::SKYBLUE);
+    }
+
+    for chaser_position in game_data.chaser_positions() {
+        let tx = offset_x + (chaser_position.x * scale_x) as i32;
+        let ty = offset_y + (chaser_position.y * scale_y) as i32;
+        d.draw_circle(tx, ty, 8.0, Color::RED);
+    }
+
+    if let Some(player) = players.get(player_index).filter(|player| player.map_reveal > 0.0) {
+        let start = GridPos::from_world_pos(player.pos.x, player.pos.y, block_size);
+        if let Some(goal) = next_objective(game_data, player, maze, sprite_manager, block_size) {
+            let route = TaylorAI::new().find_path_astar(start, goal, maze);
+            let to_screen = |cell: &GridPos| {
+                let center = cell.to_world_pos(block_size);
+                Vector2::new(offset_x as f32 + center.x * scale_x, offset_y as f32 + center.y * scale_y)
+            };
+            let points: Vec<Vector2> = std::iter::once(start).chain(route).map(|cell| to_screen(&cell)).collect();
+            for segment in points.windows(2) {
+                d.draw_line_ex(segment[0], segment[1], 3.0, Color::SKYBLUE);
+            }
+        }
+    }
+
+    for (index, player) in players.iter().enumerate().filter(|_| reveal_players) {
+        let px = offset_x + (player.pos.x * scale_x) as i32;
+        let py = offset_y + (player.pos.y * scale_y) as i32;
+        let color = if index == player_index { Color::GREEN } else { Color::LIME };
+        d.draw_circle(px, py, if index == player_index { 6.0 } else { 4.0 }, color);
+    }
+
+    d.draw_rectangle_lines_ex(Rectangle::new(offset_x as f32, offset_y as f32, minimap_size as f32, minimap_size as f32), 3.0, Color::WHITE);
+}
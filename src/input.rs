@@ -0,0 +1,583 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+use crate::config::parse_bool;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    Interact,
+    Sprint,
+    NextItem,
+    UseItem,
+    Pause,
+    Continue,
+    Retry,
+    Level1,
+    Level2,
+    Level3,
+    Back,
+    BackToMenu,
+    RestartLevel,
+    SaveReplay,
+    OpenSettings,
+    SwitchProfile,
+    NewProfile,
+    UnlockAll,
+    CycleDifficulty,
+    ToggleCoop,
+    ToggleVersus,
+    HostLan,
+    JoinLan,
+    EndlessMode,
+    WatchReplay,
+    ReplayPause,
+    ReplayFaster,
+    ReplaySlower,
+    ReplaySeekBack,
+    ReplaySeekForward,
+    ReplayRestart,
+}
+
+pub const ALL_ACTIONS: [Action; 37] = [
+    Action::MoveForward,
+    Action::MoveBackward,
+    Action::StrafeLeft,
+    Action::StrafeRight,
+    Action::TurnLeft,
+    Action::TurnRight,
+    Action::Interact,
+    Action::Sprint,
+    Action::NextItem,
+    Action::UseItem,
+    Action::Pause,
+    Action::Continue,
+    Action::Retry,
+    Action::Level1,
+    Action::Level2,
+    Action::Level3,
+    Action::Back,
+    Action::BackToMenu,
+    Action::RestartLevel,
+    Action::SaveReplay,
+    Action::OpenSettings,
+    Action::SwitchProfile,
+    Action::NewProfile,
+    Action::UnlockAll,
+    Action::CycleDifficulty,
+    Action::ToggleCoop,
+    Action::ToggleVersus,
+    Action::HostLan,
+    Action::JoinLan,
+    Action::EndlessMode,
+    Action::WatchReplay,
+    Action::ReplayPause,
+    Action::ReplayFaster,
+    Action::ReplaySlower,
+    Action::ReplaySeekBack,
+    Action::ReplaySeekForward,
+    Action::ReplayRestart,
+];
+
+impl Action {
+    pub fn config_name(self) -> &'static str {
+        match self {
+            Action::MoveForward => "move_forward",
+            Action::MoveBackward => "move_backward",
+            Action::StrafeLeft => "strafe_left",
+            Action::StrafeRight => "strafe_right",
+            Action::TurnLeft => "turn_left",
+            Action::TurnRight => "turn_right",
+            Action::Interact => "interact",
+            Action::Sprint => "sprint",
+            Action::NextItem => "next_item",
+            Action::UseItem => "use_item",
+            Action::Pause => "pause",
+            Action::Continue => "continue",
+            Action::Retry => "retry",
+            Action::Level1 => "level_1",
+            Action::Level2 => "level_2",
+            Action::Level3 => "level_3",
+            Action::Back => "back",
+            Action::BackToMenu => "back_to_menu",
+            Action::RestartLevel => "restart_level",
+            Action::SaveReplay => "save_replay",
+            Action::OpenSettings => "open_settings",
+            Action::SwitchProfile => "switch_profile",
+            Action::NewProfile => "new_profile",
+            Action::UnlockAll => "unlock_all",
+            Action::CycleDifficulty => "cycle_difficulty",
+            Action::ToggleCoop => "toggle_coop",
+            Action::ToggleVersus => "toggle_versus",
+            Action::HostLan => "host_lan",
+            Action::JoinLan => "join_lan",
+            Action::EndlessMode => "endless_mode",
+            Action::WatchReplay => "watch_replay",
+            Action::ReplayPause => "replay_pause",
+            Action::ReplayFaster => "replay_faster",
+            Action::ReplaySlower => "replay_slower",
+            Action::ReplaySeekBack => "replay_seek_back",
+            Action::ReplaySeekForward => "replay_seek_forward",
+            Action::ReplayRestart => "replay_restart",
+        }
+    }
+
+    fn from_config_name(name: &str) -> Option<Self> {
+        ALL_ACTIONS.iter().copied().find(|action| action.config_name() == name)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Binding {
+    Key(KeyboardKey),
+    Button(GamepadButton),
+    Axis(GamepadAxis, f32),
+}
+
+const KEY_NAMES: [(KeyboardKey, &str); 68] = [
+    (KeyboardKey::KEY_A, "KEY_A"), (KeyboardKey::KEY_B, "KEY_B"), (KeyboardKey::KEY_C, "KEY_C"),
+    (KeyboardKey::KEY_D, "KEY_D"), (KeyboardKey::KEY_E, "KEY_E"), (KeyboardKey::KEY_F, "KEY_F"),
+    (KeyboardKey::KEY_G, "KEY_G"), (KeyboardKey::KEY_H, "KEY_H"), (KeyboardKey::KEY_I, "KEY_I"),
+    (KeyboardKey::KEY_J, "KEY_J"), (KeyboardKey::KEY_K, "KEY_K"), (KeyboardKey::KEY_L, "KEY_L"),
+    (KeyboardKey::KEY_M, "KEY_M"), (KeyboardKey::KEY_N, "KEY_N"), (KeyboardKey::KEY_O, "KEY_O"),
+    (KeyboardKey::KEY_P, "KEY_P"), (KeyboardKey::KEY_Q, "KEY_Q"), (KeyboardKey::KEY_R, "KEY_R"),
+    (KeyboardKey::KEY_S, "KEY_S"), (KeyboardKey::KEY_T, "KEY_T"), (KeyboardKey::KEY_U, "KEY_U"),
+    (KeyboardKey::KEY_V, "KEY_V"), (KeyboardKey::KEY_W, "KEY_W"), (KeyboardKey::KEY_X, "KEY_X"),
+    (KeyboardKey::KEY_Y, "KEY_Y"), (KeyboardKey::KEY_Z, "KEY_Z"),
+    (KeyboardKey::KEY_ZERO, "KEY_0"), (KeyboardKey::KEY_ONE, "KEY_1"), (KeyboardKey::KEY_TWO, "KEY_2"),
+    (KeyboardKey::KEY_THREE, "KEY_3"), (KeyboardKey::KEY_FOUR, "KEY_4"), (KeyboardKey::KEY_FIVE, "KEY_5"),
+    (KeyboardKey::KEY_SIX, "KEY_6"), (KeyboardKey::KEY_SEVEN, "KEY_7"), (KeyboardKey::KEY_EIGHT, "KEY_8"),
+    (KeyboardKey::KEY_NINE, "KEY_9"),
+    (KeyboardKey::KEY_UP, "KEY_UP"), (KeyboardKey::KEY_DOWN, "KEY_DOWN"),
+    (KeyboardKey::KEY_LEFT, "KEY_LEFT"), (KeyboardKey::KEY_RIGHT, "KEY_RIGHT"),
+    (KeyboardKey::KEY_SPACE, "KEY_SPACE"), (KeyboardKey::KEY_ENTER, "KEY_ENTER"),
+    (KeyboardKey::KEY_TAB, "KEY_TAB"), (KeyboardKey::KEY_BACKSPACE, "KEY_BACKSPACE"),
+    (KeyboardKey::KEY_INSERT, "KEY_INSERT"), (KeyboardKey::KEY_DELETE, "KEY_DELETE"),
+    (KeyboardKey::KEY_HOME, "KEY_HOME"), (KeyboardKey::KEY_END, "KEY_END"),
+    (KeyboardKey::KEY_PAGE_UP, "KEY_PAGE_UP"), (KeyboardKey::KEY_PAGE_DOWN, "KEY_PAGE_DOWN"),
+    (KeyboardKey::KEY_LEFT_SHIFT, "KEY_LEFT_SHIFT"), (KeyboardKey::KEY_RIGHT_SHIFT, "KEY_RIGHT_SHIFT"),
+    (KeyboardKey::KEY_LEFT_CONTROL, "KEY_LEFT_CONTROL"), (KeyboardKey::KEY_RIGHT_CONTROL, "KEY_RIGHT_CONTROL"),
+    (KeyboardKey::KEY_LEFT_ALT, "KEY_LEFT_ALT"), (KeyboardKey::KEY_RIGHT_ALT, "KEY_RIGHT_ALT"),
+    (KeyboardKey::KEY_F1, "KEY_F1"), (KeyboardKey::KEY_F2, "KEY_F2"), (KeyboardKey::KEY_F3, "KEY_F3"),
+    (KeyboardKey::KEY_F4, "KEY_F4"), (KeyboardKey::KEY_F5, "KEY_F5"), (KeyboardKey::KEY_F6, "KEY_F6"),
+    (KeyboardKey::KEY_F7, "KEY_F7"), (KeyboardKey::KEY_F8, "KEY_F8"), (KeyboardKey::KEY_F9, "KEY_F9"),
+    (KeyboardKey::KEY_F10, "KEY_F10"), (KeyboardKey::KEY_F11, "KEY_F11"), (KeyboardKey::KEY_F12, "KEY_F12"),
+];
+
+const BUTTON_NAMES: [(GamepadButton, &str); 17] = [
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP, "DPAD_UP"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT, "DPAD_RIGHT"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN, "DPAD_DOWN"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT, "DPAD_LEFT"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP, "FACE_UP"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT, "FACE_RIGHT"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN, "FACE_DOWN"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT, "FACE_LEFT"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1, "L1"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2, "L2"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1, "R1"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2, "R2"),
+    (GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT, "SELECT"),
+    (GamepadButton::GAMEPAD_BUTTON_MIDDLE, "HOME"),
+    (GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT, "START"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB, "L3"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB, "R3"),
+];
+
+pub const STICK_AXES: [(GamepadAxis, &str); 4] = [
+    (GamepadAxis::GAMEPAD_AXIS_LEFT_X, "AXIS_LEFT_X"),
+    (GamepadAxis::GAMEPAD_AXIS_LEFT_Y, "AXIS_LEFT_Y"),
+    (GamepadAxis::GAMEPAD_AXIS_RIGHT_X, "AXIS_RIGHT_X"),
+    (GamepadAxis::GAMEPAD_AXIS_RIGHT_Y, "AXIS_RIGHT_Y"),
+];
+
+impl Binding {
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => KEY_NAMES
+                .iter()
+                .find(|(k, _)| k == key)
+                .map_or_else(|| format!("{:?}", key), |(_, name)| name.to_string()),
+            Binding::Button(button) => BUTTON_NAMES
+                .iter()
+                .find(|(b, _)| b == button)
+                .map_or_else(|| format!("{:?}", button), |(_, name)| name.to_string()),
+            Binding::Axis(axis, direction) => {
+                let axis_name = STICK_AXES
+                    .iter()
+                    .find(|(a, _)| a == axis)
+                    .map_or("AXIS_?", |(_, name)| *name);
+                format!("{}{}", axis_name, if *direction < 0.0 { "-" } else { "+" })
+            }
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        if let Some((key, _)) = KEY_NAMES.iter().find(|(_, n)| *n == name) {
+            return Some(Binding::Key(*key));
+        }
+        if let Some((button, _)) = BUTTON_NAMES.iter().find(|(_, n)| *n == name) {
+            return Some(Binding::Button(*button));
+        }
+
+        let (axis_name, direction) = if let Some(axis_name) = name.strip_suffix('-') {
+            (axis_name, -1.0)
+        } else {
+            (name.strip_suffix('+')?, 1.0)
+        };
+        STICK_AXES
+            .iter()
+            .find(|(_, n)| *n == axis_name)
+            .map(|(axis, _)| Binding::Axis(*axis, direction))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TickInput {
+    pub forward: f32,
+    pub strafe: f32,
+    pub turn: f32,
+    pub look: f32,
+    pub interact: bool,
+    pub sprint: bool,
+    pub next_item: bool,
+    pub use_item: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct InputMap {
+    pub bindings: HashMap<Action, Vec<Binding>>,
+    pub gamepad: i32,
+    pub stick_deadzone: f32,
+    pub invert_look_x: bool,
+    pub invert_move_y: bool,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use Binding::*;
+
+        let mut bindings = HashMap::new();
+        bindings.insert(Action::MoveForward, vec![
+            Key(KeyboardKey::KEY_W), Key(KeyboardKey::KEY_UP), Axis(GamepadAxis::GAMEPAD_AXIS_LEFT_Y, -1.0),
+        ]);
+        bindings.insert(Action::MoveBackward, vec![
+            Key(KeyboardKey::KEY_S), Key(KeyboardKey::KEY_DOWN), Axis(GamepadAxis::GAMEPAD_AXIS_LEFT_Y, 1.0),
+        ]);
+        bindings.insert(Action::StrafeLeft, vec![
+            Key(KeyboardKey::KEY_A), Axis(GamepadAxis::GAMEPAD_AXIS_LEFT_X, -1.0),
+        ]);
+        bindings.insert(Action::StrafeRight, vec![
+            Key(KeyboardKey::KEY_D), Axis(GamepadAxis::GAMEPAD_AXIS_LEFT_X, 1.0),
+        ]);
+        bindings.insert(Action::TurnLeft, vec![
+            Key(KeyboardKey::KEY_LEFT), Axis(GamepadAxis::GAMEPAD_AXIS_RIGHT_X, -1.0),
+        ]);
+        bindings.insert(Action::TurnRight, vec![
+            Key(KeyboardKey::KEY_RIGHT), Axis(GamepadAxis::GAMEPAD_AXIS_RIGHT_X, 1.0),
+        ]);
+        bindings.insert(Action::Interact, vec![
+            Key(KeyboardKey::KEY_E), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+        ]);
+        bindings.insert(Action::Sprint, vec![
+            Key(KeyboardKey::KEY_LEFT_SHIFT), Button(GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB),
+        ]);
+        bindings.insert(Action::NextItem, vec![
+            Key(KeyboardKey::KEY_Q), Button(GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1),
+        ]);
+        bindings.insert(Action::UseItem, vec![
+            Key(KeyboardKey::KEY_F), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1),
+        ]);
+        bindings.insert(Action::Pause, vec![
+            Key(KeyboardKey::KEY_P), Button(GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT),
+        ]);
+        bindings.insert(Action::Continue, vec![
+            Key(KeyboardKey::KEY_SPACE), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+        ]);
+        bindings.insert(Action::Retry, vec![
+            Key(KeyboardKey::KEY_R), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP),
+        ]);
+        bindings.insert(Action::Level1, vec![
+            Key(KeyboardKey::KEY_ONE), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT),
+        ]);
+        bindings.insert(Action::Level2, vec![
+            Key(KeyboardKey::KEY_TWO), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP),
+        ]);
+        bindings.insert(Action::Level3, vec![
+            Key(KeyboardKey::KEY_THREE), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT),
+        ]);
+        bindings.insert(Action::Back, vec![
+            Key(KeyboardKey::KEY_BACKSPACE), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT),
+        ]);
+        bindings.insert(Action::BackToMenu, vec![
+            Key(KeyboardKey::KEY_M), Button(GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT),
+        ]);
+        bindings.insert(Action::RestartLevel, vec![
+            Key(KeyboardKey::KEY_N), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT),
+        ]);
+        bindings.insert(Action::SaveReplay, vec![
+            Key(KeyboardKey::KEY_F9),
+        ]);
+        bindings.insert(Action::OpenSettings, vec![
+            Key(KeyboardKey::KEY_O), Button(GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT),
+        ]);
+        bindings.insert(Action::SwitchProfile, vec![
+            Key(KeyboardKey::KEY_TAB), Button(GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT),
+        ]);
+        bindings.insert(Action::NewProfile, vec![
+            Key(KeyboardKey::KEY_N),
+        ]);
+        bindings.insert(Action::UnlockAll, vec![
+            Key(KeyboardKey::KEY_U),
+        ]);
+        bindings.insert(Action::CycleDifficulty, vec![
+            Key(KeyboardKey::KEY_D), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1),
+        ]);
+        bindings.insert(Action::ToggleCoop, vec![
+            Key(KeyboardKey::KEY_C), Button(GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1),
+        ]);
+        bindings.insert(Action::ToggleVersus, vec![
+            Key(KeyboardKey::KEY_V), Button(GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2),
+        ]);
+        bindings.insert(Action::HostLan, vec![
+            Key(KeyboardKey::KEY_H),
+        ]);
+        bindings.insert(Action::JoinLan, vec![
+            Key(KeyboardKey::KEY_J),
+        ]);
+        bindings.insert(Action::EndlessMode, vec![
+            Key(KeyboardKey::KEY_I), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2),
+        ]);
+        bindings.insert(Action::WatchReplay, vec![
+            Key(KeyboardKey::KEY_F10),
+        ]);
+        bindings.insert(Action::ReplayPause, vec![
+            Key(KeyboardKey::KEY_SPACE), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+        ]);
+        bindings.insert(Action::ReplayFaster, vec![
+            Key(KeyboardKey::KEY_UP), Button(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP),
+        ]);
+        bindings.insert(Action::ReplaySlower, vec![
+            Key(KeyboardKey::KEY_DOWN), Button(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN),
+        ]);
+        bindings.insert(Action::ReplaySeekBack, vec![
+            Key(KeyboardKey::KEY_LEFT), Button(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT),
+        ]);
+        bindings.insert(Action::ReplaySeekForward, vec![
+            Key(KeyboardKey::KEY_RIGHT), Button(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT),
+        ]);
+        bindings.insert(Action::ReplayRestart, vec![
+            Key(KeyboardKey::KEY_HOME), Button(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP),
+        ]);
+
+        Self {
+            bindings,
+            gamepad: 0,
+            stick_deadzone: 0.2,
+            invert_look_x: false,
+            invert_move_y: false,
+        }
+    }
+}
+
+impl InputMap {
+    pub fn gamepad_only(&self, gamepad: i32) -> Self {
+        let mut controls = self.clone();
+        controls.gamepad = gamepad;
+        for bindings in controls.bindings.values_mut() {
+            bindings.retain(|binding| !matches!(binding, Binding::Key(_)));
+        }
+        controls
+    }
+
+    pub fn bindings_for(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    fn apply_deadzone(&self, raw: f32) -> f32 {
+        if raw.abs() < self.stick_deadzone {
+            0.0
+        } else {
+            raw.signum() * (raw.abs() - self.stick_deadzone) / (1.0 - self.stick_deadzone)
+        }
+    }
+
+    fn axis_sign(&self, axis: GamepadAxis) -> f32 {
+        let inverted = match axis {
+            GamepadAxis::GAMEPAD_AXIS_RIGHT_X => self.invert_look_x,
+            GamepadAxis::GAMEPAD_AXIS_LEFT_Y => self.invert_move_y,
+            _ => false,
+        };
+        if inverted { -1.0 } else { 1.0 }
+    }
+
+    fn binding_value(&self, rl: &RaylibHandle, binding: &Binding) -> f32 {
+        let gamepad_available = rl.is_gamepad_available(self.gamepad);
+        match *binding {
+            Binding::Key(key) => if rl.is_key_down(key) { 1.0 } else { 0.0 },
+            Binding::Button(button) => {
+                if gamepad_available && rl.is_gamepad_button_down(self.gamepad, button) { 1.0 } else { 0.0 }
+            }
+            Binding::Axis(axis, direction) => {
+                if !gamepad_available {
+                    return 0.0;
+                }
+                let raw = rl.get_gamepad_axis_movement(self.gamepad, axis) * self.axis_sign(axis);
+                (self.apply_deadzone(raw) * direction).max(0.0)
+            }
+        }
+    }
+
+    pub fn value(&self, rl: &RaylibHandle, action: Action) -> f32 {
+        self.scaled_value(rl, action, 1.0)
+    }
+
+    // La sensibilidad del gamepad solo escala los ejes; teclas y botones siguen valiendo 1.
+    fn scaled_value(&self, rl: &RaylibHandle, action: Action, axis_scale: f32) -> f32 {
+        self.bindings_for(action)
+            .iter()
+            .map(|binding| match binding {
+                Binding::Axis(..) => self.binding_value(rl, binding) * axis_scale,
+                _ => self.binding_value(rl, binding),
+            })
+            .fold(0.0, f32::max)
+    }
+
+    pub fn axis(&self, rl: &RaylibHandle, negative: Action, positive: Action) -> f32 {
+        self.value(rl, positive) - self.value(rl, negative)
+    }
+
+    pub fn turn(&self, rl: &RaylibHandle, gamepad_sensitivity: f32) -> f32 {
+        self.scaled_value(rl, Action::TurnRight, gamepad_sensitivity)
+            - self.scaled_value(rl, Action::TurnLeft, gamepad_sensitivity)
+    }
+
+    pub fn is_down(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.value(rl, action) > 0.5
+    }
+
+    pub fn is_pressed(&self, rl: &RaylibHandle, action: Action) -> bool {
+        let gamepad_available = rl.is_gamepad_available(self.gamepad);
+        self.bindings_for(action).iter().any(|binding| match *binding {
+            Binding::Key(key) => rl.is_key_pressed(key),
+            Binding::Button(button) => gamepad_available && rl.is_gamepad_button_pressed(self.gamepad, button),
+            Binding::Axis(..) => false,
+        })
+    }
+
+    pub fn sample(&self, rl: &RaylibHandle, gamepad_sensitivity: f32) -> TickInput {
+        TickInput {
+            forward: self.axis(rl, Action::MoveBackward, Action::MoveForward),
+            strafe: self.axis(rl, Action::StrafeLeft, Action::StrafeRight),
+            turn: self.turn(rl, gamepad_sensitivity),
+            look: 0.0,
+            interact: self.is_down(rl, Action::Interact),
+            sprint: self.is_down(rl, Action::Sprint),
+            next_item: self.is_down(rl, Action::NextItem),
+            use_item: self.is_down(rl, Action::UseItem),
+        }
+    }
+
+    // Prefiere la tecla; sin teclas (como en gamepad_only) muestra el primer botón o eje.
+    pub fn label(&self, action: Action) -> String {
+        let bindings = self.bindings_for(action);
+        bindings
+            .iter()
+            .find(|binding| matches!(binding, Binding::Key(_)))
+            .or(bindings.first())
+            .map(|binding| binding.name().trim_start_matches("KEY_").to_string())
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    // Línea de ayuda "TECLA - texto, ..." con las teclas asignadas en este momento.
+    pub fn hint(&self, entries: &[(Action, &str)]) -> String {
+        entries
+            .iter()
+            .map(|(action, text)| format!("{} - {}", self.label(*action), text))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn apply_entry(&mut self, key: &str, value: &str) -> Option<bool> {
+        match key {
+            "stick_deadzone" => Some(value.parse().map(|v: f32| self.stick_deadzone = v.clamp(0.0, 0.9)).is_ok()),
+            "invert_look_x" => Some(parse_bool(value).map(|v| self.invert_look_x = v).is_some()),
+            "invert_move_y" => Some(parse_bool(value).map(|v| self.invert_move_y = v).is_some()),
+            _ => {
+                let action = Action::from_config_name(key.strip_prefix("bind.")?)?;
+                let parsed: Vec<Binding> = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .filter_map(Binding::parse)
+                    .collect();
+                self.bindings.insert(action, parsed);
+                Some(true)
+            }
+        }
+    }
+
+    pub fn to_file_contents(&self) -> String {
+        let mut contents = String::new();
+        contents.push_str(&format!("stick_deadzone = {}\n", self.stick_deadzone));
+        contents.push_str(&format!("invert_look_x = {}\n", self.invert_look_x));
+        contents.push_str(&format!("invert_move_y = {}\n", self.invert_move_y));
+        for action in ALL_ACTIONS {
+            let names: Vec<String> = self.bindings_for(action).iter().map(Binding::name).collect();
+            contents.push_str(&format!("bind.{} = {}\n", action.config_name(), names.join(", ")));
+        }
+        contents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_key_values;
+
+    #[test]
+    fn every_action_has_a_default_binding() {
+        let controls = InputMap::default();
+        for action in ALL_ACTIONS {
+            assert!(!controls.bindings_for(action).is_empty(), "{} sin asignar", action.config_name());
+        }
+    }
+
+    #[test]
+    fn default_bindings_survive_a_save_and_load() {
+        let defaults = InputMap::default();
+        let mut loaded = InputMap::default();
+        loaded.bindings.clear();
+        for (key, value) in parse_key_values(&defaults.to_file_contents()) {
+            assert_eq!(loaded.apply_entry(&key, &value), Some(true), "{}", key);
+        }
+        for action in ALL_ACTIONS {
+            assert_eq!(loaded.bindings_for(action), defaults.bindings_for(action), "{}", action.config_name());
+        }
+    }
+
+    #[test]
+    fn parses_axis_directions() {
+        assert_eq!(Binding::parse("AXIS_LEFT_Y-"), Some(Binding::Axis(GamepadAxis::GAMEPAD_AXIS_LEFT_Y, -1.0)));
+        assert_eq!(Binding::parse("AXIS_RIGHT_X+"), Some(Binding::Axis(GamepadAxis::GAMEPAD_AXIS_RIGHT_X, 1.0)));
+        assert_eq!(Binding::parse("AXIS_RIGHT_X"), None);
+        assert_eq!(Binding::parse("KEY_HOME"), Some(Binding::Key(KeyboardKey::KEY_HOME)));
+    }
+
+    #[test]
+    fn hint_uses_current_key() {
+        let mut controls = InputMap::default();
+        controls.bindings.insert(Action::BackToMenu, vec![Binding::Key(KeyboardKey::KEY_Q)]);
+        assert_eq!(controls.hint(&[(Action::BackToMenu, "Menú"), (Action::OpenSettings, "Opciones")]), "Q - Menú, O - Opciones");
+    }
+
+    #[test]
+    fn gamepad_hint_falls_back_to_buttons() {
+        let controls = InputMap::default().gamepad_only(0);
+        assert_eq!(controls.hint(&[(Action::Level1, "Nivel 1")]), "FACE_RIGHT - Nivel 1");
+    }
+}
@@ -0,0 +1,172 @@
+use raylib::prelude::Vector2;
+use crate::entities::PickupEffect;
+use crate::maze::Maze;
+use crate::player::{Player, PlayerStatus};
+use crate::settings::Language;
+use crate::sprites::SpriteManager;
+use crate::GameData;
+
+pub const INTERACT_RANGE: f32 = 120.0;
+pub const EXIT_GLYPH: char = 'E';
+pub const DOOR_GLYPH: char = 'D';
+pub const SWITCH_GLYPH: char = 'S';
+pub const USED_SWITCH_GLYPH: char = 's';
+pub const GATE_GLYPH: char = 'G';
+
+const RAY_STEP: f32 = 4.0;
+
+// Todo lo que responde a la acción de interactuar: celdas del laberinto y entidades del mundo.
+// Las celdas se registran en `cell_interactable`; las entidades con `interact = true` en
+// assets/entities.txt aparecen solas a través de `entity_interactables`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Interactable {
+    Exit,
+    Door { x: usize, y: usize },
+    Switch { x: usize, y: usize },
+    Pickup { sprite: usize },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InteractionOutcome {
+    Escaped,
+    PickedUp(PickupEffect),
+    DoorOpened,
+    SwitchActivated,
+    Unavailable,
+}
+
+impl Interactable {
+    pub fn is_available(&self, game_data: &GameData) -> bool {
+        match self {
+            Interactable::Exit => game_data.gasoline_collected >= game_data.get_current_level().required_cans,
+            Interactable::Door { .. } | Interactable::Switch { .. } | Interactable::Pickup { .. } => true,
+        }
+    }
+
+    pub fn prompt(&self, game_data: &GameData, lang: Language) -> String {
+        match self {
+            Interactable::Exit if !self.is_available(game_data) => format!(
+                "{} {} {}",
+                lang.pick("Faltan", "Need"),
+                game_data.get_current_level().required_cans - game_data.gasoline_collected,
+                lang.pick("latas para escapar", "more cans to escape"),
+            ),
+            Interactable::Exit => lang.pick("Escapar", "Escape").to_string(),
+            Interactable::Door { .. } => lang.pick("Abrir puerta", "Open door").to_string(),
+            Interactable::Switch { .. } => lang.pick("Activar palanca", "Pull lever").to_string(),
+            Interactable::Pickup { .. } => lang.pick("Recoger", "Pick up").to_string(),
+        }
+    }
+
+    pub fn interact(
+        &self,
+        game_data: &mut GameData,
+        player: &mut Player,
+        maze: &mut Maze,
+        sprite_manager: &mut SpriteManager,
+    ) -> InteractionOutcome {
+        if !self.is_available(game_data) {
+            return InteractionOutcome::Unavailable;
+        }
+
+        match *self {
+            Interactable::Exit => {
+                game_data.car_reached = true;
+                player.status = PlayerStatus::Escaped;
+                InteractionOutcome::Escaped
+            }
+            Interactable::Door { x, y } => {
+                set_cell(game_data, maze, x, y, ' ');
+                InteractionOutcome::DoorOpened
+            }
+            Interactable::Switch { x, y } => {
+                set_cell(game_data, maze, x, y, USED_SWITCH_GLYPH);
+                let gates: Vec<(usize, usize)> = maze
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(row_y, row)| {
+                        row.iter()
+                            .enumerate()
+                            .filter(|(_, &cell)| cell == GATE_GLYPH)
+                            .map(move |(cell_x, _)| (cell_x, row_y))
+                    })
+                    .collect();
+                for (gate_x, gate_y) in gates {
+                    set_cell(game_data, maze, gate_x, gate_y, ' ');
+                }
+                InteractionOutcome::SwitchActivated
+            }
+            Interactable::Pickup { sprite } => match sprite_manager.sprites.get_mut(sprite) {
+                Some(sprite) if !sprite.collected => {
+                    sprite.collected = true;
+                    InteractionOutcome::PickedUp(sprite.entity.on_pickup)
+                }
+                _ => InteractionOutcome::Unavailable,
+            },
+        }
+    }
+}
+
+// Los cambios quedan anotados para que los clientes en red puedan reproducirlos en su laberinto.
+fn set_cell(game_data: &mut GameData, maze: &mut Maze, x: usize, y: usize, cell: char) {
+    if let Some(slot) = maze.get_mut(y).and_then(|row| row.get_mut(x)) {
+        *slot = cell;
+        game_data.changed_cells.push((x, y, cell));
+    }
+}
+
+fn cell_interactable(cell: char, x: usize, y: usize) -> Option<Interactable> {
+    match cell {
+        EXIT_GLYPH => Some(Interactable::Exit),
+        DOOR_GLYPH => Some(Interactable::Door { x, y }),
+        SWITCH_GLYPH => Some(Interactable::Switch { x, y }),
+        _ => None,
+    }
+}
+
+// Las entidades de escape no desaparecen al usarlas; las demás se recogen.
+fn entity_interactables(sprite_manager: &SpriteManager) -> Vec<(Vector2, f32, Interactable)> {
+    sprite_manager
+        .sprites
+        .iter()
+        .enumerate()
+        .filter(|(_, sprite)| sprite.entity.interact && !sprite.collected)
+        .map(|(index, sprite)| {
+            let interactable = match sprite.entity.on_pickup {
+                PickupEffect::Escape => Interactable::Exit,
+                _ => Interactable::Pickup { sprite: index },
+            };
+            (Vector2::new(sprite.x, sprite.y), sprite.entity.pickup_radius, interactable)
+        })
+        .collect()
+}
+
+// Lanza un rayo corto desde el centro de la vista y devuelve lo primero con lo que se puede
+// interactuar, ya sea una entidad en el camino o la pared donde termina el rayo.
+pub fn find_target(maze: &Maze, sprite_manager: &SpriteManager, player: &Player, block_size: usize) -> Option<Interactable> {
+    let entities = entity_interactables(sprite_manager);
+    let direction = Vector2::new(player.a.cos(), player.a.sin());
+
+    let mut distance = 0.0;
+    while distance <= INTERACT_RANGE {
+        let point = player.pos + direction * distance;
+
+        if let Some((_, _, entity)) = entities.iter().find(|(position, radius, _)| position.distance_to(point) < *radius) {
+            return Some(*entity);
+        }
+
+        if point.x < 0.0 || point.y < 0.0 {
+            return None;
+        }
+        let x = (point.x / block_size as f32) as usize;
+        let y = (point.y / block_size as f32) as usize;
+        match maze.get(y).and_then(|row| row.get(x)) {
+            Some(' ') => {}
+            Some(&cell) => return cell_interactable(cell, x, y),
+            None => return None,
+        }
+
+        distance += RAY_STEP;
+    }
+    None
+}
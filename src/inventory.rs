@@ -0,0 +1,231 @@
+use raylib::prelude::Vector2;
+use crate::input::TickInput;
+use crate::player::Player;
+use crate::settings::Language;
+
+pub const SMOKE_RADIUS: f32 = 180.0;
+pub const SPEED_BOOST_FACTOR: f32 = 1.4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ItemKind {
+    Decoy,
+    SmokeBomb,
+    SpeedBoost,
+    MapReveal,
+}
+
+pub const ALL_ITEMS: [ItemKind; 4] = [
+    ItemKind::Decoy,
+    ItemKind::SmokeBomb,
+    ItemKind::SpeedBoost,
+    ItemKind::MapReveal,
+];
+
+impl ItemKind {
+    pub fn index(self) -> usize {
+        ALL_ITEMS.iter().position(|&kind| kind == self).unwrap_or(0)
+    }
+
+    pub fn config_name(self) -> &'static str {
+        match self {
+            ItemKind::Decoy => "decoy",
+            ItemKind::SmokeBomb => "smoke_bomb",
+            ItemKind::SpeedBoost => "speed_boost",
+            ItemKind::MapReveal => "map_reveal",
+        }
+    }
+
+    pub fn from_config_name(name: &str) -> Option<Self> {
+        ALL_ITEMS.iter().copied().find(|kind| kind.config_name() == name)
+    }
+
+    pub fn name(self, lang: Language) -> &'static str {
+        match self {
+            ItemKind::Decoy => lang.pick("Señuelo", "Decoy"),
+            ItemKind::SmokeBomb => lang.pick("Bomba de humo", "Smoke bomb"),
+            ItemKind::SpeedBoost => lang.pick("Velocidad", "Speed boost"),
+            ItemKind::MapReveal => lang.pick("Mapa", "Map reveal"),
+        }
+    }
+
+    // Segundos que dura el efecto una vez usado.
+    pub fn duration(self) -> f32 {
+        match self {
+            ItemKind::Decoy => 8.0,
+            ItemKind::SmokeBomb => 10.0,
+            ItemKind::SpeedBoost => 5.0,
+            ItemKind::MapReveal => 12.0,
+        }
+    }
+}
+
+// Las teclas de cambiar y usar se leen como mantenidas en cada tick y aquí se detecta el
+// momento en que se pulsan, así una repetición reproduce exactamente los mismos usos.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Inventory {
+    pub counts: [u32; ALL_ITEMS.len()],
+    pub selected: usize,
+    use_held: bool,
+    next_held: bool,
+}
+
+impl Inventory {
+    pub fn add(&mut self, kind: ItemKind) {
+        self.counts[kind.index()] += 1;
+        if self.counts[self.selected] == 0 {
+            self.selected = kind.index();
+        }
+    }
+
+    pub fn count(&self, kind: ItemKind) -> u32 {
+        self.counts[kind.index()]
+    }
+
+    pub fn selected_item(&self) -> ItemKind {
+        ALL_ITEMS[self.selected % ALL_ITEMS.len()]
+    }
+
+    pub fn select_next(&mut self) {
+        for offset in 1..=ALL_ITEMS.len() {
+            let index = (self.selected + offset) % ALL_ITEMS.len();
+            if self.counts[index] > 0 {
+                self.selected = index;
+                return;
+            }
+        }
+    }
+
+    pub fn handle_input(&mut self, input: &TickInput) -> Option<ItemKind> {
+        let next_pressed = input.next_item && !self.next_held;
+        let use_pressed = input.use_item && !self.use_held;
+        self.next_held = input.next_item;
+        self.use_held = input.use_item;
+
+        if next_pressed {
+            self.select_next();
+        }
+        if !use_pressed || self.counts[self.selected] == 0 {
+            return None;
+        }
+
+        let kind = self.selected_item();
+        self.counts[self.selected] -= 1;
+        if self.counts[self.selected] == 0 {
+            self.select_next();
+        }
+        Some(kind)
+    }
+}
+
+// Señuelos y nubes de humo que quedan en el mapa mientras dura su efecto.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorldEffect {
+    pub kind: ItemKind,
+    pub position: Vector2,
+    pub remaining: f32,
+}
+
+pub fn use_item(kind: ItemKind, player: &mut Player, world_effects: &mut Vec<WorldEffect>) {
+    match kind {
+        ItemKind::Decoy | ItemKind::SmokeBomb => world_effects.push(WorldEffect {
+            kind,
+            position: player.pos,
+            remaining: kind.duration(),
+        }),
+        ItemKind::SpeedBoost => player.speed_boost = kind.duration(),
+        ItemKind::MapReveal => player.map_reveal = kind.duration(),
+    }
+}
+
+pub fn update_world_effects(world_effects: &mut Vec<WorldEffect>, delta_time: f32) {
+    for effect in world_effects.iter_mut() {
+        effect.remaining -= delta_time;
+    }
+    world_effects.retain(|effect| effect.remaining > 0.0);
+}
+
+pub fn nearest_decoy(world_effects: &[WorldEffect], position: Vector2) -> Option<Vector2> {
+    world_effects
+        .iter()
+        .filter(|effect| effect.kind == ItemKind::Decoy)
+        .map(|effect| effect.position)
+        .min_by(|a, b| a.distance_to(position).total_cmp(&b.distance_to(position)))
+}
+
+pub fn is_hidden_by_smoke(world_effects: &[WorldEffect], position: Vector2) -> bool {
+    world_effects
+        .iter()
+        .any(|effect| effect.kind == ItemKind::SmokeBomb && effect.position.distance_to(position) < SMOKE_RADIUS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn held(next_item: bool, use_item: bool) -> TickInput {
+        TickInput { next_item, use_item, ..TickInput::default() }
+    }
+
+    fn stocked() -> Inventory {
+        let mut inventory = Inventory::default();
+        inventory.add(ItemKind::SmokeBomb);
+        inventory.add(ItemKind::SmokeBomb);
+        inventory.add(ItemKind::MapReveal);
+        inventory
+    }
+
+    #[test]
+    fn holding_use_consumes_one_item() {
+        let mut inventory = stocked();
+
+        assert_eq!(inventory.handle_input(&held(false, true)), Some(ItemKind::SmokeBomb));
+        for _ in 0..10 {
+            assert_eq!(inventory.handle_input(&held(false, true)), None);
+        }
+        assert_eq!(inventory.count(ItemKind::SmokeBomb), 1);
+
+        inventory.handle_input(&held(false, false));
+        assert_eq!(inventory.handle_input(&held(false, true)), Some(ItemKind::SmokeBomb));
+    }
+
+    #[test]
+    fn holding_next_cycles_once() {
+        let mut inventory = stocked();
+
+        inventory.handle_input(&held(true, false));
+        inventory.handle_input(&held(true, false));
+        assert_eq!(inventory.selected_item(), ItemKind::MapReveal);
+
+        inventory.handle_input(&held(false, false));
+        inventory.handle_input(&held(true, false));
+        assert_eq!(inventory.selected_item(), ItemKind::SmokeBomb);
+    }
+
+    #[test]
+    fn empty_slot_moves_selection_to_next_item() {
+        let mut inventory = Inventory::default();
+        inventory.add(ItemKind::Decoy);
+        inventory.add(ItemKind::SpeedBoost);
+
+        assert_eq!(inventory.handle_input(&held(false, true)), Some(ItemKind::Decoy));
+        assert_eq!(inventory.selected_item(), ItemKind::SpeedBoost);
+        inventory.handle_input(&held(false, false));
+        assert_eq!(inventory.handle_input(&held(false, true)), Some(ItemKind::SpeedBoost));
+        inventory.handle_input(&held(false, false));
+        assert_eq!(inventory.handle_input(&held(false, true)), None);
+    }
+
+    #[test]
+    fn world_effects_expire() {
+        let mut effects = vec![
+            WorldEffect { kind: ItemKind::Decoy, position: Vector2::new(0.0, 0.0), remaining: 1.0 },
+            WorldEffect { kind: ItemKind::SmokeBomb, position: Vector2::new(100.0, 0.0), remaining: 3.0 },
+        ];
+        update_world_effects(&mut effects, 2.0);
+
+        assert_eq!(effects.len(), 1);
+        assert!(is_hidden_by_smoke(&effects, Vector2::new(150.0, 0.0)));
+        assert!(!is_hidden_by_smoke(&effects, Vector2::new(100.0 + SMOKE_RADIUS, 0.0)));
+        assert_eq!(nearest_decoy(&effects, Vector2::zero()), None);
+    }
+}
@@ -0,0 +1,241 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::config::{config_dir, read_key_values, write_file};
+use crate::difficulty::Difficulty;
+use crate::maze::Maze;
+
+pub const LEADERBOARD_VERSION: u32 = 1;
+pub const MAX_ENTRIES: usize = 10;
+pub const MAX_NAME_LENGTH: usize = 12;
+
+#[derive(Clone, Debug)]
+pub struct LeaderboardEntry {
+    pub time: f32,
+    pub name: String,
+    pub date: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Leaderboard {
+    pub maze_hash: u64,
+    pub difficulty: Difficulty,
+    pub level_name: String,
+    pub entries: Vec<LeaderboardEntry>,
+    read_only: bool,
+}
+
+// FNV-1a sobre el contenido del laberinto: estable entre ejecuciones y versiones de Rust,
+// así que editar un nivel crea una tabla nueva en vez de mezclar tiempos de otro trazado.
+pub fn maze_hash(maze: &Maze) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET;
+    for row in maze {
+        for &cell in row {
+            let mut buffer = [0u8; 4];
+            for byte in cell.encode_utf8(&mut buffer).bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        hash ^= b'\n' as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+pub fn today_string() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let days = (seconds / 86_400) as i64;
+
+    // Conversión de días desde 1970-01-01 a fecha civil (algoritmo de Howard Hinnant).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
+        .take(MAX_NAME_LENGTH)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+impl Leaderboard {
+    fn file_path(maze_hash: u64, difficulty: Difficulty) -> PathBuf {
+        config_dir()
+            .join("leaderboards")
+            .join(format!("{:016x}_{}.board", maze_hash, difficulty.config_name()))
+    }
+
+    pub fn load(maze_hash: u64, difficulty: Difficulty, level_name: &str) -> Self {
+        let pairs = read_key_values(&Self::file_path(maze_hash, difficulty)).unwrap_or_default();
+        Self::from_key_values(maze_hash, difficulty, level_name, pairs)
+    }
+
+    fn from_key_values(maze_hash: u64, difficulty: Difficulty, level_name: &str, pairs: Vec<(String, String)>) -> Self {
+        let mut leaderboard = Self {
+            maze_hash,
+            difficulty,
+            level_name: level_name.to_string(),
+            entries: Vec::new(),
+            read_only: false,
+        };
+
+        let version = pairs
+            .iter()
+            .find(|(key, _)| key == "version")
+            .and_then(|(_, value)| value.parse::<u32>().ok())
+            .unwrap_or(0);
+        if version > LEADERBOARD_VERSION {
+            println!("Tabla de récords con versión {} desconocida, no se modificará", version);
+            leaderboard.read_only = true;
+            return leaderboard;
+        }
+
+        for (key, value) in pairs {
+            if key != "entry" {
+                continue;
+            }
+            let mut fields = value.splitn(3, ';');
+            let (Some(time), Some(name), Some(date)) = (fields.next(), fields.next(), fields.next()) else {
+                continue;
+            };
+            if let Ok(time) = time.trim().parse::<f32>() {
+                leaderboard.entries.push(LeaderboardEntry {
+                    time,
+                    name: sanitize_name(name),
+                    date: date.trim().to_string(),
+                });
+            }
+        }
+
+        leaderboard.entries.sort_by(|a, b| a.time.total_cmp(&b.time));
+        leaderboard.entries.truncate(MAX_ENTRIES);
+        leaderboard
+    }
+
+    pub fn save(&self) {
+        if self.read_only {
+            return;
+        }
+
+        let mut contents = format!("version = {}\n", LEADERBOARD_VERSION);
+        contents.push_str(&format!("level = {}\n", self.level_name));
+        contents.push_str(&format!("difficulty = {}\n", self.difficulty.config_name()));
+        for entry in &self.entries {
+            contents.push_str(&format!("entry = {:.3};{};{}\n", entry.time, entry.name, entry.date));
+        }
+
+        let path = Self::file_path(self.maze_hash, self.difficulty);
+        if let Err(e) = write_file(&path, &contents) {
+            println!("Error guardando récords {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn best_time(&self) -> Option<f32> {
+        self.entries.first().map(|entry| entry.time)
+    }
+
+    // Una tabla de una versión más nueva se muestra pero no se toca.
+    pub fn is_writable(&self) -> bool {
+        !self.read_only
+    }
+
+    pub fn qualifies(&self, time: f32) -> bool {
+        self.is_writable()
+            && (self.entries.len() < MAX_ENTRIES || self.entries.last().is_some_and(|entry| time < entry.time))
+    }
+
+    pub fn insert(&mut self, name: &str, time: f32) -> Option<usize> {
+        if !self.qualifies(time) {
+            return None;
+        }
+
+        let rank = self.entries.iter().position(|entry| time < entry.time).unwrap_or(self.entries.len());
+        self.entries.insert(rank, LeaderboardEntry {
+            time,
+            name: sanitize_name(name),
+            date: today_string(),
+        });
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_key_values;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    fn board(contents: &str) -> Leaderboard {
+        Leaderboard::from_key_values(1, Difficulty::Normal, "Nivel", parse_key_values(contents))
+    }
+
+    #[test]
+    fn maze_hash_is_stable_and_sensitive_to_layout() {
+        let original = maze(&["+++", "+ E", "+++"]);
+
+        assert_eq!(maze_hash(&original), 0xf557_86d2_c75b_f14d);
+        assert_ne!(maze_hash(&original), maze_hash(&maze(&["+++", "+E ", "+++"])));
+        assert_ne!(maze_hash(&maze(&["ab", "c"])), maze_hash(&maze(&["a", "bc"])));
+    }
+
+    #[test]
+    fn entries_are_sorted_and_truncated() {
+        let mut contents = format!("version = {}\n", LEADERBOARD_VERSION);
+        for time in (0..MAX_ENTRIES + 3).rev() {
+            contents.push_str(&format!("entry = {}.5;Ana;2024-01-01\n", time));
+        }
+        contents.push_str("entry = roto;Ana;2024-01-01\n");
+        let leaderboard = board(&contents);
+
+        assert_eq!(leaderboard.entries.len(), MAX_ENTRIES);
+        assert_eq!(leaderboard.best_time(), Some(0.5));
+        assert!(leaderboard.entries.windows(2).all(|pair| pair[0].time <= pair[1].time));
+        assert!(leaderboard.is_writable());
+    }
+
+    #[test]
+    fn newer_version_is_read_only() {
+        let mut leaderboard = board(&format!("version = {}\nentry = 10.0;Ana;2024-01-01\n", LEADERBOARD_VERSION + 1));
+
+        assert!(!leaderboard.is_writable());
+        assert!(!leaderboard.qualifies(1.0));
+        assert_eq!(leaderboard.insert("Bea", 1.0), None);
+    }
+
+    #[test]
+    fn insert_ranks_new_times() {
+        let mut leaderboard = board("version = 1\nentry = 10.0;Ana;2024-01-01\nentry = 20.0;Bea;2024-01-02\n");
+
+        assert_eq!(leaderboard.insert("Caro", 15.0), Some(1));
+        assert_eq!(leaderboard.insert("Dani!!", 5.0), Some(0));
+        assert_eq!(leaderboard.entries[0].name, "Dani");
+        assert_eq!(leaderboard.best_time(), Some(5.0));
+    }
+
+    #[test]
+    fn sanitize_name_limits_characters() {
+        assert_eq!(sanitize_name("  Ana;María#1  "), "AnaMaría1");
+        assert_eq!(sanitize_name("abcdefghijklmnopq").chars().count(), MAX_NAME_LENGTH);
+    }
+}
@@ -6,22 +6,68 @@ mod texture;
 mod sprites;
 mod taylor_sprite;
 mod taylor_ai;
+mod config;
+mod settings;
+mod audio;
+mod input;
+mod controls_menu;
+mod difficulty;
+mod profile;
+mod leaderboard;
+mod replay;
+mod hud;
+mod ghost;
+mod rng;
+mod endless;
+mod net;
+mod taylor_player;
+mod checkpoint;
+mod collision;
+mod interaction;
+mod inventory;
+mod entities;
+mod assets;
+mod hot_reload;
+mod camera;
+mod depth_buffer;
 
-use maze::{Maze, load_maze, extract_sprite_positions, clean_maze};
+use maze::{Maze, load_maze, fallback_maze, extract_entity_positions, clean_maze};
 use caster::render_world_with_textures_sprites_and_taylor;
 use framebuffer::Framebuffer;
-use player::{Player, process_events};
+use player::{Player, PlayerStatus, SPAWN_POSITION, apply_input};
 use texture::TextureManager;
 use sprites::SpriteManager;
 use taylor_sprite::TaylorSprite;
+use settings::{Settings, SettingsMenu, SettingsEvent, SettingsItem};
+use audio::{play_effect, start_music, MUSIC_FILE};
+use input::{Action, TickInput};
+use controls_menu::{ControlsMenu, ControlsEvent};
+use difficulty::Difficulty;
+use profile::Profile;
+use leaderboard::{Leaderboard, maze_hash, MAX_NAME_LENGTH};
+use replay::{Replay, ReplayPlayer, last_run_path, new_seed};
+use ghost::GhostRun;
+use hud::Viewport;
+use endless::{EndlessRun, parse_seed};
+use net::{ClientEvent, LevelInfo, NetClient, NetHost, NetRole, DEFAULT_PORT};
 use raylib::prelude::*;
-use rodio::{Decoder, OutputStream, Sink, Source};
-use std::fs::File;
-use std::io::BufReader;
+use rodio::{OutputStream, Sink};
+use std::path::Path;
 use std::thread;
 use std::time::Duration;
 use std::f32::consts::PI;
-use taylor_ai::TaylorAI;
+use taylor_ai::{Chaser, TaylorAI};
+use taylor_player::TaylorPlayer;
+use checkpoint::{Checkpoint, touched_checkpoint};
+use interaction::{InteractionOutcome, find_target};
+use inventory::{Inventory, WorldEffect, is_hidden_by_smoke, nearest_decoy, update_world_effects, use_item};
+use sprites::CHECKPOINT_ENTITY;
+use entities::PickupEffect;
+use assets::AssetManager;
+use hot_reload::{FileWatcher, is_texture};
+
+pub const TICK_RATE: u32 = 60;
+pub const TICK_DT: f32 = 1.0 / TICK_RATE as f32;
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum GameState {
@@ -30,15 +76,22 @@ pub enum GameState {
     LevelComplete,
     GameOver,
     Victory,
+    Paused,
+    Settings,
+    Controls,
+    Replay,
+    NetClient,
 }
 
-struct GameLevel {
+pub struct GameLevel {
     maze_file: String,
     level_name: String,
     required_cans: usize,
     taylor_speed: f32,
     taylor_spawn_x: f32,
     taylor_spawn_y: f32,
+    exit_x: f32,
+    exit_y: f32,
 }
 
 pub struct GameData {
@@ -51,6 +104,18 @@ pub struct GameData {
     pub taylor_last_move_time: f32,
     pub car_reached: bool,
     pub taylor_ai: TaylorAI,
+    pub extra_chasers: Vec<Chaser>,
+    pub taylor_player: Option<TaylorPlayer>,
+    pub endless: Option<EndlessRun>,
+    pub difficulty: Difficulty,
+    pub maze_hash: u64,
+    pub seed: u64,
+    pub max_lives: u32,
+    pub checkpoints: Vec<Vector2>,
+    pub checkpoint: Option<Checkpoint>,
+    pub resumed_from_checkpoint: bool,
+    pub changed_cells: Vec<(usize, usize, char)>,
+    pub world_effects: Vec<WorldEffect>,
 }
 
 impl GameData {
@@ -63,6 +128,8 @@ impl GameData {
                 taylor_speed: 2.6, 
                 taylor_spawn_x: 250.0,
                 taylor_spawn_y: 950.0,
+                exit_x: 6400.0,
+                exit_y: 150.0,
             },
             GameLevel {
                 maze_file: "levels/level2.txt".to_string(),
@@ -71,6 +138,8 @@ impl GameData {
                 taylor_speed: 3.25, 
                 taylor_spawn_x: 250.0,
                 taylor_spawn_y: 950.0,
+                exit_x: 6400.0,
+                exit_y: 150.0,
             },
             GameLevel {
                 maze_file: "levels/level3.txt".to_string(),
@@ -79,6 +148,8 @@ impl GameData {
                 taylor_speed: 3.9,
                 taylor_spawn_x: 250.0,
                 taylor_spawn_y: 1150.0,
+                exit_x: 6400.0,
+                exit_y: 150.0,
             },
         ];
 
@@ -92,11 +163,39 @@ impl GameData {
             taylor_last_move_time: 0.0,
             car_reached: false,
             taylor_ai: TaylorAI::new(),
+            extra_chasers: Vec::new(),
+            taylor_player: None,
+            endless: None,
+            difficulty: Difficulty::Normal,
+            maze_hash: 0,
+            seed: 0,
+            max_lives: 0,
+            checkpoints: Vec::new(),
+            checkpoint: None,
+            resumed_from_checkpoint: false,
+            changed_cells: Vec::new(),
+            world_effects: Vec::new(),
         }
     }
 
     pub fn get_current_level(&self) -> &GameLevel {
-        &self.levels[self.current_level]
+        match &self.endless {
+            Some(run) => &run.level,
+            None => &self.levels[self.current_level],
+        }
+    }
+
+    pub fn chaser_positions(&self) -> Vec<Vector2> {
+        std::iter::once(self.taylor_position)
+            .chain(self.extra_chasers.iter().map(|chaser| chaser.position))
+            .collect()
+    }
+
+    pub fn nearest_chaser_distance(&self, position: Vector2) -> f32 {
+        self.chaser_positions()
+            .iter()
+            .map(|chaser| chaser.distance_to(position))
+            .fold(f32::INFINITY, f32::min)
     }
 
     pub fn next_level(&mut self) {
@@ -113,15 +212,27 @@ impl GameData {
     pub fn reset_level(&mut self) {
         self.gasoline_collected = 0;
         self.car_reached = false;
-        let level = &self.levels[self.current_level];
+        let level = self.get_current_level();
         self.taylor_position = Vector2::new(level.taylor_spawn_x, level.taylor_spawn_y);
         self.taylor_target = self.taylor_position;
     }
 }
 
+// Un señuelo activo atrae a los perseguidores antes que cualquier jugador, y quien está
+// dentro de una nube de humo no se ve.
+fn chaser_target(players: &[Player], world_effects: &[WorldEffect], position: Vector2) -> Option<Vector2> {
+    nearest_decoy(world_effects, position).or_else(|| {
+        players
+            .iter()
+            .filter(|player| player.is_active() && !is_hidden_by_smoke(world_effects, player.pos))
+            .map(|player| player.pos)
+            .min_by(|a, b| a.distance_to(position).total_cmp(&b.distance_to(position)))
+    })
+}
+
 fn update_taylor_ai(
     game_data: &mut GameData,
-    player: &Player,
+    players: &[Player],
     maze: &Maze,
     block_size: usize,
     delta_time: f32,
@@ -130,68 +241,409 @@ fn update_taylor_ai(
     let current_level_index = game_data.current_level;
     
     let speed_multiplier = match current_level_index {
+        _ if game_data.endless.is_some() => 1.0,
         0 => 1.1,   
         1 => 1.3,   
         2 => 1.6,   
         _ => 1.0,
     };
     
-    let effective_speed = taylor_speed * speed_multiplier;
+    let effective_speed = taylor_speed * speed_multiplier * game_data.difficulty.taylor_speed_multiplier();
     
-    game_data.taylor_ai.update_ai(
-        &mut game_data.taylor_position,
-        player,
-        maze,
-        block_size,
-        delta_time,
-        effective_speed,
-    );
+    let taylor_target = chaser_target(players, &game_data.world_effects, game_data.taylor_position);
+    if let Some(target) = taylor_target.filter(|_| game_data.taylor_player.is_none()) {
+        game_data.taylor_ai.update_ai(
+            &mut game_data.taylor_position,
+            target,
+            maze,
+            block_size,
+            delta_time,
+            effective_speed,
+        );
+    }
+
+    for chaser in &mut game_data.extra_chasers {
+        if let Some(target) = chaser_target(players, &game_data.world_effects, chaser.position) {
+            chaser.ai.update_ai(&mut chaser.position, target, maze, block_size, delta_time, effective_speed);
+        }
+    }
 }
-fn start_level(
+
+fn load_level_maze(
     maze: &mut Maze,
     sprite_manager: &mut SpriteManager,
     game_data: &mut GameData,
-    player: &mut Player,
     block_size: usize,
-    stream_handle: &rodio::OutputStreamHandle,
-    current_sink: &mut Option<Sink>,
 ) {
-    *maze = load_maze(&game_data.get_current_level().maze_file);
-    let sprite_positions = extract_sprite_positions(&maze, block_size);
-    clean_maze(maze);
-    sprite_manager.initialize_gasoline_cans(&sprite_positions);
+    let level_maze = match &game_data.endless {
+        Some(run) => run.maze.clone(),
+        None => load_maze(&game_data.get_current_level().maze_file).unwrap_or_else(|| {
+            println!("Usando maze de fallback...");
+            fallback_maze()
+        }),
+    };
+    install_level_maze(level_maze, maze, sprite_manager, game_data, block_size);
+}
+
+fn install_level_maze(
+    level_maze: Maze,
+    maze: &mut Maze,
+    sprite_manager: &mut SpriteManager,
+    game_data: &mut GameData,
+    block_size: usize,
+) {
+    *maze = level_maze;
+    game_data.maze_hash = maze_hash(maze);
+    game_data.changed_cells.clear();
+    let entity_positions = extract_entity_positions(maze, block_size, |glyph| sprite_manager.entity_for_glyph(glyph));
+    clean_maze(maze, &sprite_manager.entity_glyphs());
+    sprite_manager.initialize_entities(&entity_positions);
+    game_data.checkpoints = sprite_manager
+        .sprites
+        .iter()
+        .filter(|sprite| sprite.is(CHECKPOINT_ENTITY))
+        .map(|sprite| Vector2::new(sprite.x, sprite.y))
+        .collect();
+}
+
+// Recarga el nivel actual sin mover a los jugadores; lo ya recogido sigue recogido. Si el
+// archivo guardado no se puede leer (por ejemplo a medio escribir) se mantiene el laberinto actual.
+fn reload_level_in_place(
+    maze: &mut Maze,
+    sprite_manager: &mut SpriteManager,
+    game_data: &mut GameData,
+    block_size: usize,
+) {
+    let collected: Vec<(String, f32, f32)> = sprite_manager
+        .sprites
+        .iter()
+        .filter(|sprite| sprite.collected)
+        .map(|sprite| (sprite.entity.name.clone(), sprite.x, sprite.y))
+        .collect();
+
+    let Some(level_maze) = load_maze(&game_data.get_current_level().maze_file) else {
+        println!("Se mantiene el laberinto actual");
+        return;
+    };
+    install_level_maze(level_maze, maze, sprite_manager, game_data, block_size);
+
+    for sprite in &mut sprite_manager.sprites {
+        sprite.collected = collected
+            .iter()
+            .any(|(name, x, y)| sprite.is(name) && *x == sprite.x && *y == sprite.y);
+    }
+    sprite_manager.mark_checkpoint(game_data.checkpoint.as_ref().map(|checkpoint| checkpoint.index));
+    sprite_manager.set_world_effects(&game_data.world_effects);
+    println!("Nivel recargado: {}", game_data.get_current_level().maze_file);
+}
+
+fn reset_run(
+    maze: &mut Maze,
+    sprite_manager: &mut SpriteManager,
+    game_data: &mut GameData,
+    players: &mut [Player],
+    block_size: usize,
+    seed: u64,
+) {
+    load_level_maze(maze, sprite_manager, game_data, block_size);
     
-    player.pos = Vector2::new(150.0, 150.0);
+    for player in players.iter_mut() {
+        player.pos = SPAWN_POSITION;
+        player.respawn = SPAWN_POSITION;
+        player.a = PI / 4.0;
+        player.status = PlayerStatus::Active;
+        player.lives = game_data.max_lives;
+        player.invulnerable = 0.0;
+        player.stamina = 1.0;
+        player.exhausted = 0.0;
+        player.inventory = Inventory::default();
+        player.speed_boost = 0.0;
+        player.map_reveal = 0.0;
+    }
+    game_data.seed = seed;
+    game_data.world_effects.clear();
     game_data.game_timer = 0.0;
     game_data.gasoline_collected = 0;
+    game_data.car_reached = false;
+    game_data.checkpoint = None;
+    game_data.resumed_from_checkpoint = false;
     
-    let level = &game_data.levels[game_data.current_level];
+    reset_chasers(game_data);
+    if let Some(taylor) = &mut game_data.taylor_player {
+        *taylor = TaylorPlayer::new(game_data.taylor_position, taylor.body.fov);
+    }
+}
+
+fn reset_chasers(game_data: &mut GameData) {
+    let level = game_data.get_current_level();
     game_data.taylor_position = Vector2::new(level.taylor_spawn_x, level.taylor_spawn_y);
     game_data.taylor_target = game_data.taylor_position;
     
     game_data.taylor_ai = TaylorAI::new();
+    game_data.extra_chasers = game_data
+        .endless
+        .as_ref()
+        .map(|run| run.chaser_spawns.iter().map(|&spawn| Chaser::new(spawn)).collect())
+        .unwrap_or_default();
+}
+
+fn start_level(
+    maze: &mut Maze,
+    sprite_manager: &mut SpriteManager,
+    game_data: &mut GameData,
+    players: &mut [Player],
+    block_size: usize,
+    stream_handle: &rodio::OutputStreamHandle,
+    current_sink: &mut Option<Sink>,
+    settings: &Settings,
+) {
+    let seed = game_data.endless.as_ref().map_or_else(new_seed, |run| run.seed);
+    game_data.max_lives = settings.lives;
+    reset_run(maze, sprite_manager, game_data, players, block_size, seed);
     
-    if let Ok(game_file) = File::open("audio/getaway_car.mp3") {
-        if let Ok(game_source) = Decoder::new(BufReader::new(game_file)) {
-            let sink = Sink::try_new(stream_handle).unwrap();
-            sink.append(game_source.repeat_infinite());
-            sink.set_volume(0.4);
-            sink.play();
-            *current_sink = Some(sink);
+    *current_sink = start_music(stream_handle, MUSIC_FILE, settings.music_volume);
+}
+
+fn start_replay(
+    replay: Replay,
+    maze: &mut Maze,
+    sprite_manager: &mut SpriteManager,
+    game_data: &mut GameData,
+    players: &mut Vec<Player>,
+    block_size: usize,
+) -> ReplayPlayer {
+    game_data.current_level = replay.level_index.min(game_data.levels.len() - 1);
+    game_data.difficulty = replay.difficulty;
+    players.truncate(1);
+    game_data.taylor_player = None;
+    game_data.max_lives = replay.lives;
+    reset_replay_run(&replay, maze, sprite_manager, game_data, players, block_size);
+
+    let mut playback = ReplayPlayer::new(replay);
+    playback.maze_mismatch = game_data.maze_hash != playback.replay.maze_hash;
+    if playback.maze_mismatch {
+        println!("Aviso: el laberinto actual no coincide con el de la repetición, puede divergir");
+    }
+    playback
+}
+
+// La etapa sin fin y su reloj salen siempre de la repetición: al rebobinar, la partida
+// en curso puede haber gastado tiempo o avanzado de etapa.
+fn reset_replay_run(
+    replay: &Replay,
+    maze: &mut Maze,
+    sprite_manager: &mut SpriteManager,
+    game_data: &mut GameData,
+    players: &mut [Player],
+    block_size: usize,
+) {
+    game_data.endless = replay
+        .endless
+        .map(|(stage, time_remaining)| EndlessRun::at_stage(replay.seed, stage, time_remaining, block_size));
+    reset_run(maze, sprite_manager, game_data, players, block_size, replay.seed);
+}
+
+#[derive(Default)]
+struct TickEvents {
+    can_collected: bool,
+    checkpoint_reached: bool,
+    interacted: bool,
+    item_collected: bool,
+    item_used: bool,
+    life_lost: bool,
+    player_caught: bool,
+    caught: bool,
+    timed_out: bool,
+    escaped: bool,
+}
+
+fn apply_pickup(effect: PickupEffect, game_data: &mut GameData, player: &mut Player, events: &mut TickEvents) {
+    match effect {
+        PickupEffect::Gasoline => {
+            game_data.gasoline_collected += 1;
+            events.can_collected = true;
+        }
+        PickupEffect::Item(kind) => {
+            player.inventory.add(kind);
+            events.item_collected = true;
+        }
+        PickupEffect::Escape => {
+            game_data.car_reached = true;
+            player.status = PlayerStatus::Escaped;
+        }
+        PickupEffect::None => {}
+    }
+}
+
+// Un paso fijo de simulación: todo lo que afecta al resultado de la partida pasa por aquí
+// con TICK_DT constante, así una repetición reproduce exactamente la misma partida.
+// Si Taylor la controla un jugador, su entrada va después de la de todos los corredores.
+fn simulate_tick(
+    game_data: &mut GameData,
+    players: &mut [Player],
+    maze: &mut Maze,
+    sprite_manager: &mut SpriteManager,
+    block_size: usize,
+    inputs: &[TickInput],
+) -> TickEvents {
+    let mut events = TickEvents::default();
+    game_data.game_timer += TICK_DT;
+
+    if let Some(run) = &mut game_data.endless {
+        run.time_remaining -= TICK_DT;
+        if run.time_remaining <= 0.0 {
+            events.timed_out = true;
+            return events;
+        }
+    }
+
+    let (inputs, taylor_input) = inputs.split_at(players.len().min(inputs.len()));
+    if let (Some(taylor), Some(input)) = (&mut game_data.taylor_player, taylor_input.first()) {
+        taylor.update(input, &mut game_data.taylor_position, maze, block_size, &game_data.difficulty.sprint_tuning());
+    }
+
+    update_world_effects(&mut game_data.world_effects, TICK_DT);
+    update_taylor_ai(game_data, players, maze, block_size, TICK_DT);
+
+    // Al perder una vida vuelven todos los perseguidores a su punto de salida, si no un
+    // perseguidor que espera junto al punto de reaparición atraparía al jugador otra vez.
+    let mut knock_back = false;
+    for player in players.iter_mut().filter(|player| player.is_active()) {
+        player.invulnerable = (player.invulnerable - TICK_DT).max(0.0);
+        if !player.is_invulnerable() && check_taylor_collision(game_data, player) {
+            if player.lose_life() {
+                events.life_lost = true;
+                knock_back = true;
+            } else {
+                events.player_caught = true;
+            }
         }
     }
+    if knock_back {
+        reset_chasers(game_data);
+    }
+
+    sprite_manager.update(TICK_DT);
+
+    let sprint = game_data.difficulty.sprint_tuning();
+
+    for (player, input) in players.iter_mut().zip(inputs) {
+        if !player.is_active() {
+            continue;
+        }
+
+        apply_input(player, input, maze, block_size, &sprint);
+        player.map_reveal = (player.map_reveal - TICK_DT).max(0.0);
+
+        if let Some(index) = sprite_manager.check_collision(player) {
+            apply_pickup(sprite_manager.sprites[index].entity.on_pickup, game_data, player, &mut events);
+        }
+
+        if let Some(kind) = player.inventory.handle_input(input) {
+            use_item(kind, player, &mut game_data.world_effects);
+            events.item_used = true;
+        }
+
+        if input.interact {
+            if let Some(target) = find_target(maze, sprite_manager, player, block_size) {
+                match target.interact(game_data, player, maze, sprite_manager) {
+                    InteractionOutcome::DoorOpened | InteractionOutcome::SwitchActivated => events.interacted = true,
+                    InteractionOutcome::PickedUp(effect) => apply_pickup(effect, game_data, player, &mut events),
+                    InteractionOutcome::Escaped | InteractionOutcome::Unavailable => {}
+                }
+            }
+        }
+    }
+
+    let current_checkpoint = game_data.checkpoint.as_ref().map(|checkpoint| checkpoint.index);
+    let reached = players
+        .iter()
+        .filter(|player| player.is_active())
+        .find_map(|player| touched_checkpoint(&game_data.checkpoints, player.pos))
+        .filter(|&index| Some(index) != current_checkpoint);
+    if let Some(index) = reached {
+        for player in players.iter_mut() {
+            player.respawn = game_data.checkpoints[index];
+        }
+        game_data.checkpoint = Some(Checkpoint::capture(index, game_data, players, maze, sprite_manager));
+        sprite_manager.mark_checkpoint(Some(index));
+        events.checkpoint_reached = true;
+    }
+
+    sprite_manager.set_world_effects(&game_data.world_effects);
+
+    if players.iter().all(|player| !player.is_active()) {
+        if players.iter().any(|player| player.status == PlayerStatus::Escaped) {
+            events.escaped = true;
+        } else {
+            events.caught = true;
+        }
+    }
+
+    events
+}
+
+fn step_replay(
+    playback: &mut ReplayPlayer,
+    game_data: &mut GameData,
+    players: &mut [Player],
+    maze: &mut Maze,
+    sprite_manager: &mut SpriteManager,
+    block_size: usize,
+) -> bool {
+    let Some(input) = playback.next_input() else {
+        return false;
+    };
+
+    let events = simulate_tick(game_data, players, maze, sprite_manager, block_size, &[input]);
+    if events.caught || events.timed_out || events.escaped {
+        playback.finished = true;
+    }
+    true
+}
+
+fn other_player_positions(players: &[Player], viewer_index: usize) -> Vec<Vector2> {
+    players
+        .iter()
+        .enumerate()
+        .filter(|(index, player)| *index != viewer_index && player.is_active())
+        .map(|(_, player)| player.pos)
+        .collect()
 }
 
+fn render_taylor_view(
+    framebuffer: &mut Framebuffer,
+    maze: &Maze,
+    block_size: usize,
+    game_data: &GameData,
+    players: &[Player],
+    texture_manager: &TextureManager,
+    sprite_manager: &mut SpriteManager,
+    taylor_sprite: &TaylorSprite,
+) {
+    let Some(taylor) = &game_data.taylor_player else {
+        return;
+    };
+    let other_chasers: Vec<Vector2> = game_data.extra_chasers.iter().map(|chaser| chaser.position).collect();
+    sprite_manager.set_remote_players(&other_player_positions(players, usize::MAX));
+    render_world_with_textures_sprites_and_taylor(
+        framebuffer,
+        maze,
+        block_size,
+        &taylor.body,
+        texture_manager,
+        sprite_manager,
+        taylor_sprite,
+        &other_chasers,
+        None,
+    );
+}
 
+// El margen de cada nivel se suma al radio de captura, no a los cuerpos de colisión.
 fn check_taylor_collision(game_data: &GameData, player: &Player) -> bool {
-    const TAYLOR_RADIUS: f32 = 25.0;
-    const PLAYER_RADIUS: f32 = 15.0;
+    let distance = game_data.nearest_chaser_distance(player.pos);
     
-    let dx = game_data.taylor_position.x - player.pos.x;
-    let dy = game_data.taylor_position.y - player.pos.y;
-    let distance = (dx * dx + dy * dy).sqrt();
-    
-    let collision_distance = TAYLOR_RADIUS + PLAYER_RADIUS + match game_data.current_level {
+    let collision_distance = collision::CATCH_RADIUS + match game_data.current_level {
         0 => 10.0,
         1 => 5.0,
         2 => 0.0,
@@ -201,9 +653,41 @@ fn check_taylor_collision(game_data: &GameData, player: &Player) -> bool {
     distance < collision_distance
 }
 
+fn load_ghost(game_data: &GameData, profile: &Profile) -> Option<GhostRun> {
+    if game_data.endless.is_some() {
+        return None;
+    }
+    GhostRun::load(&profile.name, game_data.maze_hash, game_data.difficulty)
+}
+
+fn apply_display_settings(
+    window: &mut RaylibHandle,
+    framebuffer: &mut Framebuffer,
+    settings: &Settings,
+) {
+    if settings.fullscreen != window.is_window_fullscreen() {
+        window.toggle_fullscreen();
+    }
+    window.set_window_size(settings.window_width, settings.window_height);
+
+    *framebuffer = create_framebuffer(settings);
+}
+
+// El framebuffer se crea a la resolución interna y se estira a la ventana al presentarlo.
+fn create_framebuffer(settings: &Settings) -> Framebuffer {
+    let (width, height) = settings.render_scale.internal_size(settings.window_width, settings.window_height);
+    let mut framebuffer = Framebuffer::new(width.max(1) as u32, height.max(1) as u32);
+    framebuffer.set_background_color(Color::new(10, 10, 30, 255));
+    framebuffer.set_render_threads(settings.render_threads);
+    framebuffer
+}
+
 fn main() {
-    let window_width = 1280;
-    let window_height = 720;
+    let mut settings = Settings::load();
+    let mut settings_menu = SettingsMenu::new();
+    let mut controls_menu = ControlsMenu::new();
+    let mut window_width = settings.window_width;
+    let mut window_height = settings.window_height;
     let block_size = 100;
 
     let (mut window, raylib_thread) = raylib::init()
@@ -212,6 +696,10 @@ fn main() {
         .log_level(TraceLogLevel::LOG_WARNING)
         .build();
 
+    if settings.fullscreen {
+        window.toggle_fullscreen();
+    }
+
     window.hide_cursor();
     window.disable_cursor();
 
@@ -234,13 +722,31 @@ fn main() {
         println!("No se detectó ningún control");
     }
 
-    let mut framebuffer = Framebuffer::new(window_width as u32, window_height as u32);
-    framebuffer.set_background_color(Color::new(10, 10, 30, 255));
+    let mut framebuffer = create_framebuffer(&settings);
 
-    let texture_manager = TextureManager::new();
-    let mut sprite_manager = SpriteManager::new();
-    let mut taylor_sprite = TaylorSprite::new();
+    let mut assets = AssetManager::new();
+    let mut texture_manager = TextureManager::new(&mut assets, settings.texture_filter);
+    let mut sprite_manager = SpriteManager::new(&mut assets);
+    let mut taylor_sprite = TaylorSprite::new(&mut assets);
+    assets.print_usage();
     let mut game_data = GameData::new();
+    let mut profile = Profile::load_or_create(&settings.profile_name, game_data.levels.len());
+    let mut new_personal_best = false;
+    let mut leaderboard: Option<Leaderboard> = None;
+    let mut leaderboard_rank: Option<usize> = None;
+    let mut name_entry: Option<String> = None;
+    let mut new_record = false;
+    let mut recording = Replay::for_run(&game_data);
+    let mut replay_player: Option<ReplayPlayer> = None;
+    let mut tick_accumulator = 0.0;
+    let mut pending_look = 0.0;
+    let mut ghost: Option<GhostRun> = None;
+    let mut ghost_recording = GhostRun::new();
+    let mut new_endless_best = false;
+    let mut endless_seed_arg = std::env::args()
+        .skip_while(|arg| arg != "--seed")
+        .nth(1)
+        .and_then(|seed| parse_seed(&seed));
 
     let (_stream, stream_handle) = OutputStream::try_default().unwrap();
     let mut current_sink: Option<Sink> = None;
@@ -248,22 +754,56 @@ fn main() {
     let mut state = GameState::Menu;
     let mut last_time = std::time::Instant::now();
 
-    let mut maze = load_maze(&game_data.get_current_level().maze_file);
-    let sprite_positions = extract_sprite_positions(&maze, block_size);
-    clean_maze(&mut maze);
-    sprite_manager.initialize_gasoline_cans(&sprite_positions);
+    let mut maze = Maze::new();
+    load_level_maze(&mut maze, &mut sprite_manager, &mut game_data, block_size);
 
-    let mut player = Player {
-        pos: Vector2::new(150.0, 150.0),
-        a: PI / 4.0,
-        fov: PI / 3.0,
-    };
+    let mut players = vec![Player::new(settings.fov_radians())];
+    let mut coop_enabled = false;
+    let mut versus_enabled = false;
+
+    let net_port = std::env::args()
+        .skip_while(|arg| arg != "--port")
+        .nth(1)
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_PORT);
+    let join_address = std::env::args()
+        .skip_while(|arg| arg != "--join")
+        .nth(1)
+        .unwrap_or_else(|| format!("127.0.0.1:{}", net_port));
+    let mut net_host: Option<NetHost> = None;
+    let mut net_client: Option<NetClient> = None;
+    let mut file_watcher = std::env::args().any(|arg| arg == "--dev").then(FileWatcher::new);
+
+    if let Some(path) = std::env::args().skip_while(|arg| arg != "--replay").nth(1) {
+        if let Some(replay) = Replay::load(Path::new(&path)) {
+            replay_player = Some(start_replay(replay, &mut maze, &mut sprite_manager, &mut game_data, &mut players, block_size));
+            state = GameState::Replay;
+        }
+    }
 
     while !window.window_should_close() {
         let current_time = std::time::Instant::now();
         let delta_time = current_time.duration_since(last_time).as_secs_f32();
         last_time = current_time;
 
+        if let Some(watcher) = &mut file_watcher {
+            let changed = watcher.poll(delta_time);
+            let mut textures_changed = false;
+            for path in &changed {
+                if is_texture(path) {
+                    textures_changed |= assets.reload(path);
+                } else if game_data.endless.is_none() && *path == game_data.get_current_level().maze_file {
+                    reload_level_in_place(&mut maze, &mut sprite_manager, &mut game_data, block_size);
+                }
+            }
+            if textures_changed {
+                texture_manager = TextureManager::new(&mut assets, settings.texture_filter);
+                sprite_manager.refresh_textures(&mut assets);
+                taylor_sprite.refresh_texture(&mut assets);
+                assets.print_usage();
+            }
+        }
+
         framebuffer.clear();
 
         match state {
@@ -279,8 +819,10 @@ fn main() {
                     Color::WHITE,
                 );
                 
+                let lang = settings.language;
+
                 d.draw_text(
-                    "¡Escapa de Taylor Swift!",
+                    lang.pick("¡Escapa de Taylor Swift!", "Escape from Taylor Swift!"),
                     window_width / 2 - 200,
                     220,
                     28,
@@ -288,47 +830,85 @@ fn main() {
                 );
                 
                 d.draw_text(
-                    "SELECCIONA NIVEL:",
+                    lang.pick("SELECCIONA NIVEL:", "SELECT LEVEL:"),
                     window_width / 2 - 140,
                     300,
                     32,
                     Color::GREEN,
                 );
                 
+                let level_labels = [
+                    lang.pick("1 - Hollywood Studio (Fácil)", "1 - Hollywood Studio (Easy)"),
+                    lang.pick("2 - Recording Studio (Medio)", "2 - Recording Studio (Medium)"),
+                    lang.pick("3 - Concert Venue (Difícil)", "3 - Concert Venue (Hard)"),
+                ];
+
+                for (level_index, label) in level_labels.iter().enumerate() {
+                    let best_time = profile.levels.get(level_index).and_then(|level| level.best_time);
+                    let (text, color) = if !profile.is_unlocked(level_index) {
+                        (format!("{} - {}", label, lang.pick("BLOQUEADO", "LOCKED")), Color::DARKGRAY)
+                    } else if let Some(best_time) = best_time {
+                        (format!("{} - {:.1}s", label, best_time), Color::GREEN)
+                    } else {
+                        (label.to_string(), Color::WHITE)
+                    };
+
+                    d.draw_text(&text, window_width / 2 - 210, 360 + level_index as i32 * 40, 24, color);
+                }
+
+                let coop_label = match (coop_enabled, d.is_gamepad_available(1)) {
+                    (false, _) => lang.pick("No", "Off"),
+                    (true, true) => lang.pick("Sí", "On"),
+                    (true, false) => lang.pick("Sí (conecta un segundo control)", "On (connect a second gamepad)"),
+                };
+
+                let versus_label = match (versus_enabled, net_host.is_some(), d.is_gamepad_available(1)) {
+                    (false, _, _) => lang.pick("No", "Off"),
+                    (true, true, _) => lang.pick("Sí (Taylor: primer jugador LAN)", "On (Taylor: first LAN player)"),
+                    (true, false, true) => lang.pick("Sí (Taylor: control 2)", "On (Taylor: gamepad 2)"),
+                    (true, false, false) => lang.pick("Sí (conecta un segundo control)", "On (connect a second gamepad)"),
+                };
+
                 d.draw_text(
-                    "1 - Hollywood Studio (Fácil)",
-                    window_width / 2 - 200,
-                    360,
-                    24,
-                    Color::WHITE,
-                );
-                
-                d.draw_text(
-                    "2 - Recording Studio (Medio)",
+                    &format!(
+                        "{}: {}   {}: {}   {}: {}   Versus: {}",
+                        lang.pick("Perfil", "Profile"),
+                        profile.name,
+                        lang.pick("Dificultad", "Difficulty"),
+                        game_data.difficulty.label(lang),
+                        lang.pick("Cooperativo", "Co-op"),
+                        coop_label,
+                        versus_label,
+                    ),
                     window_width / 2 - 210,
-                    400,
-                    24,
-                    Color::WHITE,
-                );
-                
-                d.draw_text(
-                    "3 - Concert Venue (Difícil)",
-                    window_width / 2 - 190,
-                    440,
-                    24,
-                    Color::WHITE,
+                    480,
+                    20,
+                    Color::SKYBLUE,
                 );
                 
                 d.draw_text(
-                    "WASD - Moverse, Mouse - Mirar",
-                    window_width / 2 - 180,
+                    &format!(
+                        "{}, Mouse - {}",
+                        settings.controls.hint(&[
+                            (Action::MoveForward, lang.pick("Avanzar", "Forward")),
+                            (Action::MoveBackward, lang.pick("Retroceder", "Back")),
+                            (Action::StrafeLeft, lang.pick("Izquierda", "Left")),
+                            (Action::StrafeRight, lang.pick("Derecha", "Right")),
+                        ]),
+                        lang.pick("Mirar", "Look"),
+                    ),
+                    window_width / 2 - 330,
                     520,
                     20,
                     Color::LIGHTGRAY,
                 );
                 
                 d.draw_text(
-                    "E - Abrir puerta (con 3 bidones)",
+                    &format!(
+                        "{} - {}",
+                        settings.controls.label(Action::Interact),
+                        lang.pick("Abrir puerta (con 3 bidones)", "Open door (with 3 cans)"),
+                    ),
                     window_width / 2 - 190,
                     550,
                     20,
@@ -336,248 +916,448 @@ fn main() {
                 );
 
                 d.draw_text(
-                    "Gamepad: O-Nivel1, △-Nivel2, □-Nivel3",
-                    window_width / 2 - 200,
+                    &format!(
+                        "Gamepad: {}",
+                        settings.controls.gamepad_only(settings.controls.gamepad).hint(&[
+                            (Action::Level1, lang.pick("Nivel 1", "Level 1")),
+                            (Action::Level2, lang.pick("Nivel 2", "Level 2")),
+                            (Action::Level3, lang.pick("Nivel 3", "Level 3")),
+                        ]),
+                    ),
+                    window_width / 2 - 280,
                     580,
                     20,
                     Color::LIGHTGRAY,
                 );
-                            
-                if d.is_key_pressed(KeyboardKey::KEY_ONE) {
-                    game_data.current_level = 0;
-                    state = GameState::Playing;
-                    start_level(&mut maze, &mut sprite_manager, &mut game_data, &mut player, block_size, &stream_handle, &mut current_sink);
+
+                d.draw_text(
+                    &format!(
+                        "{} ({}: {})",
+                        settings.controls.hint(&[(Action::EndlessMode, lang.pick("Modo infinito", "Endless mode"))]),
+                        lang.pick("récord", "best"),
+                        profile.endless_best_stage,
+                    ),
+                    window_width / 2 - 160,
+                    605,
+                    20,
+                    Color::ORANGE,
+                );
+
+                let lan_label = match &net_host {
+                    Some(host) => format!(
+                        "{} {} ({} {})",
+                        settings.controls.hint(&[(Action::HostLan, lang.pick("Dejar de hospedar, puerto", "Stop hosting, port"))]),
+                        net_port,
+                        host.connected_count(),
+                        lang.pick("conectados", "connected"),
+                    ),
+                    None => format!(
+                        "{} {}, {} {}",
+                        settings.controls.hint(&[(Action::HostLan, lang.pick("Hospedar LAN en puerto", "Host LAN on port"))]),
+                        net_port,
+                        settings.controls.hint(&[(Action::JoinLan, lang.pick("Unirse a", "Join"))]),
+                        join_address,
+                    ),
+                };
+                d.draw_text(&lan_label, window_width / 2 - 300, 690, 20, Color::SKYBLUE);
+
+                d.draw_text(
+                    &settings.controls.hint(&[
+                        (Action::OpenSettings, lang.pick("Opciones", "Settings")),
+                        (Action::WatchReplay, lang.pick("Ver última repetición", "Watch last replay")),
+                    ]),
+                    window_width / 2 - 220,
+                    630,
+                    20,
+                    Color::SKYBLUE,
+                );
+
+                d.draw_text(
+                    &settings.controls.hint(&[
+                        (Action::SwitchProfile, lang.pick("Cambiar perfil", "Switch profile")),
+                        (Action::NewProfile, lang.pick("Nuevo perfil", "New profile")),
+                        (Action::CycleDifficulty, lang.pick("Dificultad", "Difficulty")),
+                        (Action::ToggleCoop, lang.pick("Cooperativo", "Co-op")),
+                        (Action::ToggleVersus, lang.pick("Versus", "Versus")),
+                        (Action::UnlockAll, lang.pick("Desbloquear todo", "Unlock all")),
+                    ]),
+                    window_width / 2 - 450,
+                    665,
+                    20,
+                    Color::GRAY,
+                );
+
+                if settings.controls.is_pressed(&d, Action::SwitchProfile) {
+                    let names = Profile::list_names();
+                    if !names.is_empty() {
+                        let next_index = names
+                            .iter()
+                            .position(|name| *name == profile.name)
+                            .map_or(0, |index| (index + 1) % names.len());
+                        profile = Profile::load_or_create(&names[next_index], game_data.levels.len());
+                        settings.profile_name = profile.name.clone();
+                        settings.save();
+                    }
                 }
-                if d.is_key_pressed(KeyboardKey::KEY_TWO) {
-                    game_data.current_level = 1;
-                    state = GameState::Playing;
-                    start_level(&mut maze, &mut sprite_manager, &mut game_data, &mut player, block_size, &stream_handle, &mut current_sink);
+
+                if settings.controls.is_pressed(&d, Action::NewProfile) {
+                    let names = Profile::list_names();
+                    let new_name = (1..)
+                        .map(|number| format!("{} {}", lang.pick("Jugador", "Player"), number))
+                        .find(|name| !names.contains(name))
+                        .unwrap();
+                    profile = Profile::load_or_create(&new_name, game_data.levels.len());
+                    settings.profile_name = profile.name.clone();
+                    settings.save();
                 }
-                if d.is_key_pressed(KeyboardKey::KEY_THREE) {
-                    game_data.current_level = 2;
-                    state = GameState::Playing;
-                    start_level(&mut maze, &mut sprite_manager, &mut game_data, &mut player, block_size, &stream_handle, &mut current_sink);
+
+                if settings.controls.is_pressed(&d, Action::CycleDifficulty) {
+                    game_data.difficulty = game_data.difficulty.next();
                 }
 
-                if d.is_gamepad_available(0) {
-                    if d.is_gamepad_button_pressed(0, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT) {
-                        game_data.current_level = 0;
-                        state = GameState::Playing;
-                        start_level(&mut maze, &mut sprite_manager, &mut game_data, &mut player, block_size, &stream_handle, &mut current_sink);
+                if settings.controls.is_pressed(&d, Action::ToggleCoop) {
+                    coop_enabled = !coop_enabled;
+                    if coop_enabled {
+                        net_host = None;
+                        versus_enabled = false;
                     }
-                    if d.is_gamepad_button_pressed(0, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP) { 
-                        game_data.current_level = 1;
-                        state = GameState::Playing;
-                        start_level(&mut maze, &mut sprite_manager, &mut game_data, &mut player, block_size, &stream_handle, &mut current_sink);
+                }
+
+                if settings.controls.is_pressed(&d, Action::ToggleVersus) {
+                    versus_enabled = !versus_enabled;
+                    if versus_enabled {
+                        coop_enabled = false;
+                    }
+                }
+
+                if let Some(host) = &mut net_host {
+                    host.poll();
+                }
+
+                if settings.controls.is_pressed(&d, Action::HostLan) {
+                    net_host = match net_host {
+                        Some(_) => {
+                            println!("Partida LAN cerrada");
+                            None
+                        }
+                        None => match NetHost::bind(net_port) {
+                            Ok(host) => {
+                                coop_enabled = false;
+                                Some(host)
+                            }
+                            Err(e) => {
+                                println!("No se pudo abrir el puerto {}: {}", net_port, e);
+                                None
+                            }
+                        },
+                    };
+                }
+
+                if settings.controls.is_pressed(&d, Action::JoinLan) {
+                    match NetClient::connect(&join_address, &profile.name) {
+                        Ok(client) => {
+                            net_host = None;
+                            net_client = Some(client);
+                            game_data.endless = None;
+                            state = GameState::NetClient;
+                            continue;
+                        }
+                        Err(e) => println!("No se pudo conectar a {}: {}", join_address, e),
                     }
-                    if d.is_gamepad_button_pressed(0, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT) { 
-                        game_data.current_level = 2;
-                        state = GameState::Playing;
-                        start_level(&mut maze, &mut sprite_manager, &mut game_data, &mut player, block_size, &stream_handle, &mut current_sink);
+                }
+
+                if settings.controls.is_pressed(&d, Action::UnlockAll) {
+                    profile.unlock_all = !profile.unlock_all;
+                    profile.save();
+                }
+
+                if settings.controls.is_pressed(&d, Action::OpenSettings) {
+                    settings_menu = SettingsMenu::new();
+                    state = GameState::Settings;
+                }
+                            
+                let selected_level = [Action::Level1, Action::Level2, Action::Level3]
+                    .iter()
+                    .position(|&action| settings.controls.is_pressed(&d, action));
+
+                let player_count = if coop_enabled { 2 } else { 1 };
+                let local_versus = versus_enabled && net_host.is_none();
+
+                if let Some(level_index) = selected_level.filter(|&index| profile.is_unlocked(index)) {
+                    game_data.current_level = level_index;
+                    game_data.endless = None;
+                    game_data.taylor_player = local_versus.then(|| TaylorPlayer::new(Vector2::zero(), settings.fov_radians()));
+                    players.resize_with(player_count, || Player::new(settings.fov_radians()));
+                    state = GameState::Playing;
+                    start_level(&mut maze, &mut sprite_manager, &mut game_data, &mut players, block_size, &stream_handle, &mut current_sink, &settings);
+                    recording = Replay::for_run(&game_data);
+                    ghost = load_ghost(&game_data, &profile);
+                    ghost_recording = GhostRun::new();
+                }
+
+                if net_host.is_none() && settings.controls.is_pressed(&d, Action::EndlessMode) {
+                    let seed = endless_seed_arg.take().unwrap_or_else(new_seed);
+                    println!("Modo infinito con semilla {}", seed);
+                    game_data.endless = Some(EndlessRun::new(seed, block_size));
+                    game_data.taylor_player = local_versus.then(|| TaylorPlayer::new(Vector2::zero(), settings.fov_radians()));
+                    players.resize_with(player_count, || Player::new(settings.fov_radians()));
+                    state = GameState::Playing;
+                    start_level(&mut maze, &mut sprite_manager, &mut game_data, &mut players, block_size, &stream_handle, &mut current_sink, &settings);
+                    recording = Replay::for_run(&game_data);
+                    ghost = None;
+                    ghost_recording = GhostRun::new();
+                }
+
+                if settings.controls.is_pressed(&d, Action::WatchReplay) {
+                    if let Some(replay) = Replay::load(&last_run_path()) {
+                        replay_player = Some(start_replay(replay, &mut maze, &mut sprite_manager, &mut game_data, &mut players, block_size));
+                        state = GameState::Replay;
                     }
                 }
             }
 
             GameState::Playing => {
-                game_data.game_timer += delta_time;
-                
-                let dx = game_data.taylor_position.x - player.pos.x;
-                let dy = game_data.taylor_position.y - player.pos.y;
-                let taylor_distance = (dx * dx + dy * dy).sqrt();
-                taylor_sprite.update(delta_time, taylor_distance);
-                
-                update_taylor_ai(&mut game_data, &player, &maze, block_size, delta_time);
-                
-                if check_taylor_collision(&game_data, &player) {
-                    state = GameState::GameOver;
+                if settings.controls.is_pressed(&window, Action::Pause) {
+                    state = GameState::Paused;
                     if let Some(sink) = &current_sink {
-                        sink.stop();
+                        sink.pause();
                     }
-                    
-                    if let Ok(death_file) = File::open("audio/caught.mp3") {
-                        if let Ok(death_source) = Decoder::new(BufReader::new(death_file)) {
-                            if let Ok(effect_sink) = Sink::try_new(&stream_handle) {
-                                effect_sink.append(death_source);
-                                effect_sink.set_volume(0.8);
-                                effect_sink.play();
-                                effect_sink.detach();
-                            }
+                    continue;
+                }
+
+                if let Some(host) = &mut net_host {
+                    host.poll();
+                    players.resize_with(1 + host.remote_player_count(), || Player::new(settings.fov_radians()));
+                    for player_index in host.disconnected_runners() {
+                        let player = &mut players[player_index];
+                        if player.is_active() {
+                            player.status = PlayerStatus::Caught;
                         }
                     }
-                    continue;
+                    if host.taylor_connected() != game_data.taylor_player.is_some() {
+                        game_data.taylor_player = host
+                            .taylor_connected()
+                            .then(|| TaylorPlayer::new(game_data.taylor_position, settings.fov_radians()));
+                    }
+                    host.sync_level(LevelInfo {
+                        level_index: game_data.current_level,
+                        difficulty: game_data.difficulty,
+                        maze_hash: game_data.maze_hash,
+                        seed: game_data.seed,
+                        versus: versus_enabled,
+                    });
                 }
 
-                process_events(&mut player, &window, &maze, block_size);
-                
-                let mouse_delta = window.get_mouse_delta();
-                const MOUSE_SENSITIVITY: f32 = 0.003;
-                player.a += mouse_delta.x * MOUSE_SENSITIVITY;
+                // Solo las partidas individuales y sin reanudar desde un punto de control se pueden
+                // reproducir desde la semilla, así que solo esas se graban y puntúan.
+                let recordable = players.len() == 1
+                    && game_data.taylor_player.is_none()
+                    && !game_data.resumed_from_checkpoint;
+                let local_players = if net_host.is_some() { 1 } else { players.len() };
+                let local_taylor = net_host.is_none() && game_data.taylor_player.is_some();
 
-                if window.is_gamepad_available(0) {
-                    let right_stick_x = window.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_RIGHT_X);
-                    if right_stick_x.abs() > 0.2 {
-                        player.a += right_stick_x * 0.05; 
+                if recordable && settings.controls.is_pressed(&window, Action::SaveReplay) {
+                    if let Some(path) = recording.save_snapshot() {
+                        println!("Repetición guardada en {}", path.display());
                     }
                 }
 
-                if player.a < 0.0 { player.a += 2.0 * PI; }
-                else if player.a > 2.0 * PI { player.a -= 2.0 * PI; }
+                let mut tick_inputs = vec![settings.controls.sample(&window, settings.gamepad_sensitivity)];
+                if local_players > 1 || local_taylor {
+                    tick_inputs.push(settings.controls.gamepad_only(1).sample(&window, settings.gamepad_sensitivity));
+                }
+                pending_look += window.get_mouse_delta().x * settings.mouse_sensitivity * settings.look_sign();
 
-                sprite_manager.update(delta_time);
+                tick_accumulator += delta_time.min(0.25);
+                let mut caught = false;
+                let mut escaped = false;
 
-                if let Some(_) = sprite_manager.check_collision(&player, 30.0) {
-                    game_data.gasoline_collected += 1;
-                    
-                    if let Ok(collect_file) = File::open("audio/gasoline_pickup.mp3") {
-                        if let Ok(collect_source) = Decoder::new(BufReader::new(collect_file)) {
-                            if let Ok(effect_sink) = Sink::try_new(&stream_handle) {
-                                effect_sink.append(collect_source);
-                                effect_sink.set_volume(0.8);
-                                effect_sink.play();
-                                effect_sink.detach();
-                            }
-                        }
+                while tick_accumulator >= TICK_DT && !caught && !escaped {
+                    tick_accumulator -= TICK_DT;
+                    tick_inputs[0].look = pending_look;
+                    pending_look = 0.0;
+                    if recordable {
+                        ghost_recording.record(recording.inputs.len(), &players[0]);
+                        recording.inputs.push(tick_inputs[0]);
                     }
-                    
-                    println!("¡Gasolina recolectada! {}/{}", 
-                            game_data.gasoline_collected, 
-                            game_data.get_current_level().required_cans);
-                }
-
-                if game_data.gasoline_collected >= game_data.get_current_level().required_cans {
-                    if window.is_key_down(KeyboardKey::KEY_E) || 
-                    (window.is_gamepad_available(0) && window.is_gamepad_button_down(0, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)) {
-                        let car_pos = Vector2::new(6400.0, 150.0);
-                        let dx = player.pos.x - car_pos.x;
-                        let dy = player.pos.y - car_pos.y;
-                        let distance = (dx * dx + dy * dy).sqrt();
+
+                    let mut inputs = tick_inputs.clone();
+                    if let Some(host) = &mut net_host {
+                        let (runner_inputs, taylor_input) = host.take_inputs();
+                        inputs.extend(runner_inputs);
+                        inputs.extend(taylor_input);
+                    }
+
+                    let events = simulate_tick(&mut game_data, &mut players, &mut maze, &mut sprite_manager, block_size, &inputs);
+
+                    if events.life_lost || (events.player_caught && !events.caught) {
+                        play_effect(&stream_handle, "audio/caught.mp3", settings.sfx_volume);
+                    }
+
+                    if events.checkpoint_reached {
+                        play_effect(&stream_handle, "audio/gasoline_pickup.mp3", settings.sfx_volume);
+                        println!("Punto de control alcanzado en {:.1}s", game_data.game_timer);
+                    }
+
+                    if events.interacted || events.item_used {
+                        play_effect(&stream_handle, "audio/level_complete.mp3", settings.sfx_volume);
+                    }
+
+                    if events.item_collected {
+                        play_effect(&stream_handle, "audio/gasoline_pickup.mp3", settings.sfx_volume);
+                    }
+
+                    if events.can_collected {
+                        play_effect(&stream_handle, "audio/gasoline_pickup.mp3", settings.sfx_volume);
                         
-                        if distance < 80.0 {
-                            if game_data.current_level >= game_data.levels.len() - 1 {
-                                state = GameState::Victory;
-                            } else {
-                                state = GameState::LevelComplete;
-                            }
-                            
-                            if let Some(sink) = &current_sink {
-                                sink.stop();
-                            }
-                            
-                            if let Ok(success_file) = File::open("audio/level_complete.mp3") {
-                                if let Ok(success_source) = Decoder::new(BufReader::new(success_file)) {
-                                    if let Ok(effect_sink) = Sink::try_new(&stream_handle) {
-                                        effect_sink.append(success_source);
-                                        effect_sink.set_volume(0.8);
-                                        effect_sink.play();
-                                        effect_sink.detach();
-                                    }
-                                }
-                            }
-                            continue;
-                        }
+                        println!("¡Gasolina recolectada! {}/{}", 
+                                game_data.gasoline_collected, 
+                                game_data.get_current_level().required_cans);
                     }
+                    caught = events.caught || events.timed_out;
+                    escaped = events.escaped;
                 }
 
-                render_world_with_textures_sprites_and_taylor(
-                    &mut framebuffer,
-                    &maze,
-                    block_size,
-                    &player,
-                    &texture_manager,
-                    &sprite_manager,
-                    &taylor_sprite,
-                    game_data.taylor_position,
-                );
+                let nearest_distance = players
+                    .iter()
+                    .map(|player| game_data.nearest_chaser_distance(player.pos))
+                    .fold(f32::INFINITY, f32::min);
+                taylor_sprite.update(delta_time, nearest_distance);
 
-                let framebuffer_texture = framebuffer.get_texture(&mut window, &raylib_thread);
+                if let Some(host) = &mut net_host {
+                    host.broadcast_snapshot(&game_data, &players, &sprite_manager);
+                    if caught || escaped {
+                        host.broadcast_end(escaped);
+                    }
+                }
 
-                let mut d = window.begin_drawing(&raylib_thread);
-                d.clear_background(Color::BLACK);
-                
-                if let Ok(texture) = &framebuffer_texture {
-                    d.draw_texture(texture, 0, 0, Color::WHITE);
+                if recordable && (caught || escaped) {
+                    recording.save(&last_run_path());
                 }
-                
-                d.draw_text(
-                    &format!("Nivel: {}", game_data.get_current_level().level_name),
-                    20, 20, 28, Color::WHITE,
-                );
-                
-                d.draw_text(
-                    &format!("Gasolina: {}/{}", 
-                            game_data.gasoline_collected, 
-                            game_data.get_current_level().required_cans),
-                    20, 55, 28, Color::YELLOW,
-                );
-                
-                d.draw_text(
-                    &format!("Tiempo: {:.1}s", game_data.game_timer),
-                    20, 90, 28, Color::WHITE,
-                );
-                
-                if taylor_distance < 150.0 {
-                    d.draw_text(
-                        "¡TAYLOR ESTÁ CERCA!",
-                        window_width / 2 - 200, 160,
-                        36, Color::RED,
-                    );
+                if caught || escaped {
+                    tick_accumulator = 0.0;
                 }
                 
-                let minimap_size = 300;
-                let map_rows = maze.len();
-                let map_cols = maze[0].len();
-                let scale_x = minimap_size as f32 / (map_cols * block_size) as f32;
-                let scale_y = minimap_size as f32 / (map_rows * block_size) as f32;
-                let offset_x = window_width - minimap_size - 20;
-                let offset_y = 20;
-
-                d.draw_rectangle(offset_x, offset_y, minimap_size, minimap_size, Color::new(0, 0, 0, 150));
-
-                for (row_index, row) in maze.iter().enumerate() {
-                    for (col_index, &cell) in row.iter().enumerate() {
-                        if cell != ' ' {
-                            let x = offset_x + ((col_index * block_size) as f32 * scale_x) as i32;
-                            let y = offset_y + ((row_index * block_size) as f32 * scale_y) as i32;
-                            let cell_width = (block_size as f32 * scale_x) as i32;
-                            let cell_height = (block_size as f32 * scale_y) as i32;
-                            d.draw_rectangle(x, y, cell_width, cell_height, Color::GRAY);
-                        }
+                if caught {
+                    state = GameState::GameOver;
+                    match &game_data.endless {
+                        Some(run) => new_endless_best = profile.record_endless(run.stages_survived()),
+                        None => profile.record_attempt(game_data.current_level, game_data.gasoline_collected),
                     }
-                }
-
-                for sprite in &sprite_manager.sprites {
-                    if !sprite.collected {
-                        let sx = offset_x + (sprite.x * scale_x) as i32;
-                        let sy = offset_y + (sprite.y * scale_y) as i32;
-                        d.draw_circle(sx, sy, 6.0, Color::ORANGE);
+                    if let Some(sink) = &current_sink {
+                        sink.stop();
                     }
+                    
+                    play_effect(&stream_handle, "audio/caught.mp3", settings.sfx_volume);
+                    continue;
                 }
 
-                let exit_x = offset_x + (6400.0 * scale_x) as i32;
-                let exit_y = offset_y + (150.0 * scale_y) as i32;
-                d.draw_rectangle(exit_x - 8, exit_y - 8, 16, 16, Color::BLUE);
-                d.draw_text("E", exit_x - 4, exit_y - 6, 12, Color::WHITE);
+                if escaped {
+                    if game_data.endless.is_some() || !recordable {
+                        new_personal_best = false;
+                        new_record = false;
+                        name_entry = None;
+                        leaderboard = None;
+                    } else {
+                        ghost_recording.time = game_data.game_timer;
+                        if ghost_recording.is_faster_than(ghost.as_ref()) {
+                            ghost_recording.save(&profile.name, game_data.maze_hash, game_data.difficulty);
+                        }
 
+                        new_personal_best = profile.record_completion(
+                            game_data.current_level,
+                            game_data.game_timer,
+                            game_data.gasoline_collected,
+                            game_data.difficulty,
+                        );
 
-                let tx = offset_x + (game_data.taylor_position.x * scale_x) as i32;
-                let ty = offset_y + (game_data.taylor_position.y * scale_y) as i32;
-                d.draw_circle(tx, ty, 8.0, Color::RED);
+                        let board = Leaderboard::load(
+                            game_data.maze_hash,
+                            game_data.difficulty,
+                            &game_data.get_current_level().level_name,
+                        );
+                        new_record = board.is_writable() && board.best_time().is_none_or(|best| game_data.game_timer < best);
+                        name_entry = board.qualifies(game_data.game_timer).then(|| profile.name.clone());
+                        leaderboard_rank = None;
+                        leaderboard = Some(board);
+                    }
 
-                let px = offset_x + (player.pos.x * scale_x) as i32;
-                let py = offset_y + (player.pos.y * scale_y) as i32;
-                d.draw_circle(px, py, 6.0, Color::GREEN);
+                    state = GameState::LevelComplete;
+                    
+                    if let Some(sink) = &current_sink {
+                        sink.stop();
+                    }
+                    
+                    play_effect(&stream_handle, "audio/level_complete.mp3", settings.sfx_volume);
+                    continue;
+                }
+
+                let ghost_frame = ghost.as_ref().and_then(|run| run.frame_at(game_data.game_timer));
+                let viewports = Viewport::split(local_players + local_taylor as usize, window_width, window_height);
+                let (render_width, render_height) = framebuffer.full_size();
+                let render_viewports = Viewport::split(viewports.len(), render_width, render_height);
 
-                d.draw_rectangle_lines_ex(Rectangle::new(offset_x as f32, offset_y as f32, minimap_size as f32, minimap_size as f32), 3.0, Color::WHITE);
+                for (player_index, viewport) in render_viewports.iter().enumerate() {
+                    framebuffer.set_viewport(viewport.x as u32, viewport.y as u32, viewport.width as u32, viewport.height as u32);
+                    if player_index >= local_players {
+                        render_taylor_view(&mut framebuffer, &maze, block_size, &game_data, &players, &texture_manager, &mut sprite_manager, &taylor_sprite);
+                        continue;
+                    }
+                    sprite_manager.set_remote_players(&other_player_positions(&players, player_index));
+                    render_world_with_textures_sprites_and_taylor(
+                        &mut framebuffer,
+                        &maze,
+                        block_size,
+                        &players[player_index],
+                        &texture_manager,
+                        &sprite_manager,
+                        &taylor_sprite,
+                        &game_data.chaser_positions(),
+                        ghost_frame,
+                    );
+                }
+                framebuffer.reset_viewport();
 
-                d.draw_fps(20, window_height - 60);
+                let framebuffer_texture = framebuffer.get_texture(&mut window, &raylib_thread, settings.smooth_upscale);
 
-                d.draw_text("WASD para moverse", 20, window_height - 120, 24, Color::LIGHTGRAY);
+                let mut d = window.begin_drawing(&raylib_thread);
+                d.clear_background(Color::BLACK);
                 
-                if game_data.gasoline_collected >= game_data.get_current_level().required_cans {
+                if let Ok(texture) = &framebuffer_texture {
+                    framebuffer::draw_scaled(&mut d, texture, window_width, window_height);
+                }
+                
+                for (player_index, viewport) in viewports.iter().enumerate() {
+                    match &game_data.taylor_player {
+                        Some(taylor) if player_index >= local_players => {
+                            hud::draw_taylor_hud(&mut d, &game_data, taylor, &players, &maze, &sprite_manager, &settings, block_size, *viewport);
+                        }
+                        _ => hud::draw_hud(&mut d, &game_data, &players, player_index, &maze, &sprite_manager, &settings, block_size, ghost_frame, *viewport),
+                    }
+                }
+
+                for viewport in viewports.iter().skip(1) {
+                    d.draw_line_ex(
+                        Vector2::new(viewport.x as f32, 0.0),
+                        Vector2::new(viewport.x as f32, window_height as f32),
+                        4.0,
+                        Color::BLACK,
+                    );
+                }
+
+                if let Some(host) = &net_host {
                     d.draw_text(
-                        "¡Presiona E cerca de la salida para escapar!",
-                        window_width / 2 - 300, 240,
-                        28, Color::GREEN,
+                        &format!(
+                            "LAN {}: {}  {}: {}",
+                            settings.language.pick("puerto", "port"),
+                            net_port,
+                            settings.language.pick("Jugadores", "Players"),
+                            1 + host.connected_count(),
+                        ),
+                        20,
+                        window_height - 30,
+                        18,
+                        Color::SKYBLUE,
                     );
                 }
             }
@@ -586,8 +1366,10 @@ fn main() {
                 let mut d = window.begin_drawing(&raylib_thread);
                 d.clear_background(Color::BLACK);
                 
+                let lang = settings.language;
+
                 d.draw_text(
-                    "¡NIVEL COMPLETO!",
+                    lang.pick("¡NIVEL COMPLETO!", "LEVEL COMPLETE!"),
                     window_width / 2 - 200,
                     window_height / 2 - 140,
                     48,
@@ -595,43 +1377,131 @@ fn main() {
                 );
                 
                 d.draw_text(
-                    &format!("Tiempo: {:.1} segundos", game_data.game_timer),
+                    &format!("{}: {:.1} {}", lang.pick("Tiempo", "Time"), game_data.game_timer, lang.pick("segundos", "seconds")),
                     window_width / 2 - 160,
                     window_height / 2 - 70,
                     28,
                     Color::WHITE,
                 );
-                
-                d.draw_text(
-                    "Presiona ESPACIO para continuar",
-                    window_width / 2 - 200,
-                    window_height / 2,
-                    28,
-                    Color::YELLOW,
-                );
 
-                if d.is_key_pressed(KeyboardKey::KEY_SPACE) {
-                    game_data.next_level();
-                    
-                    maze = load_maze(&game_data.get_current_level().maze_file);
-                    let sprite_positions = extract_sprite_positions(&maze, block_size);
-                    clean_maze(&mut maze);
-                    sprite_manager.initialize_gasoline_cans(&sprite_positions);
+                if let Some(run) = &game_data.endless {
+                    d.draw_text(
+                        &format!(
+                            "{} {} - {}: +{:.0}s",
+                            lang.pick("Etapa", "Stage"),
+                            run.stage,
+                            lang.pick("Bonificación de tiempo", "Time bonus"),
+                            endless::time_bonus(run.stage),
+                        ),
+                        window_width / 2 - 160,
+                        window_height / 2 - 35,
+                        24,
+                        Color::ORANGE,
+                    );
+                } else if new_record {
+                    d.draw_text(
+                        lang.pick("¡NUEVO RÉCORD!", "NEW RECORD!"),
+                        window_width / 2 - 160,
+                        window_height / 2 - 35,
+                        24,
+                        Color::GOLD,
+                    );
+                } else if new_personal_best {
+                    d.draw_text(
+                        lang.pick("¡Nuevo récord personal!", "New personal best!"),
+                        window_width / 2 - 160,
+                        window_height / 2 - 35,
+                        24,
+                        Color::GOLD,
+                    );
+                }
+
+                if let Some(name) = &mut name_entry {
+                    while let Some(typed) = d.get_char_pressed() {
+                        if name.chars().count() < MAX_NAME_LENGTH && (typed.is_alphanumeric() || " -_".contains(typed)) {
+                            name.push(typed);
+                        }
+                    }
+                    // El nombre se escribe con el teclado, así que aquí las teclas son fijas.
+                    if d.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                        name.pop();
+                    }
+
+                    d.draw_text(
+                        &format!("{}: {}_", lang.pick("Tu nombre", "Your name"), name),
+                        window_width / 2 - 200,
+                        window_height / 2,
+                        28,
+                        Color::YELLOW,
+                    );
+                    d.draw_text(
+                        lang.pick("Enter - Guardar récord", "Enter - Save record"),
+                        window_width / 2 - 200,
+                        window_height / 2 + 35,
+                        20,
+                        Color::LIGHTGRAY,
+                    );
+
+                    if d.is_key_pressed(KeyboardKey::KEY_ENTER) && !name.trim().is_empty() {
+                        if let Some(board) = &mut leaderboard {
+                            leaderboard_rank = board.insert(name, game_data.game_timer);
+                            board.save();
+                        }
+                        name_entry = None;
+                    }
+                } else {
+                    d.draw_text(
+                        &format!(
+                            "{} {} {}",
+                            lang.pick("Presiona", "Press"),
+                            settings.controls.label(Action::Continue),
+                            lang.pick("para continuar", "to continue"),
+                        ),
+                        window_width / 2 - 200,
+                        window_height / 2,
+                        28,
+                        Color::YELLOW,
+                    );
+                }
+
+                if let Some(board) = &leaderboard {
+                    d.draw_text(
+                        &format!("{} ({})", lang.pick("MEJORES TIEMPOS", "BEST TIMES"), board.difficulty.label(lang)),
+                        window_width / 2 - 200,
+                        window_height / 2 + 70,
+                        22,
+                        Color::SKYBLUE,
+                    );
+
+                    for (rank, entry) in board.entries.iter().enumerate() {
+                        let color = if leaderboard_rank == Some(rank) { Color::GOLD } else { Color::LIGHTGRAY };
+                        d.draw_text(
+                            &format!("{:2}. {:7.2}s  {:<12}  {}", rank + 1, entry.time, entry.name, entry.date),
+                            window_width / 2 - 200,
+                            window_height / 2 + 100 + rank as i32 * 22,
+                            18,
+                            color,
+                        );
+                    }
+                }
+
+                if name_entry.is_none() && settings.controls.is_pressed(&d, Action::Continue) {
+                    if game_data.endless.is_none() && game_data.current_level >= game_data.levels.len() - 1 {
+                        state = GameState::Victory;
+                        continue;
+                    }
+
+                    match &mut game_data.endless {
+                        Some(run) => run.advance(block_size),
+                        None => game_data.next_level(),
+                    }
                     
-                    player.pos = Vector2::new(150.0, 150.0);
-                    game_data.game_timer = 0.0;
+                    start_level(&mut maze, &mut sprite_manager, &mut game_data, &mut players, block_size, &stream_handle, &mut current_sink, &settings);
+                    recording = Replay::for_run(&game_data);
+                    ghost = load_ghost(&game_data, &profile);
+                    ghost_recording = GhostRun::new();
                     
                     state = GameState::Playing;
-                    
-                    if let Ok(game_file) = File::open("audio/getaway_car.mp3") {
-                        if let Ok(game_source) = Decoder::new(BufReader::new(game_file)) {
-                            let sink = Sink::try_new(&stream_handle).unwrap();
-                            sink.append(game_source.repeat_infinite());
-                            sink.set_volume(0.4);
-                            sink.play();
-                            current_sink = Some(sink);
-                        }
-                    }
                 }
             }
 
@@ -639,6 +1509,8 @@ fn main() {
                 let mut d = window.begin_drawing(&raylib_thread);
                 d.clear_background(Color::BLACK);
                 
+                let lang = settings.language;
+
                 d.draw_text(
                     "¡GAME OVER!",
                     window_width / 2 - 160,
@@ -647,43 +1519,96 @@ fn main() {
                     Color::RED,
                 );
                 
+                let timed_out = game_data.endless.as_ref().is_some_and(|run| run.time_remaining <= 0.0);
                 d.draw_text(
-                    "¡Taylor Swift te atrapó!",
+                    if timed_out {
+                        lang.pick("¡Se acabó el tiempo!", "Time's up!")
+                    } else {
+                        lang.pick("¡Taylor Swift te atrapó!", "Taylor Swift caught you!")
+                    },
                     window_width / 2 - 180,
                     window_height / 2 - 70,
                     28,
                     Color::WHITE,
                 );
+
+                if let Some(run) = &game_data.endless {
+                    d.draw_text(
+                        &format!(
+                            "{}: {}   {}: {}{}",
+                            lang.pick("Etapas superadas", "Stages survived"),
+                            run.stages_survived(),
+                            lang.pick("Semilla", "Seed"),
+                            run.seed,
+                            if new_endless_best { lang.pick("   ¡Nuevo récord!", "   New best!") } else { "" },
+                        ),
+                        window_width / 2 - 300,
+                        window_height / 2 - 30,
+                        24,
+                        Color::ORANGE,
+                    );
+
+                    d.draw_text(
+                        &settings.controls.hint(&[(Action::BackToMenu, lang.pick("Volver al menú", "Back to menu"))]),
+                        window_width / 2 - 160,
+                        window_height / 2 + 40,
+                        24,
+                        Color::LIGHTGRAY,
+                    );
+                }
                 
+                let has_checkpoint = game_data.checkpoint.is_some();
                 d.draw_text(
-                    "Presiona R para reintentar",
-                    window_width / 2 - 160,
+                    &format!(
+                        "{} {} {}",
+                        lang.pick("Presiona", "Press"),
+                        settings.controls.label(Action::Retry),
+                        if has_checkpoint {
+                            lang.pick("para volver al punto de control", "to resume from the checkpoint")
+                        } else {
+                            lang.pick("para reintentar", "to retry")
+                        },
+                    ),
+                    window_width / 2 - if has_checkpoint { 280 } else { 160 },
                     window_height / 2,
                     28,
                     Color::YELLOW,
                 );
 
-                if d.is_key_pressed(KeyboardKey::KEY_R) {
+                if has_checkpoint {
+                    d.draw_text(
+                        &settings.controls.hint(&[(Action::RestartLevel, lang.pick("Reiniciar el nivel completo", "Restart the whole level"))]),
+                        window_width / 2 - 180,
+                        window_height / 2 + 40,
+                        24,
+                        Color::LIGHTGRAY,
+                    );
+                }
+
+                if game_data.endless.is_some() && settings.controls.is_pressed(&d, Action::BackToMenu) {
+                    game_data.endless = None;
+                    state = GameState::Menu;
+                    continue;
+                }
+
+                let retry = settings.controls.is_pressed(&d, Action::Retry);
+                if let Some(checkpoint) = game_data.checkpoint.clone().filter(|_| retry) {
+                    checkpoint.restore(&mut game_data, &mut players, &mut maze, &mut sprite_manager);
+                    current_sink = start_music(&stream_handle, MUSIC_FILE, settings.music_volume);
+                    println!("Reanudando desde el punto de control ({:.1}s)", game_data.game_timer);
+                    state = GameState::Playing;
+                } else if retry || (has_checkpoint && settings.controls.is_pressed(&d, Action::RestartLevel)) {
+                    if let Some(run) = &game_data.endless {
+                        game_data.endless = Some(EndlessRun::new(run.seed, block_size));
+                    }
                     game_data.reset_level();
-                    player.pos = Vector2::new(150.0, 150.0);
-                    game_data.game_timer = 0.0;
                     
-                    maze = load_maze(&game_data.get_current_level().maze_file);
-                    let sprite_positions = extract_sprite_positions(&maze, block_size);
-                    clean_maze(&mut maze);
-                    sprite_manager.initialize_gasoline_cans(&sprite_positions);
+                    start_level(&mut maze, &mut sprite_manager, &mut game_data, &mut players, block_size, &stream_handle, &mut current_sink, &settings);
+                    recording = Replay::for_run(&game_data);
+                    ghost = load_ghost(&game_data, &profile);
+                    ghost_recording = GhostRun::new();
                     
                     state = GameState::Playing;
-                    
-                    if let Ok(game_file) = File::open("audio/getaway_car.mp3") {
-                        if let Ok(game_source) = Decoder::new(BufReader::new(game_file)) {
-                            let sink = Sink::try_new(&stream_handle).unwrap();
-                            sink.append(game_source.repeat_infinite());
-                            sink.set_volume(0.4);
-                            sink.play();
-                            current_sink = Some(sink);
-                        }
-                    }
                 }
             }
 
@@ -691,8 +1616,10 @@ fn main() {
                 let mut d = window.begin_drawing(&raylib_thread);
                 d.clear_background(Color::BLACK);
                 
+                let lang = settings.language;
+
                 d.draw_text(
-                    "¡FELICIDADES!",
+                    lang.pick("¡FELICIDADES!", "CONGRATULATIONS!"),
                     window_width / 2 - 200,
                     window_height / 2 - 140,
                     48,
@@ -700,7 +1627,7 @@ fn main() {
                 );
                 
                 d.draw_text(
-                    "¡Tom Hiddleston escapó exitosamente!",
+                    lang.pick("¡Tom Hiddleston escapó exitosamente!", "Tom Hiddleston escaped successfully!"),
                     window_width / 2 - 280,
                     window_height / 2 - 70,
                     28,
@@ -708,17 +1635,439 @@ fn main() {
                 );
                 
                 d.draw_text(
-                    "Presiona ESC para salir",
+                    lang.pick("Presiona ESC para salir", "Press ESC to quit"),
                     window_width / 2 - 140,
                     window_height / 2,
                     28,
                     Color::YELLOW,
                 );
 
+                // ESC es también la tecla de salida de raylib, así que no se puede reasignar.
                 if d.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
                     break;
                 }
             }
+
+            GameState::Settings => {
+                let event = settings_menu.update(&window, &mut settings);
+
+                match event {
+                    Some(SettingsEvent::Changed(SettingsItem::Resolution)) |
+                    Some(SettingsEvent::Changed(SettingsItem::Fullscreen)) => {
+                        apply_display_settings(&mut window, &mut framebuffer, &settings);
+                        window_width = settings.window_width;
+                        window_height = settings.window_height;
+                    }
+                    Some(SettingsEvent::Changed(SettingsItem::Fov)) => {
+                        for player in &mut players {
+                            player.fov = settings.fov_radians();
+                        }
+                    }
+                    Some(SettingsEvent::Changed(SettingsItem::RenderScale)) => {
+                        framebuffer = create_framebuffer(&settings);
+                    }
+                    Some(SettingsEvent::Changed(SettingsItem::RenderThreads)) => {
+                        framebuffer.set_render_threads(settings.render_threads);
+                    }
+                    Some(SettingsEvent::Changed(SettingsItem::TextureFilter)) => {
+                        texture_manager.filter = settings.texture_filter;
+                    }
+                    Some(SettingsEvent::Changed(SettingsItem::MusicVolume)) => {
+                        if let Some(sink) = &current_sink {
+                            sink.set_volume(settings.music_volume);
+                        }
+                    }
+                    Some(SettingsEvent::Changed(_)) => {}
+                    Some(SettingsEvent::OpenControls) => {
+                        controls_menu = ControlsMenu::new();
+                        state = GameState::Controls;
+                    }
+                    Some(SettingsEvent::Close) => {
+                        settings.save();
+                        state = GameState::Menu;
+                    }
+                    None => {}
+                }
+
+                let mut d = window.begin_drawing(&raylib_thread);
+                d.clear_background(Color::BLACK);
+                settings_menu.draw(&mut d, &settings);
+            }
+
+            GameState::Controls => {
+                if let Some(ControlsEvent::Close) = controls_menu.update(&mut window, &mut settings.controls) {
+                    settings.save();
+                    state = GameState::Settings;
+                }
+
+                let mut d = window.begin_drawing(&raylib_thread);
+                d.clear_background(Color::BLACK);
+                controls_menu.draw(&mut d, &settings.controls, settings.language, window_width, window_height);
+            }
+
+            GameState::Replay => {
+                let Some(playback) = &mut replay_player else {
+                    state = GameState::Menu;
+                    continue;
+                };
+
+                if settings.controls.is_pressed(&window, Action::Back) {
+                    replay_player = None;
+                    state = GameState::Menu;
+                    continue;
+                }
+
+                if settings.controls.is_pressed(&window, Action::ReplayPause) {
+                    playback.paused = !playback.paused;
+                }
+                if settings.controls.is_pressed(&window, Action::ReplayFaster) {
+                    playback.faster();
+                }
+                if settings.controls.is_pressed(&window, Action::ReplaySlower) {
+                    playback.slower();
+                }
+
+                let scrub_ticks = 5 * TICK_RATE as usize;
+                let seek_target = if settings.controls.is_pressed(&window, Action::ReplaySeekForward) {
+                    Some(playback.tick + scrub_ticks)
+                } else if settings.controls.is_pressed(&window, Action::ReplaySeekBack) {
+                    Some(playback.tick.saturating_sub(scrub_ticks))
+                } else if settings.controls.is_pressed(&window, Action::ReplayRestart) {
+                    Some(0)
+                } else {
+                    None
+                };
+
+                // La simulación no se puede deshacer: retroceder vuelve a simular desde el inicio.
+                if let Some(target) = seek_target {
+                    if target < playback.tick {
+                        reset_replay_run(&playback.replay, &mut maze, &mut sprite_manager, &mut game_data, &mut players, block_size);
+                        playback.rewind();
+                    }
+                    while playback.tick < target
+                        && step_replay(playback, &mut game_data, &mut players, &mut maze, &mut sprite_manager, block_size) {}
+                }
+
+                for _ in 0..playback.ticks_due(delta_time) {
+                    if !step_replay(playback, &mut game_data, &mut players, &mut maze, &mut sprite_manager, block_size) {
+                        break;
+                    }
+                }
+
+                taylor_sprite.update(delta_time, game_data.nearest_chaser_distance(players[0].pos));
+
+                render_world_with_textures_sprites_and_taylor(
+                    &mut framebuffer,
+                    &maze,
+                    block_size,
+                    &players[0],
+                    &texture_manager,
+                    &sprite_manager,
+                    &taylor_sprite,
+                    &game_data.chaser_positions(),
+                    None,
+                );
+
+                let framebuffer_texture = framebuffer.get_texture(&mut window, &raylib_thread, settings.smooth_upscale);
+
+                let mut d = window.begin_drawing(&raylib_thread);
+                d.clear_background(Color::BLACK);
+
+                if let Ok(texture) = &framebuffer_texture {
+                    framebuffer::draw_scaled(&mut d, texture, window_width, window_height);
+                }
+
+                hud::draw_hud(&mut d, &game_data, &players, 0, &maze, &sprite_manager, &settings, block_size, None, Viewport::split(1, window_width, window_height)[0]);
+
+                let lang = settings.language;
+
+                d.draw_text(
+                    &format!(
+                        "{}  {:.1}s / {:.1}s  x{}{}",
+                        lang.pick("REPETICIÓN", "REPLAY"),
+                        playback.seconds(),
+                        playback.replay.duration(),
+                        playback.speed,
+                        if playback.paused { lang.pick("  (PAUSA)", "  (PAUSED)") } else { "" },
+                    ),
+                    20,
+                    165,
+                    24,
+                    Color::SKYBLUE,
+                );
+
+                if playback.maze_mismatch {
+                    d.draw_text(
+                        lang.pick("Aviso: el laberinto cambió desde la grabación", "Warning: the maze changed since recording"),
+                        20,
+                        195,
+                        20,
+                        Color::ORANGE,
+                    );
+                }
+
+                if playback.is_over() {
+                    d.draw_text(
+                        lang.pick("FIN DE LA REPETICIÓN", "END OF REPLAY"),
+                        window_width / 2 - 180,
+                        window_height / 2,
+                        36,
+                        Color::YELLOW,
+                    );
+                }
+
+                d.draw_text(
+                    &settings.controls.hint(&[
+                        (Action::ReplayPause, lang.pick("Pausa", "Pause")),
+                        (Action::ReplayFaster, lang.pick("Más rápido", "Faster")),
+                        (Action::ReplaySlower, lang.pick("Más lento", "Slower")),
+                        (Action::ReplaySeekBack, lang.pick("-5s", "-5s")),
+                        (Action::ReplaySeekForward, lang.pick("+5s", "+5s")),
+                        (Action::ReplayRestart, lang.pick("Reiniciar", "Restart")),
+                        (Action::Back, lang.pick("Salir", "Exit")),
+                    ]),
+                    20,
+                    window_height - 30,
+                    18,
+                    Color::GRAY,
+                );
+            }
+
+            GameState::NetClient => {
+                let Some(client) = &mut net_client else {
+                    state = GameState::Menu;
+                    continue;
+                };
+
+                if settings.controls.is_pressed(&window, Action::Back) {
+                    client.leave();
+                    net_client = None;
+                    if let Some(sink) = &current_sink {
+                        sink.stop();
+                    }
+                    state = GameState::Menu;
+                    continue;
+                }
+
+                let own_runner = match client.role {
+                    Some(NetRole::Runner(index)) => Some(index),
+                    _ => None,
+                };
+                let was_active = own_runner.and_then(|index| players.get(index)).is_some_and(|player| player.is_active());
+                let cans_before = game_data.gasoline_collected;
+                let mut rejected = false;
+
+                for event in client.poll() {
+                    match event {
+                        ClientEvent::Welcome { role, level } => {
+                            match role {
+                                NetRole::Runner(index) => {
+                                    println!("Unido como jugador {} al nivel {}", index + 1, level.level_index + 1);
+                                    players.resize_with(players.len().max(index + 1), || Player::new(settings.fov_radians()));
+                                }
+                                NetRole::Taylor => println!("Unido como Taylor al nivel {}", level.level_index + 1),
+                            }
+                            game_data.current_level = level.level_index.min(game_data.levels.len() - 1);
+                            game_data.difficulty = level.difficulty;
+                            game_data.endless = None;
+                            start_level(&mut maze, &mut sprite_manager, &mut game_data, &mut players, block_size, &stream_handle, &mut current_sink, &settings);
+                            if game_data.maze_hash != level.maze_hash {
+                                println!("Aviso: el laberinto local no coincide con el del anfitrión");
+                            }
+                        }
+                        ClientEvent::Ended { escaped } => {
+                            if let Some(sink) = &current_sink {
+                                sink.stop();
+                            }
+                            let effect = if escaped { "audio/level_complete.mp3" } else { "audio/caught.mp3" };
+                            play_effect(&stream_handle, effect, settings.sfx_volume);
+                        }
+                        ClientEvent::Rejected => {
+                            println!("El anfitrión usa otra versión del protocolo");
+                            rejected = true;
+                        }
+                    }
+                }
+
+                if rejected {
+                    net_client = None;
+                    state = GameState::Menu;
+                    continue;
+                }
+
+                let mut input = settings.controls.sample(&window, settings.gamepad_sensitivity);
+                input.look = window.get_mouse_delta().x * settings.mouse_sensitivity * settings.look_sign();
+                client.send_input(&input);
+
+                // Los demás jugadores y los perseguidores se dibujan interpolados; el propio
+                // jugador usa el último snapshot para que la respuesta al control sea inmediata.
+                if let Some(snapshot) = client.interpolated() {
+                    snapshot.apply(&mut game_data, &mut players, &mut maze, &mut sprite_manager, settings.fov_radians());
+                }
+                let role = client.role;
+                if let Some(latest) = client.latest() {
+                    match role {
+                        Some(NetRole::Runner(index)) => {
+                            players.resize_with(players.len().max(index + 1), || Player::new(settings.fov_radians()));
+                            latest.apply_player(index, &mut players[index]);
+                        }
+                        Some(NetRole::Taylor) => latest.apply_taylor(&mut game_data, settings.fov_radians()),
+                        None => {}
+                    }
+                }
+
+                if let Some(index) = own_runner {
+                    if was_active && players[index].status == PlayerStatus::Caught {
+                        play_effect(&stream_handle, "audio/caught.mp3", settings.sfx_volume);
+                    }
+                }
+                if role.is_some() && game_data.gasoline_collected > cans_before {
+                    play_effect(&stream_handle, "audio/gasoline_pickup.mp3", settings.sfx_volume);
+                }
+
+                let view_position = own_runner.map_or(game_data.taylor_position, |index| players[index].pos);
+                taylor_sprite.update(delta_time, game_data.nearest_chaser_distance(view_position));
+
+                match role {
+                    Some(NetRole::Runner(index)) => {
+                        sprite_manager.set_remote_players(&other_player_positions(&players, index));
+                        render_world_with_textures_sprites_and_taylor(
+                            &mut framebuffer,
+                            &maze,
+                            block_size,
+                            &players[index],
+                            &texture_manager,
+                            &sprite_manager,
+                            &taylor_sprite,
+                            &game_data.chaser_positions(),
+                            None,
+                        );
+                    }
+                    Some(NetRole::Taylor) => {
+                        render_taylor_view(&mut framebuffer, &maze, block_size, &game_data, &players, &texture_manager, &mut sprite_manager, &taylor_sprite);
+                    }
+                    None => {}
+                }
+
+                let framebuffer_texture = framebuffer.get_texture(&mut window, &raylib_thread, settings.smooth_upscale);
+
+                let mut d = window.begin_drawing(&raylib_thread);
+                d.clear_background(Color::BLACK);
+
+                let lang = settings.language;
+
+                if role.is_none() {
+                    d.draw_text(
+                        &format!("{} {}...", lang.pick("Conectando a", "Connecting to"), client.server()),
+                        window_width / 2 - 250,
+                        window_height / 2,
+                        32,
+                        Color::WHITE,
+                    );
+                } else {
+                    if let Ok(texture) = &framebuffer_texture {
+                        framebuffer::draw_scaled(&mut d, texture, window_width, window_height);
+                    }
+
+                    let viewport = Viewport::split(1, window_width, window_height)[0];
+                    match (role, &game_data.taylor_player) {
+                        (Some(NetRole::Taylor), Some(taylor)) => {
+                            hud::draw_taylor_hud(&mut d, &game_data, taylor, &players, &maze, &sprite_manager, &settings, block_size, viewport);
+                        }
+                        (Some(NetRole::Runner(index)), _) => {
+                            hud::draw_hud(&mut d, &game_data, &players, index, &maze, &sprite_manager, &settings, block_size, None, viewport);
+                        }
+                        _ => {}
+                    }
+
+                    if let Some(escaped) = client.ended {
+                        d.draw_text(
+                            match (escaped, role) {
+                                (true, _) => lang.pick("¡ESCAPARON!", "THEY ESCAPED!"),
+                                (false, Some(NetRole::Taylor)) => lang.pick("¡LOS ATRAPASTE!", "YOU CAUGHT THEM!"),
+                                (false, _) => lang.pick("¡OS ATRAPARON!", "YOU WERE CAUGHT!"),
+                            },
+                            window_width / 2 - 180,
+                            window_height / 2 - 80,
+                            44,
+                            if escaped { Color::GREEN } else { Color::RED },
+                        );
+                        d.draw_text(
+                            lang.pick("Esperando al anfitrión...", "Waiting for the host..."),
+                            window_width / 2 - 160,
+                            window_height / 2 - 20,
+                            24,
+                            Color::LIGHTGRAY,
+                        );
+                    }
+                }
+
+                if client.connection_lost() {
+                    d.draw_text(
+                        lang.pick("Sin respuesta del anfitrión", "No response from the host"),
+                        20,
+                        165,
+                        24,
+                        Color::ORANGE,
+                    );
+                }
+
+                d.draw_text(
+                    &format!("LAN {}  -  {}", client.server(), settings.controls.hint(&[(Action::Back, lang.pick("Salir", "Leave"))])),
+                    20,
+                    window_height - 30,
+                    18,
+                    Color::GRAY,
+                );
+            }
+
+            GameState::Paused => {
+                let mut d = window.begin_drawing(&raylib_thread);
+                d.clear_background(Color::BLACK);
+
+                let lang = settings.language;
+
+                d.draw_text(
+                    lang.pick("PAUSA", "PAUSED"),
+                    window_width / 2 - 90,
+                    window_height / 2 - 140,
+                    48,
+                    Color::WHITE,
+                );
+
+                d.draw_text(
+                    &format!(
+                        "{} {} {}",
+                        lang.pick("Presiona", "Press"),
+                        settings.controls.label(Action::Pause),
+                        lang.pick("para continuar", "to resume"),
+                    ),
+                    window_width / 2 - 180,
+                    window_height / 2 - 40,
+                    28,
+                    Color::YELLOW,
+                );
+
+                d.draw_text(
+                    &settings.controls.hint(&[(Action::BackToMenu, lang.pick("Volver al menú", "Back to menu"))]),
+                    window_width / 2 - 150,
+                    window_height / 2 + 10,
+                    28,
+                    Color::LIGHTGRAY,
+                );
+
+                if settings.controls.is_pressed(&d, Action::Pause) {
+                    state = GameState::Playing;
+                    if let Some(sink) = &current_sink {
+                        sink.play();
+                    }
+                } else if settings.controls.is_pressed(&d, Action::BackToMenu) {
+                    state = GameState::Menu;
+                    if let Some(sink) = &current_sink {
+                        sink.stop();
+                    }
+                }
+            }
         }
 
         thread::sleep(Duration::from_millis(16));
@@ -727,4 +2076,51 @@ fn main() {
     if let Some(sink) = current_sink {
         sink.stop();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK: usize = 100;
+
+    fn endless_replay() -> Replay {
+        let seed = 99;
+        let mut replay = Replay::new(0, Difficulty::Normal, maze_hash(&EndlessRun::at_stage(seed, 2, 40.0, BLOCK).maze), seed);
+        replay.endless = Some((2, 40.0));
+        replay.lives = 3;
+        let forward = TickInput { forward: 1.0, turn: 0.3, ..TickInput::default() };
+        replay.inputs = vec![forward; 3 * TICK_RATE as usize];
+        replay
+    }
+
+    #[test]
+    fn rewinding_endless_replay_restores_recorded_stage() {
+        let mut assets = AssetManager::new();
+        let mut sprite_manager = SpriteManager::new(&mut assets);
+        let mut game_data = GameData::new();
+        let mut players = vec![Player::new(PI / 3.0)];
+        let mut maze = Maze::new();
+
+        let mut playback = start_replay(endless_replay(), &mut maze, &mut sprite_manager, &mut game_data, &mut players, BLOCK);
+        assert!(!playback.maze_mismatch);
+        while step_replay(&mut playback, &mut game_data, &mut players, &mut maze, &mut sprite_manager, BLOCK) {}
+        let first_pass = (players[0].pos, game_data.game_timer);
+
+        // La partida en curso cruza a la etapa siguiente antes de rebobinar.
+        if let Some(run) = &mut game_data.endless {
+            run.advance(BLOCK);
+            run.time_remaining = 0.5;
+        }
+        load_level_maze(&mut maze, &mut sprite_manager, &mut game_data, BLOCK);
+
+        reset_replay_run(&playback.replay, &mut maze, &mut sprite_manager, &mut game_data, &mut players, BLOCK);
+        playback.rewind();
+        let run = game_data.endless.as_ref().unwrap();
+        assert_eq!((run.stage, run.time_remaining), (2, 40.0));
+        assert_eq!(game_data.maze_hash, playback.replay.maze_hash);
+
+        while step_replay(&mut playback, &mut game_data, &mut players, &mut maze, &mut sprite_manager, BLOCK) {}
+        assert_eq!((players[0].pos, game_data.game_timer), first_pass);
+    }
+}
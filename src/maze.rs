@@ -9,55 +9,85 @@ pub struct SpritePosition {
     pub y: f32,
 }
 
-pub fn load_maze(filename: &str) -> Maze {
-    match File::open(filename) {
-        Ok(file) => {
-            let reader = BufReader::new(file);
-            reader
-                .lines()
-                .map(|line| line.unwrap().chars().collect())
-                .collect()
-        }
-        Err(e) => {
-            println!("Error cargando {}: {}", filename, e);
-            println!("Usando maze de fallback...");
-            vec![
-                "+++++++++++++++++".chars().collect(),
-                "+  .    +     . +".chars().collect(),
-                "+   +   +   +   +".chars().collect(),
-                "+       +       +".chars().collect(),
-                "+   .   +   .   +".chars().collect(),
-                "+++++++++++++++++".chars().collect(),
-            ]
-        }
+// None si el archivo no se puede abrir o leer, o si no trae ninguna fila.
+pub fn load_maze(filename: &str) -> Option<Maze> {
+    let file = File::open(filename)
+        .map_err(|e| println!("Error cargando {}: {}", filename, e))
+        .ok()?;
+    let maze: Maze = BufReader::new(file)
+        .lines()
+        .map(|line| line.map(|line| line.chars().collect()))
+        .collect::<Result<_, _>>()
+        .map_err(|e| println!("Error leyendo {}: {}", filename, e))
+        .ok()?;
+
+    if maze.iter().all(|row| row.is_empty()) {
+        println!("El laberinto {} está vacío", filename);
+        return None;
     }
+    Some(maze)
 }
 
-pub fn extract_sprite_positions(maze: &Maze, block_size: usize) -> Vec<SpritePosition> {
-    let mut sprite_positions = Vec::new();
+pub fn fallback_maze() -> Maze {
+    vec![
+        "+++++++++++++++++".chars().collect(),
+        "+  .    +     . +".chars().collect(),
+        "+   +   +   +   +".chars().collect(),
+        "+       +       +".chars().collect(),
+        "+   .   +   .   +".chars().collect(),
+        "+++++++++++++++++".chars().collect(),
+    ]
+}
+
+// Posiciones de las entidades marcadas en el laberinto, junto con el índice de su definición.
+pub fn extract_entity_positions(
+    maze: &Maze,
+    block_size: usize,
+    entity_for_glyph: impl Fn(char) -> Option<usize>,
+) -> Vec<(usize, SpritePosition)> {
+    let mut entity_positions = Vec::new();
     
     for (row_index, row) in maze.iter().enumerate() {
         for (col_index, &cell) in row.iter().enumerate() {
-            if cell == '.' {
+            if let Some(definition) = entity_for_glyph(cell) {
                 let x = (col_index * block_size) as f32 + (block_size as f32 / 2.0);
                 let y = (row_index * block_size) as f32 + (block_size as f32 / 2.0);
-                
-                sprite_positions.push(SpritePosition { x, y });
-                println!("Sprite encontrado en posición: ({}, {}) - grid({}, {})", x, y, col_index, row_index);
+                entity_positions.push((definition, SpritePosition { x, y }));
             }
         }
     }
     
-    println!("Total de sprites encontrados: {}", sprite_positions.len());
-    sprite_positions
+    entity_positions
 }
 
-pub fn clean_maze(maze: &mut Maze) {
+pub fn clean_maze(maze: &mut Maze, entity_glyphs: &[char]) {
     for row in maze.iter_mut() {
         for cell in row.iter_mut() {
-            if *cell == '.' {
+            if entity_glyphs.contains(cell) {
                 *cell = ' '; 
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn load_contents(name: &str, contents: &str) -> Option<Maze> {
+        let path = std::env::temp_dir().join(format!("{}_{}.txt", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        let maze = load_maze(path.to_str().unwrap());
+        let _ = fs::remove_file(&path);
+        maze
+    }
+
+    #[test]
+    fn loads_rows_and_rejects_empty_or_missing_files() {
+        assert_eq!(load_contents("maze_rows", "+++\n+ +\n"), Some(vec![vec!['+'; 3], vec!['+', ' ', '+']]));
+        assert_eq!(load_contents("maze_empty", ""), None);
+        assert_eq!(load_contents("maze_blank", "\n\n"), None);
+        assert_eq!(load_maze("levels/no_existe.txt"), None);
+    }
+}
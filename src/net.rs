@@ -0,0 +1,925 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+use raylib::prelude::Vector2;
+use crate::difficulty::Difficulty;
+use crate::input::TickInput;
+use crate::inventory::{WorldEffect, ALL_ITEMS};
+use crate::maze::Maze;
+use crate::player::{Player, PlayerStatus};
+use crate::replay::{decode_input, encode_input};
+use crate::sprites::SpriteManager;
+use crate::taylor_ai::Chaser;
+use crate::taylor_player::TaylorPlayer;
+use crate::GameData;
+
+pub const DEFAULT_PORT: u16 = 7777;
+pub const PROTOCOL_VERSION: u32 = 7;
+const MAX_PACKET: usize = 4096;
+const INTERPOLATION_DELAY: Duration = Duration::from_millis(100);
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+const JOIN_RETRY: Duration = Duration::from_secs(1);
+const MAX_SNAPSHOTS: usize = 32;
+const CELL_HISTORY: usize = 128;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LevelInfo {
+    pub level_index: usize,
+    pub difficulty: Difficulty,
+    pub maze_hash: u64,
+    pub seed: u64,
+    pub versus: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NetRole {
+    Runner(usize),
+    Taylor,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NetTaylor {
+    pub a: f32,
+    pub sense_remaining: f32,
+    pub sense_cooldown: f32,
+    pub stamina: f32,
+    pub exhausted: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NetPlayer {
+    pub x: f32,
+    pub y: f32,
+    pub a: f32,
+    pub status: PlayerStatus,
+    pub lives: u32,
+    pub invulnerable: f32,
+    pub stamina: f32,
+    pub exhausted: f32,
+    pub items: [u32; ALL_ITEMS.len()],
+    pub selected_item: usize,
+    pub speed_boost: f32,
+    pub map_reveal: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub tick: u64,
+    pub game_timer: f32,
+    pub gasoline_collected: usize,
+    pub max_lives: u32,
+    pub pickups_collected: Vec<bool>,
+    pub players: Vec<NetPlayer>,
+    pub chasers: Vec<Vector2>,
+    pub changed_cells: Vec<(usize, usize, char)>,
+    pub world_effects: Vec<WorldEffect>,
+    pub taylor: Option<NetTaylor>,
+}
+
+fn status_code(status: PlayerStatus) -> u8 {
+    match status {
+        PlayerStatus::Active => 0,
+        PlayerStatus::Caught => 1,
+        PlayerStatus::Escaped => 2,
+    }
+}
+
+fn status_from_code(code: u8) -> Option<PlayerStatus> {
+    match code {
+        0 => Some(PlayerStatus::Active),
+        1 => Some(PlayerStatus::Caught),
+        2 => Some(PlayerStatus::Escaped),
+        _ => None,
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn lerp_angle(a: f32, b: f32, t: f32) -> f32 {
+    let delta = (b - a + PI).rem_euclid(2.0 * PI) - PI;
+    a + delta * t
+}
+
+impl Snapshot {
+    pub fn capture(tick: u64, game_data: &GameData, players: &[Player], sprite_manager: &SpriteManager) -> Self {
+        Self {
+            tick,
+            game_timer: game_data.game_timer,
+            gasoline_collected: game_data.gasoline_collected,
+            max_lives: game_data.max_lives,
+            pickups_collected: sprite_manager
+                .sprites
+                .iter()
+                .filter(|sprite| sprite.is_pickup())
+                .map(|sprite| sprite.collected)
+                .collect(),
+            players: players
+                .iter()
+                .map(|player| NetPlayer {
+                    x: player.pos.x,
+                    y: player.pos.y,
+                    a: player.a,
+                    status: player.status,
+                    lives: player.lives,
+                    invulnerable: player.invulnerable,
+                    stamina: player.stamina,
+                    exhausted: player.exhausted,
+                    items: player.inventory.counts,
+                    selected_item: player.inventory.selected,
+                    speed_boost: player.speed_boost,
+                    map_reveal: player.map_reveal,
+                })
+                .collect(),
+            chasers: game_data.chaser_positions(),
+            changed_cells: game_data.changed_cells.clone(),
+            world_effects: game_data.world_effects.clone(),
+            taylor: game_data.taylor_player.as_ref().map(|taylor| NetTaylor {
+                a: taylor.body.a,
+                sense_remaining: taylor.sense_remaining,
+                sense_cooldown: taylor.sense_cooldown,
+                stamina: taylor.body.stamina,
+                exhausted: taylor.body.exhausted,
+            }),
+        }
+    }
+
+    // SNAPSHOT tick timer latas vidas base mascara
+    //   | x y a estado vidas invulnerable energia agotado objetos seleccionado velocidad mapa ; ...
+    //   | x y ; ... | a sentido recarga energia agotado | x y celda ; ... | objeto x y restante ; ...
+    // Las celdas cambiadas solo se envían desde `cell_base`, lo que el cliente ya confirmó.
+    fn encode(&self, cell_base: usize) -> String {
+        let mask: String = self.pickups_collected.iter().map(|&collected| if collected { '1' } else { '0' }).collect();
+        let players: Vec<String> = self
+            .players
+            .iter()
+            .map(|player| {
+                let items: Vec<String> = player.items.iter().map(u32::to_string).collect();
+                format!(
+                    "{:.2} {:.2} {:.4} {} {} {:.2} {:.3} {:.2} {} {} {:.2} {:.2}",
+                    player.x,
+                    player.y,
+                    player.a,
+                    status_code(player.status),
+                    player.lives,
+                    player.invulnerable,
+                    player.stamina,
+                    player.exhausted,
+                    items.join(","),
+                    player.selected_item,
+                    player.speed_boost,
+                    player.map_reveal,
+                )
+            })
+            .collect();
+        let chasers: Vec<String> = self.chasers.iter().map(|chaser| format!("{:.2} {:.2}", chaser.x, chaser.y)).collect();
+
+        let taylor = self
+            .taylor
+            .map(|taylor| {
+                format!(
+                    "{:.4} {:.3} {:.3} {:.3} {:.2}",
+                    taylor.a, taylor.sense_remaining, taylor.sense_cooldown, taylor.stamina, taylor.exhausted,
+                )
+            })
+            .unwrap_or_default();
+        let changed_cells: Vec<String> = self
+            .changed_cells
+            .iter()
+            .skip(cell_base)
+            .map(|&(x, y, cell)| format!("{} {} {}", x, y, cell as u32))
+            .collect();
+        let world_effects: Vec<String> = self
+            .world_effects
+            .iter()
+            .map(|effect| {
+                format!("{} {:.2} {:.2} {:.2}", effect.kind.index(), effect.position.x, effect.position.y, effect.remaining)
+            })
+            .collect();
+
+        format!(
+            "SNAPSHOT {} {:.3} {} {} {} c{}|{}|{}|{}|{}|{}",
+            self.tick,
+            self.game_timer,
+            self.gasoline_collected,
+            self.max_lives,
+            cell_base,
+            mask,
+            players.join(";"),
+            chasers.join(";"),
+            taylor,
+            changed_cells.join(";"),
+            world_effects.join(";"),
+        )
+    }
+
+    // Devuelve también la base de las celdas: `changed_cells` trae solo las que van desde ahí.
+    fn decode(body: &str) -> Option<(Self, usize)> {
+        let mut sections = body.split('|');
+        let mut header = sections.next()?.split_whitespace();
+        let tick = header.next()?.parse().ok()?;
+        let game_timer = header.next()?.parse().ok()?;
+        let gasoline_collected = header.next()?.parse().ok()?;
+        let max_lives = header.next()?.parse().ok()?;
+        let cell_base = header.next()?.parse().ok()?;
+        let pickups_collected = header.next()?.strip_prefix('c')?.chars().map(|c| c == '1').collect();
+
+        let mut players = Vec::new();
+        for entry in sections.next()?.split(';').filter(|entry| !entry.trim().is_empty()) {
+            let fields: Vec<&str> = entry.split_whitespace().collect();
+            let [x, y, a, status, lives, invulnerable, stamina, exhausted, items, selected_item, speed_boost, map_reveal] =
+                fields[..]
+            else {
+                return None;
+            };
+            let mut counts = [0; ALL_ITEMS.len()];
+            for (count, value) in counts.iter_mut().zip(items.split(',')) {
+                *count = value.parse().ok()?;
+            }
+            players.push(NetPlayer {
+                x: x.parse().ok()?,
+                y: y.parse().ok()?,
+                a: a.parse().ok()?,
+                status: status_from_code(status.parse().ok()?)?,
+                lives: lives.parse().ok()?,
+                invulnerable: invulnerable.parse().ok()?,
+                stamina: stamina.parse().ok()?,
+                exhausted: exhausted.parse().ok()?,
+                items: counts,
+                selected_item: selected_item.parse().ok()?,
+                speed_boost: speed_boost.parse().ok()?,
+                map_reveal: map_reveal.parse().ok()?,
+            });
+        }
+
+        let mut chasers = Vec::new();
+        for entry in sections.next()?.split(';').filter(|entry| !entry.trim().is_empty()) {
+            let fields: Vec<&str> = entry.split_whitespace().collect();
+            let [x, y] = fields[..] else {
+                return None;
+            };
+            chasers.push(Vector2::new(x.parse().ok()?, y.parse().ok()?));
+        }
+
+        let taylor_fields: Vec<f32> = sections.next()?.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+        let taylor = match taylor_fields[..] {
+            [a, sense_remaining, sense_cooldown, stamina, exhausted] => {
+                Some(NetTaylor { a, sense_remaining, sense_cooldown, stamina, exhausted })
+            }
+            _ => None,
+        };
+
+        let mut changed_cells = Vec::new();
+        for entry in sections.next()?.split(';').filter(|entry| !entry.trim().is_empty()) {
+            let fields: Vec<&str> = entry.split_whitespace().collect();
+            let [x, y, cell] = fields[..] else {
+                return None;
+            };
+            changed_cells.push((x.parse().ok()?, y.parse().ok()?, char::from_u32(cell.parse().ok()?)?));
+        }
+
+        let mut world_effects = Vec::new();
+        for entry in sections.next()?.split(';').filter(|entry| !entry.trim().is_empty()) {
+            let fields: Vec<&str> = entry.split_whitespace().collect();
+            let [kind, x, y, remaining] = fields[..] else {
+                return None;
+            };
+            world_effects.push(WorldEffect {
+                kind: *ALL_ITEMS.get(kind.parse::<usize>().ok()?)?,
+                position: Vector2::new(x.parse().ok()?, y.parse().ok()?),
+                remaining: remaining.parse().ok()?,
+            });
+        }
+
+        let snapshot = Self {
+            tick,
+            game_timer,
+            gasoline_collected,
+            max_lives,
+            pickups_collected,
+            players,
+            chasers,
+            changed_cells,
+            world_effects,
+            taylor,
+        };
+        Some((snapshot, cell_base))
+    }
+
+    // Solo se interpolan posiciones; estados, latas y contadores salen del snapshot más nuevo.
+    fn interpolate(&self, next: &Snapshot, t: f32) -> Snapshot {
+        let mut result = next.clone();
+        for (player, previous) in result.players.iter_mut().zip(&self.players) {
+            player.x = lerp(previous.x, player.x, t);
+            player.y = lerp(previous.y, player.y, t);
+            player.a = lerp_angle(previous.a, player.a, t);
+        }
+        for (chaser, previous) in result.chasers.iter_mut().zip(&self.chasers) {
+            chaser.x = lerp(previous.x, chaser.x, t);
+            chaser.y = lerp(previous.y, chaser.y, t);
+        }
+        if let (Some(taylor), Some(previous)) = (&mut result.taylor, &self.taylor) {
+            taylor.a = lerp_angle(previous.a, taylor.a, t);
+        }
+        result
+    }
+
+    pub fn apply(&self, game_data: &mut GameData, players: &mut Vec<Player>, maze: &mut Maze, sprite_manager: &mut SpriteManager, fov: f32) {
+        game_data.game_timer = self.game_timer;
+        game_data.gasoline_collected = self.gasoline_collected;
+        game_data.max_lives = self.max_lives;
+
+        players.resize_with(self.players.len().max(1), || Player::new(fov));
+        for index in 0..self.players.len() {
+            self.apply_player(index, &mut players[index]);
+        }
+
+        if let Some((_, extra)) = self.chasers.split_first() {
+            game_data.extra_chasers.resize_with(extra.len(), || Chaser::new(Vector2::zero()));
+            for (chaser, &position) in game_data.extra_chasers.iter_mut().zip(extra) {
+                chaser.position = position;
+            }
+        }
+        self.apply_taylor(game_data, fov);
+
+        for &(x, y, cell) in &self.changed_cells {
+            if let Some(slot) = maze.get_mut(y).and_then(|row| row.get_mut(x)) {
+                *slot = cell;
+            }
+        }
+
+        let pickups = sprite_manager.sprites.iter_mut().filter(|sprite| sprite.is_pickup());
+        for (sprite, &collected) in pickups.zip(&self.pickups_collected) {
+            sprite.collected = collected;
+        }
+        game_data.world_effects = self.world_effects.clone();
+        sprite_manager.set_world_effects(&self.world_effects);
+    }
+
+    pub fn apply_taylor(&self, game_data: &mut GameData, fov: f32) {
+        let Some(&position) = self.chasers.first() else {
+            return;
+        };
+        game_data.taylor_position = position;
+        game_data.taylor_player = self.taylor.map(|state| {
+            let mut taylor = TaylorPlayer::new(position, fov);
+            taylor.body.a = state.a;
+            taylor.sense_remaining = state.sense_remaining;
+            taylor.sense_cooldown = state.sense_cooldown;
+            taylor.body.stamina = state.stamina;
+            taylor.body.exhausted = state.exhausted;
+            taylor
+        });
+    }
+
+    pub fn apply_player(&self, index: usize, player: &mut Player) {
+        if let Some(state) = self.players.get(index) {
+            player.pos = Vector2::new(state.x, state.y);
+            player.a = state.a;
+            player.status = state.status;
+            player.lives = state.lives;
+            player.invulnerable = state.invulnerable;
+            player.stamina = state.stamina;
+            player.exhausted = state.exhausted;
+            player.inventory.counts = state.items;
+            player.inventory.selected = state.selected_item;
+            player.speed_boost = state.speed_boost;
+            player.map_reveal = state.map_reveal;
+        }
+    }
+}
+
+fn bind_socket(port: u16) -> io::Result<UdpSocket> {
+    let socket = UdpSocket::bind(("0.0.0.0", port))?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+// Lee todos los datagramas pendientes sin bloquear el bucle del juego.
+fn drain(socket: &UdpSocket) -> Vec<(String, SocketAddr)> {
+    let mut buffer = [0u8; MAX_PACKET];
+    let mut messages = Vec::new();
+    loop {
+        match socket.recv_from(&mut buffer) {
+            Ok((length, addr)) => {
+                if let Ok(text) = std::str::from_utf8(&buffer[..length]) {
+                    messages.push((text.to_string(), addr));
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+            Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+            Err(e) => {
+                println!("Error de red: {}", e);
+                break;
+            }
+        }
+    }
+    messages
+}
+
+fn send(socket: &UdpSocket, addr: SocketAddr, message: &str) {
+    if let Err(e) = socket.send_to(message.as_bytes(), addr) {
+        if e.kind() != ErrorKind::WouldBlock {
+            println!("Error enviando a {}: {}", addr, e);
+        }
+    }
+}
+
+struct RemoteClient {
+    addr: SocketAddr,
+    name: String,
+    input: TickInput,
+    pending_look: f32,
+    last_sequence: u32,
+    acked_tick: u64,
+    last_seen: Instant,
+    connected: bool,
+}
+
+// El anfitrión simula la partida completa; los clientes solo envían su entrada y dibujan
+// lo que reciben. En modo versus el primer cliente controla a Taylor y el resto corre;
+// los corredores remotos ocupan players[1..] en el orden en que se unieron.
+pub struct NetHost {
+    socket: UdpSocket,
+    clients: Vec<RemoteClient>,
+    level: Option<LevelInfo>,
+    tick: u64,
+    // Cuántas celdas cambiadas había en cada tick enviado, para saber desde dónde mandar a
+    // cada cliente según el último tick que confirmó.
+    cell_history: VecDeque<(u64, usize)>,
+    oversize_logged: bool,
+}
+
+impl NetHost {
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let socket = bind_socket(port)?;
+        println!("Partida LAN abierta en el puerto {}", port);
+        Ok(Self {
+            socket,
+            clients: Vec::new(),
+            level: None,
+            tick: 0,
+            cell_history: VecDeque::new(),
+            oversize_logged: false,
+        })
+    }
+
+    fn is_versus(&self) -> bool {
+        self.level.is_some_and(|level| level.versus)
+    }
+
+    pub fn role(&self, client_index: usize) -> NetRole {
+        match (self.is_versus(), client_index) {
+            (true, 0) => NetRole::Taylor,
+            (true, index) => NetRole::Runner(index),
+            (false, index) => NetRole::Runner(index + 1),
+        }
+    }
+
+    pub fn remote_player_count(&self) -> usize {
+        (0..self.clients.len())
+            .filter(|&index| matches!(self.role(index), NetRole::Runner(_)))
+            .count()
+    }
+
+    pub fn taylor_connected(&self) -> bool {
+        self.is_versus() && self.is_connected(0)
+    }
+
+    pub fn connected_count(&self) -> usize {
+        (0..self.clients.len()).filter(|&index| self.is_connected(index)).count()
+    }
+
+    pub fn is_connected(&self, client_index: usize) -> bool {
+        self.clients
+            .get(client_index)
+            .is_some_and(|client| client.connected && client.last_seen.elapsed() < CONNECTION_TIMEOUT)
+    }
+
+    pub fn poll(&mut self) {
+        for (message, addr) in drain(&self.socket) {
+            self.handle_message(&message, addr);
+        }
+    }
+
+    fn handle_message(&mut self, message: &str, addr: SocketAddr) {
+        let (command, body) = message.split_once(' ').unwrap_or((message, ""));
+        let client_index = self.clients.iter().position(|client| client.addr == addr);
+
+        match (command, client_index) {
+            ("JOIN", _) => {
+                let (version, name) = body.split_once(' ').unwrap_or((body, "?"));
+                if version.parse::<u32>().ok() != Some(PROTOCOL_VERSION) {
+                    println!("{} usa otra versión del protocolo ({})", addr, version);
+                    send(&self.socket, addr, &format!("REJECT {}", PROTOCOL_VERSION));
+                    return;
+                }
+
+                let index = client_index.unwrap_or_else(|| {
+                    self.clients.push(RemoteClient {
+                        addr,
+                        name: name.to_string(),
+                        input: TickInput::default(),
+                        pending_look: 0.0,
+                        last_sequence: 0,
+                        acked_tick: 0,
+                        last_seen: Instant::now(),
+                        connected: true,
+                    });
+                    println!("{} se unió desde {} como jugador {}", name, addr, self.clients.len() + 1);
+                    self.clients.len() - 1
+                });
+                let client = &mut self.clients[index];
+                client.connected = true;
+                client.last_seen = Instant::now();
+                client.last_sequence = 0;
+                client.acked_tick = 0;
+                self.send_welcome(index);
+            }
+            ("INPUT", Some(index)) => {
+                let mut fields = body.splitn(3, ' ');
+                let (Some(sequence), Some(ack), Some(input)) = (fields.next(), fields.next(), fields.next()) else {
+                    return;
+                };
+                let (Ok(sequence), Ok(ack), Some(input)) = (sequence.parse::<u32>(), ack.parse::<u64>(), decode_input(input)) else {
+                    return;
+                };
+                let tick = self.tick;
+                let client = &mut self.clients[index];
+                client.last_seen = Instant::now();
+                if ack <= tick {
+                    client.acked_tick = client.acked_tick.max(ack);
+                }
+                if sequence > client.last_sequence {
+                    client.last_sequence = sequence;
+                    client.pending_look += input.look;
+                    client.input = input;
+                }
+            }
+            ("LEAVE", Some(index)) => {
+                let client = &mut self.clients[index];
+                client.connected = false;
+                println!("{} abandonó la partida", client.name);
+            }
+            _ => {}
+        }
+    }
+
+    fn send_welcome(&self, client_index: usize) {
+        if let Some(level) = self.level {
+            let role = match self.role(client_index) {
+                NetRole::Runner(index) => format!("runner {}", index),
+                NetRole::Taylor => "taylor 0".to_string(),
+            };
+            send(
+                &self.socket,
+                self.clients[client_index].addr,
+                &format!(
+                    "WELCOME {} {} {} {:016x} {} {}",
+                    role,
+                    level.level_index,
+                    level.difficulty.config_name(),
+                    level.maze_hash,
+                    level.seed,
+                    level.versus as u8,
+                ),
+            );
+        }
+    }
+
+    // Una semilla nueva implica un nivel nuevo o un reintento: los clientes deben recargar.
+    pub fn sync_level(&mut self, level: LevelInfo) {
+        if self.level == Some(level) {
+            return;
+        }
+        self.level = Some(level);
+        self.cell_history.clear();
+        self.oversize_logged = false;
+        for index in 0..self.clients.len() {
+            self.send_welcome(index);
+        }
+    }
+
+    // Entradas de los corredores remotos en orden de players[1..], y la de Taylor si la lleva un cliente.
+    pub fn take_inputs(&mut self) -> (Vec<TickInput>, Option<TickInput>) {
+        let mut runners = Vec::new();
+        let mut taylor = None;
+        for client_index in 0..self.clients.len() {
+            let input = self.take_input(client_index);
+            match self.role(client_index) {
+                NetRole::Runner(_) => runners.push(input),
+                NetRole::Taylor => taylor = self.is_connected(client_index).then_some(input),
+            }
+        }
+        (runners, taylor)
+    }
+
+    // El giro con ratón llega como deltas: se acumulan entre ticks y se consumen en el primero.
+    fn take_input(&mut self, client_index: usize) -> TickInput {
+        if !self.is_connected(client_index) {
+            return TickInput::default();
+        }
+        let client = &mut self.clients[client_index];
+        let mut input = client.input;
+        input.look = client.pending_look;
+        client.pending_look = 0.0;
+        input
+    }
+
+    pub fn disconnected_runners(&self) -> Vec<usize> {
+        (0..self.clients.len())
+            .filter(|&index| !self.is_connected(index))
+            .filter_map(|index| match self.role(index) {
+                NetRole::Runner(player_index) => Some(player_index),
+                NetRole::Taylor => None,
+            })
+            .collect()
+    }
+
+    pub fn broadcast_snapshot(&mut self, game_data: &GameData, players: &[Player], sprite_manager: &SpriteManager) {
+        self.tick += 1;
+        let snapshot = Snapshot::capture(self.tick, game_data, players, sprite_manager);
+        let cell_count = snapshot.changed_cells.len();
+
+        // Al recargar el nivel la lista vuelve a empezar y las cuentas viejas ya no sirven.
+        if self.cell_history.back().is_some_and(|&(_, count)| count > cell_count) {
+            self.cell_history.clear();
+        }
+        self.cell_history.push_back((self.tick, cell_count));
+        if self.cell_history.len() > CELL_HISTORY {
+            self.cell_history.pop_front();
+        }
+
+        for client in self.clients.iter().filter(|client| client.connected) {
+            let cell_base = self
+                .cell_history
+                .iter()
+                .find(|&&(tick, _)| tick == client.acked_tick)
+                .map_or(0, |&(_, count)| count);
+            let message = snapshot.encode(cell_base);
+            // Un datagrama más grande que el búfer de recepción llega truncado y no se puede decodificar.
+            if message.len() > MAX_PACKET {
+                if !self.oversize_logged {
+                    println!("Snapshot de {} bytes supera el máximo de {}; no se envía", message.len(), MAX_PACKET);
+                    self.oversize_logged = true;
+                }
+                continue;
+            }
+            send(&self.socket, client.addr, &message);
+        }
+    }
+
+    pub fn broadcast_end(&self, escaped: bool) {
+        for client in self.clients.iter().filter(|client| client.connected) {
+            for _ in 0..3 {
+                send(&self.socket, client.addr, &format!("END {}", escaped as u8));
+            }
+        }
+    }
+}
+
+pub enum ClientEvent {
+    Welcome { role: NetRole, level: LevelInfo },
+    Ended { escaped: bool },
+    Rejected,
+}
+
+pub struct NetClient {
+    socket: UdpSocket,
+    server: SocketAddr,
+    name: String,
+    pub role: Option<NetRole>,
+    pub level: Option<LevelInfo>,
+    pub ended: Option<bool>,
+    snapshots: VecDeque<(Instant, Snapshot)>,
+    // Todas las celdas cambiadas recibidas en este nivel; los snapshots solo traen las nuevas.
+    changed_cells: Vec<(usize, usize, char)>,
+    sequence: u32,
+    last_join: Instant,
+    last_contact: Instant,
+}
+
+impl NetClient {
+    pub fn connect(address: &str, name: &str) -> io::Result<Self> {
+        let server = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, address.to_string()))?;
+        let socket = bind_socket(0)?;
+        let client = Self {
+            socket,
+            server,
+            name: name.to_string(),
+            role: None,
+            level: None,
+            ended: None,
+            snapshots: VecDeque::new(),
+            changed_cells: Vec::new(),
+            sequence: 0,
+            last_join: Instant::now(),
+            last_contact: Instant::now(),
+        };
+        client.send_join();
+        println!("Conectando a {}...", server);
+        Ok(client)
+    }
+
+    pub fn server(&self) -> SocketAddr {
+        self.server
+    }
+
+    fn send_join(&self) {
+        send(&self.socket, self.server, &format!("JOIN {} {}", PROTOCOL_VERSION, self.name));
+    }
+
+    pub fn connection_lost(&self) -> bool {
+        self.role.is_some() && self.last_contact.elapsed() >= CONNECTION_TIMEOUT
+    }
+
+    pub fn poll(&mut self) -> Vec<ClientEvent> {
+        if self.role.is_none() && self.last_join.elapsed() >= JOIN_RETRY {
+            self.last_join = Instant::now();
+            self.send_join();
+        }
+
+        let mut events = Vec::new();
+        for (message, addr) in drain(&self.socket) {
+            if addr != self.server {
+                continue;
+            }
+            self.last_contact = Instant::now();
+            let (command, body) = message.split_once(' ').unwrap_or((&message, ""));
+            match command {
+                "WELCOME" => {
+                    let fields: Vec<&str> = body.split_whitespace().collect();
+                    let [role, player_index, level_index, difficulty, maze_hash, seed, versus] = fields[..] else {
+                        continue;
+                    };
+                    let (Ok(player_index), Ok(level_index), Some(difficulty), Ok(maze_hash), Ok(seed)) = (
+                        player_index.parse::<usize>(),
+                        level_index.parse::<usize>(),
+                        Difficulty::from_config_name(difficulty),
+                        u64::from_str_radix(maze_hash, 16),
+                        seed.parse::<u64>(),
+                    ) else {
+                        continue;
+                    };
+                    let role = if role == "taylor" { NetRole::Taylor } else { NetRole::Runner(player_index) };
+                    let level = LevelInfo { level_index, difficulty, maze_hash, seed, versus: versus == "1" };
+                    if self.role == Some(role) && self.level == Some(level) {
+                        continue;
+                    }
+                    self.role = Some(role);
+                    self.level = Some(level);
+                    self.ended = None;
+                    self.snapshots.clear();
+                    self.changed_cells.clear();
+                    events.push(ClientEvent::Welcome { role, level });
+                }
+                "SNAPSHOT" => {
+                    let Some((mut snapshot, cell_base)) = Snapshot::decode(body) else {
+                        continue;
+                    };
+                    if self.snapshots.back().is_some_and(|(_, last)| last.tick >= snapshot.tick) {
+                        continue;
+                    }
+                    // Si falta un tramo de celdas se descarta; el anfitrión lo reenvía desde
+                    // el último tick confirmado.
+                    if cell_base > self.changed_cells.len() {
+                        continue;
+                    }
+                    self.changed_cells.truncate(cell_base);
+                    self.changed_cells.append(&mut snapshot.changed_cells);
+                    snapshot.changed_cells = self.changed_cells.clone();
+                    // El anfitrión deja de enviar snapshots al terminar la partida: si vuelven a
+                    // llegar es que reanudó desde un punto de control.
+                    self.ended = None;
+                    self.snapshots.push_back((Instant::now(), snapshot));
+                    if self.snapshots.len() > MAX_SNAPSHOTS {
+                        self.snapshots.pop_front();
+                    }
+                }
+                "END" if self.ended.is_none() => {
+                    let escaped = body.trim() == "1";
+                    self.ended = Some(escaped);
+                    events.push(ClientEvent::Ended { escaped });
+                }
+                "REJECT" => events.push(ClientEvent::Rejected),
+                _ => {}
+            }
+        }
+        events
+    }
+
+    pub fn send_input(&mut self, input: &TickInput) {
+        if self.role.is_none() {
+            return;
+        }
+        self.sequence += 1;
+        let ack = self.latest().map_or(0, |snapshot| snapshot.tick);
+        send(&self.socket, self.server, &format!("INPUT {} {} {}", self.sequence, ack, encode_input(input)));
+    }
+
+    pub fn leave(&self) {
+        send(&self.socket, self.server, "LEAVE");
+    }
+
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.snapshots.back().map(|(_, snapshot)| snapshot)
+    }
+
+    // Se dibuja el mundo con un pequeño retraso para tener siempre dos snapshots entre los
+    // que interpolar, aunque alguno se pierda por el camino.
+    pub fn interpolated(&self) -> Option<Snapshot> {
+        let render_time = Instant::now().checked_sub(INTERPOLATION_DELAY)?;
+        let (first_time, first) = self.snapshots.front()?;
+        if render_time <= *first_time {
+            return Some(first.clone());
+        }
+
+        for ((previous_time, previous), (next_time, next)) in self.snapshots.iter().zip(self.snapshots.iter().skip(1)) {
+            if render_time >= *previous_time && render_time <= *next_time {
+                let span = next_time.duration_since(*previous_time).as_secs_f32();
+                let t = if span > 0.0 {
+                    render_time.duration_since(*previous_time).as_secs_f32() / span
+                } else {
+                    1.0
+                };
+                return Some(previous.interpolate(next, t));
+            }
+        }
+
+        self.latest().cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_snapshot() -> Snapshot {
+        Snapshot {
+            tick: 42,
+            game_timer: 12.5,
+            gasoline_collected: 2,
+            max_lives: 3,
+            pickups_collected: vec![true, false, true],
+            players: vec![NetPlayer {
+                x: 150.25,
+                y: 320.5,
+                a: -1.25,
+                status: PlayerStatus::Caught,
+                lives: 2,
+                invulnerable: 0.5,
+                stamina: 0.75,
+                exhausted: 0.0,
+                items: [1; ALL_ITEMS.len()],
+                selected_item: 1,
+                speed_boost: 2.0,
+                map_reveal: 0.0,
+            }],
+            chasers: vec![Vector2::new(50.0, 60.0), Vector2::new(70.5, 80.25)],
+            changed_cells: vec![(3, 4, 'd'), (5, 1, 's'), (6, 2, 'g')],
+            world_effects: vec![WorldEffect { kind: ALL_ITEMS[1], position: Vector2::new(10.0, 20.0), remaining: 3.5 }],
+            taylor: Some(NetTaylor { a: 0.5, sense_remaining: 1.0, sense_cooldown: 2.0, stamina: 0.25, exhausted: 0.0 }),
+        }
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let snapshot = sample_snapshot();
+        let message = snapshot.encode(0);
+        let (decoded, cell_base) = Snapshot::decode(message.strip_prefix("SNAPSHOT ").unwrap()).unwrap();
+
+        assert_eq!(cell_base, 0);
+        assert_eq!(decoded.tick, 42);
+        assert_eq!(decoded.pickups_collected, snapshot.pickups_collected);
+        assert_eq!(decoded.players.len(), 1);
+        assert_eq!(decoded.players[0].status, PlayerStatus::Caught);
+        assert_eq!(decoded.players[0].items, snapshot.players[0].items);
+        assert_eq!((decoded.players[0].x, decoded.players[0].y), (150.25, 320.5));
+        assert_eq!(decoded.chasers, snapshot.chasers);
+        assert_eq!(decoded.changed_cells, snapshot.changed_cells);
+        assert_eq!(decoded.world_effects.len(), 1);
+        assert_eq!(decoded.taylor.map(|taylor| taylor.stamina), Some(0.25));
+    }
+
+    #[test]
+    fn snapshot_sends_only_cells_after_base() {
+        let message = sample_snapshot().encode(2);
+        let (decoded, cell_base) = Snapshot::decode(message.strip_prefix("SNAPSHOT ").unwrap()).unwrap();
+
+        assert_eq!(cell_base, 2);
+        assert_eq!(decoded.changed_cells, vec![(6, 2, 'g')]);
+        assert!(message.len() < MAX_PACKET);
+    }
+
+    #[test]
+    fn snapshot_rejects_truncated_body() {
+        let message = sample_snapshot().encode(0);
+        assert!(Snapshot::decode(&message["SNAPSHOT ".len()..message.len() / 2]).is_none());
+    }
+}
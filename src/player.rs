@@ -1,42 +1,113 @@
 use raylib::prelude::*;
 use std::f32::consts::PI;
 use crate::maze::Maze;
+use crate::collision::{self, PLAYER_RADIUS};
+use crate::input::TickInput;
+use crate::difficulty::SprintTuning;
+use crate::inventory::{Inventory, SPEED_BOOST_FACTOR};
+use crate::TICK_DT;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PlayerStatus {
+    Active,
+    Caught,
+    Escaped,
+}
+
+pub const SPAWN_POSITION: Vector2 = Vector2::new(150.0, 150.0);
+pub const INVULNERABLE_TIME: f32 = 2.0;
+pub const EXHAUSTED_SPEED_FACTOR: f32 = 0.7;
 
 pub struct Player {
     pub pos: Vector2,
     pub a: f32,  
     pub fov: f32,
+    pub status: PlayerStatus,
+    pub lives: u32,
+    pub invulnerable: f32,
+    pub respawn: Vector2,
+    pub stamina: f32,
+    pub exhausted: f32,
+    pub inventory: Inventory,
+    pub speed_boost: f32,
+    pub map_reveal: f32,
 }
 
-fn is_valid_position(maze: &Maze, x: f32, y: f32, block_size: usize) -> bool {
-    let grid_x = (x / block_size as f32) as usize;
-    let grid_y = (y / block_size as f32) as usize;
-    
-    if grid_y >= maze.len() || grid_x >= maze[0].len() {
-        return false;
+impl Player {
+    pub fn new(fov: f32) -> Self {
+        Self {
+            pos: SPAWN_POSITION,
+            a: PI / 4.0,
+            fov,
+            status: PlayerStatus::Active,
+            lives: 0,
+            invulnerable: 0.0,
+            respawn: SPAWN_POSITION,
+            stamina: 1.0,
+            exhausted: 0.0,
+            inventory: Inventory::default(),
+            speed_boost: 0.0,
+            map_reveal: 0.0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status == PlayerStatus::Active
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted > 0.0
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
+    }
+
+    // Devuelve true si aún le quedaban vidas y vuelve al último punto de reaparición.
+    pub fn lose_life(&mut self) -> bool {
+        self.lives = self.lives.saturating_sub(1);
+        if self.lives == 0 {
+            self.status = PlayerStatus::Caught;
+            return false;
+        }
+        self.pos = self.respawn;
+        self.invulnerable = INVULNERABLE_TIME;
+        true
+    }
+}
+
+// Correr gasta energía; al agotarla el jugador queda un rato sin poder correr y más lento.
+fn update_stamina(player: &mut Player, input: &TickInput, sprint: &SprintTuning) -> f32 {
+    let moving = input.forward != 0.0 || input.strafe != 0.0;
+    let sprinting = input.sprint && moving && !player.is_exhausted() && player.stamina > 0.0;
+
+    if sprinting {
+        player.stamina -= sprint.drain_per_second * TICK_DT;
+        if player.stamina <= 0.0 {
+            player.stamina = 0.0;
+            player.exhausted = sprint.exhaustion_time;
+        }
+        sprint.speed_multiplier
+    } else if player.is_exhausted() {
+        player.exhausted = (player.exhausted - TICK_DT).max(0.0);
+        EXHAUSTED_SPEED_FACTOR
+    } else {
+        player.stamina = (player.stamina + sprint.regen_per_second * TICK_DT).min(1.0);
+        1.0
     }
-    
-    maze[grid_y][grid_x] == ' '
 }
 
-pub fn process_events(player: &mut Player, rl: &RaylibHandle, maze: &Maze, block_size: usize) {
+pub fn apply_input(player: &mut Player, input: &TickInput, maze: &Maze, block_size: usize, sprint: &SprintTuning) {
     const MOVE_SPEED: f32 = 10.0;
     const ROTATION_SPEED: f32 = PI / 12.0;
-    const GAMEPAD_SENSITIVITY: f32 = 0.8;
     
-    if rl.is_key_down(KeyboardKey::KEY_LEFT) {
-        player.a += ROTATION_SPEED;
-    }
-    if rl.is_key_down(KeyboardKey::KEY_RIGHT) {
-        player.a -= ROTATION_SPEED;
+    let mut move_speed = MOVE_SPEED * update_stamina(player, input, sprint);
+    if player.speed_boost > 0.0 {
+        player.speed_boost = (player.speed_boost - TICK_DT).max(0.0);
+        move_speed *= SPEED_BOOST_FACTOR;
     }
     
-    if rl.is_gamepad_available(0) {
-        let right_stick_x = rl.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_RIGHT_X);
-        if right_stick_x.abs() > 0.2 {
-            player.a -= right_stick_x * ROTATION_SPEED * GAMEPAD_SENSITIVITY;
-        }
-    }
+    player.a += input.turn * ROTATION_SPEED + input.look;
     
     if player.a < 0.0 {
         player.a += 2.0 * PI;
@@ -44,68 +115,10 @@ pub fn process_events(player: &mut Player, rl: &RaylibHandle, maze: &Maze, block
         player.a -= 2.0 * PI;
     }
     
-    let mut new_x = player.pos.x;
-    let mut new_y = player.pos.y;
-    
-    let mut gamepad_move_forward = 0.0;
-    let mut gamepad_move_strafe = 0.0;
-    
-    if rl.is_gamepad_available(0) {
-        let left_stick_y = rl.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_LEFT_Y);
-        let left_stick_x = rl.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_LEFT_X);
-        
-        if left_stick_y.abs() > 0.2 {
-            gamepad_move_forward = -left_stick_y; 
-        }
-        if left_stick_x.abs() > 0.2 {
-            gamepad_move_strafe = left_stick_x;
-        }
-    }
-    
-    if rl.is_key_down(KeyboardKey::KEY_UP) || rl.is_key_down(KeyboardKey::KEY_W) || gamepad_move_forward > 0.0 {
-        let move_amount = if gamepad_move_forward > 0.0 { 
-            MOVE_SPEED * gamepad_move_forward 
-        } else { 
-            MOVE_SPEED 
-        };
-        new_x += move_amount * player.a.cos();
-        new_y += move_amount * player.a.sin();
-    }
+    let delta = Vector2::new(
+        move_speed * input.forward * player.a.cos() + move_speed * input.strafe * (player.a + PI/2.0).cos(),
+        move_speed * input.forward * player.a.sin() + move_speed * input.strafe * (player.a + PI/2.0).sin(),
+    );
     
-    if rl.is_key_down(KeyboardKey::KEY_DOWN) || rl.is_key_down(KeyboardKey::KEY_S) || gamepad_move_forward < 0.0 {
-        let move_amount = if gamepad_move_forward < 0.0 { 
-            MOVE_SPEED * (-gamepad_move_forward) 
-        } else { 
-            MOVE_SPEED 
-        };
-        new_x -= move_amount * player.a.cos();
-        new_y -= move_amount * player.a.sin();
-    }
-    
-    if rl.is_key_down(KeyboardKey::KEY_A) || gamepad_move_strafe < 0.0 {
-        let move_amount = if gamepad_move_strafe < 0.0 { 
-            MOVE_SPEED * (-gamepad_move_strafe) 
-        } else { 
-            MOVE_SPEED 
-        };
-        new_x += move_amount * (player.a - PI/2.0).cos();
-        new_y += move_amount * (player.a - PI/2.0).sin();
-    }
-    
-    if rl.is_key_down(KeyboardKey::KEY_D) || gamepad_move_strafe > 0.0 {
-        let move_amount = if gamepad_move_strafe > 0.0 { 
-            MOVE_SPEED * gamepad_move_strafe 
-        } else { 
-            MOVE_SPEED 
-        };
-        new_x += move_amount * (player.a + PI/2.0).cos();
-        new_y += move_amount * (player.a + PI/2.0).sin();
-    }
-    
-    if is_valid_position(maze, new_x, player.pos.y, block_size) {
-        player.pos.x = new_x;
-    }
-    if is_valid_position(maze, player.pos.x, new_y, block_size) {
-        player.pos.y = new_y;
-    }
-}
\ No newline at end of file
+    player.pos = collision::move_and_slide(maze, player.pos, delta, PLAYER_RADIUS, block_size);
+}
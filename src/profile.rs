@@ -0,0 +1,238 @@
+use std::fs;
+use std::path::PathBuf;
+use crate::config::{config_dir, parse_bool, read_key_values, write_file};
+use crate::difficulty::Difficulty;
+
+#[derive(Clone, Debug, Default)]
+pub struct LevelProgress {
+    pub unlocked: bool,
+    pub completed: bool,
+    pub best_time: Option<f32>,
+    pub cans_collected: usize,
+    pub difficulty_cleared: Option<Difficulty>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Profile {
+    pub name: String,
+    pub unlock_all: bool,
+    pub levels: Vec<LevelProgress>,
+    pub endless_best_stage: usize,
+}
+
+impl Profile {
+    pub fn new(name: &str, level_count: usize) -> Self {
+        let mut levels = vec![LevelProgress::default(); level_count];
+        if let Some(first) = levels.first_mut() {
+            first.unlocked = true;
+        }
+
+        Self {
+            name: name.to_string(),
+            unlock_all: false,
+            levels,
+            endless_best_stage: 0,
+        }
+    }
+
+    fn profiles_dir() -> PathBuf {
+        config_dir().join("profiles")
+    }
+
+    fn file_path(name: &str) -> PathBuf {
+        let file_name: String = name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        Self::profiles_dir().join(format!("{}.profile", file_name))
+    }
+
+    pub fn list_names() -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(Self::profiles_dir())
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "profile"))
+                    .filter_map(|path| {
+                        let entries = read_key_values(&path)?;
+                        entries.into_iter().find(|(key, _)| key == "name").map(|(_, value)| value)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    pub fn load_or_create(name: &str, level_count: usize) -> Self {
+        let mut profile = Profile::new(name, level_count);
+        let path = Self::file_path(name);
+
+        let Some(entries) = read_key_values(&path) else {
+            println!("Creando perfil nuevo: {}", name);
+            profile.save();
+            return profile;
+        };
+
+        for (key, value) in entries {
+            if !profile.apply_entry(&key, &value) {
+                println!("Entrada de perfil inválida: {} = {}", key, value);
+            }
+        }
+
+        println!("Perfil cargado: {}", profile.name);
+        profile
+    }
+
+    fn apply_entry(&mut self, key: &str, value: &str) -> bool {
+        match key {
+            "name" => {
+                self.name = value.to_string();
+                true
+            }
+            "unlock_all" => parse_bool(value).map(|v| self.unlock_all = v).is_some(),
+            "endless_best_stage" => value.parse().map(|v| self.endless_best_stage = v).is_ok(),
+            _ => {
+                let mut parts = key.splitn(3, '.');
+                let (Some("level"), Some(index), Some(field)) = (parts.next(), parts.next(), parts.next()) else {
+                    return true;
+                };
+                let Some(level) = index.parse::<usize>().ok().and_then(|i| self.levels.get_mut(i)) else {
+                    return true;
+                };
+
+                match field {
+                    "unlocked" => parse_bool(value).map(|v| level.unlocked = level.unlocked || v).is_some(),
+                    "completed" => parse_bool(value).map(|v| level.completed = v).is_some(),
+                    "best_time" => value.parse().map(|v| level.best_time = Some(v)).is_ok(),
+                    "cans_collected" => value.parse().map(|v| level.cans_collected = v).is_ok(),
+                    "difficulty_cleared" => {
+                        Difficulty::from_config_name(value).map(|v| level.difficulty_cleared = Some(v)).is_some()
+                    }
+                    _ => true,
+                }
+            }
+        }
+    }
+
+    pub fn to_file_contents(&self) -> String {
+        let mut contents = String::from("# Perfil de guardado\n");
+        contents.push_str(&format!("name = {}\n", self.name));
+        contents.push_str(&format!("unlock_all = {}\n", self.unlock_all));
+        contents.push_str(&format!("endless_best_stage = {}\n", self.endless_best_stage));
+
+        for (index, level) in self.levels.iter().enumerate() {
+            contents.push_str(&format!("level.{}.unlocked = {}\n", index, level.unlocked));
+            contents.push_str(&format!("level.{}.completed = {}\n", index, level.completed));
+            if let Some(best_time) = level.best_time {
+                contents.push_str(&format!("level.{}.best_time = {}\n", index, best_time));
+            }
+            contents.push_str(&format!("level.{}.cans_collected = {}\n", index, level.cans_collected));
+            if let Some(difficulty) = level.difficulty_cleared {
+                contents.push_str(&format!("level.{}.difficulty_cleared = {}\n", index, difficulty.config_name()));
+            }
+        }
+        contents
+    }
+
+    pub fn save(&self) {
+        let path = Self::file_path(&self.name);
+        if let Err(e) = write_file(&path, &self.to_file_contents()) {
+            println!("Error guardando perfil {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn is_unlocked(&self, level_index: usize) -> bool {
+        self.unlock_all || self.levels.get(level_index).is_some_and(|level| level.unlocked)
+    }
+
+    pub fn record_attempt(&mut self, level_index: usize, cans_collected: usize) {
+        if let Some(level) = self.levels.get_mut(level_index) {
+            level.cans_collected = level.cans_collected.max(cans_collected);
+        }
+        self.save();
+    }
+
+    pub fn record_endless(&mut self, stages_survived: usize) -> bool {
+        let new_best = stages_survived > self.endless_best_stage;
+        if new_best {
+            self.endless_best_stage = stages_survived;
+            self.save();
+        }
+        new_best
+    }
+
+    pub fn record_completion(
+        &mut self,
+        level_index: usize,
+        time: f32,
+        cans_collected: usize,
+        difficulty: Difficulty,
+    ) -> bool {
+        let Some(level) = self.levels.get_mut(level_index) else {
+            return false;
+        };
+
+        let new_best = level.best_time.is_none_or(|best| time < best);
+        level.completed = true;
+        level.cans_collected = level.cans_collected.max(cans_collected);
+        if new_best {
+            level.best_time = Some(time);
+        }
+        if level.difficulty_cleared.is_none_or(|cleared| difficulty > cleared) {
+            level.difficulty_cleared = Some(difficulty);
+        }
+
+        if let Some(next_level) = self.levels.get_mut(level_index + 1) {
+            next_level.unlocked = true;
+        }
+
+        self.save();
+        new_best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_key_values;
+
+    #[test]
+    fn file_contents_round_trip() {
+        let mut profile = Profile::new("Tom", 3);
+        profile.unlock_all = true;
+        profile.endless_best_stage = 4;
+        profile.levels[1].unlocked = true;
+        profile.levels[1].completed = true;
+        profile.levels[1].best_time = Some(42.5);
+        profile.levels[1].cans_collected = 3;
+        profile.levels[1].difficulty_cleared = Some(Difficulty::Hard);
+
+        let mut loaded = Profile::new("", 3);
+        for (key, value) in parse_key_values(&profile.to_file_contents()) {
+            assert!(loaded.apply_entry(&key, &value), "{} = {}", key, value);
+        }
+
+        assert_eq!(loaded.to_file_contents(), profile.to_file_contents());
+        assert_eq!(loaded.name, "Tom");
+        assert!(loaded.levels[1].completed);
+        assert!(!loaded.levels[2].unlocked);
+    }
+
+    #[test]
+    fn completion_unlocks_next_level() {
+        let mut profile = Profile::new("__prueba_desbloqueo__", 3);
+        assert!(!profile.is_unlocked(1));
+
+        assert!(profile.record_completion(0, 30.0, 2, Difficulty::Normal));
+        assert!(!profile.record_completion(0, 35.0, 1, Difficulty::Easy));
+        let _ = fs::remove_file(Profile::file_path(&profile.name));
+
+        assert!(profile.is_unlocked(1));
+        assert!(!profile.is_unlocked(2));
+        assert_eq!(profile.levels[0].best_time, Some(30.0));
+        assert_eq!(profile.levels[0].cans_collected, 2);
+        assert_eq!(profile.levels[0].difficulty_cleared, Some(Difficulty::Normal));
+    }
+}
@@ -0,0 +1,361 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::config::{config_dir, read_key_values, write_file};
+use crate::difficulty::Difficulty;
+use crate::input::TickInput;
+use crate::{GameData, TICK_DT, TICK_RATE};
+
+pub const REPLAY_VERSION: u32 = 1;
+pub const MIN_SPEED: f32 = 0.25;
+pub const MAX_SPEED: f32 = 8.0;
+
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub level_index: usize,
+    pub difficulty: Difficulty,
+    pub maze_hash: u64,
+    pub seed: u64,
+    pub endless: Option<(usize, f32)>,
+    pub lives: u32,
+    pub inputs: Vec<TickInput>,
+}
+
+pub fn new_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+pub fn replays_dir() -> PathBuf {
+    config_dir().join("replays")
+}
+
+pub fn last_run_path() -> PathBuf {
+    replays_dir().join("last_run.replay")
+}
+
+// Los f32 se guardan como sus bits en hexadecimal: un valor redondeado al escribirlo
+// haría que la simulación reproducida divergiera de la original.
+pub fn encode_input(input: &TickInput) -> String {
+    let flags = input.interact as u8
+        | (input.sprint as u8) << 1
+        | (input.next_item as u8) << 2
+        | (input.use_item as u8) << 3;
+    format!(
+        "{:08x} {:08x} {:08x} {:08x} {}",
+        input.forward.to_bits(),
+        input.strafe.to_bits(),
+        input.turn.to_bits(),
+        input.look.to_bits(),
+        flags,
+    )
+}
+
+pub fn decode_input(value: &str) -> Option<TickInput> {
+    let mut fields = value.split_whitespace();
+    let mut next_f32 = || u32::from_str_radix(fields.next()?, 16).ok().map(f32::from_bits);
+    let forward = next_f32()?;
+    let strafe = next_f32()?;
+    let turn = next_f32()?;
+    let look = next_f32()?;
+    let flags: u8 = fields.next()?.parse().ok()?;
+
+    Some(TickInput {
+        forward,
+        strafe,
+        turn,
+        look,
+        interact: flags & 1 != 0,
+        sprint: flags & 2 != 0,
+        next_item: flags & 4 != 0,
+        use_item: flags & 8 != 0,
+    })
+}
+
+impl Replay {
+    pub fn new(level_index: usize, difficulty: Difficulty, maze_hash: u64, seed: u64) -> Self {
+        Self {
+            level_index,
+            difficulty,
+            maze_hash,
+            seed,
+            endless: None,
+            lives: 0,
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn for_run(game_data: &GameData) -> Self {
+        let mut replay = Self::new(game_data.current_level, game_data.difficulty, game_data.maze_hash, game_data.seed);
+        replay.endless = game_data.endless.as_ref().map(|run| (run.stage, run.time_remaining));
+        replay.lives = game_data.max_lives;
+        replay
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.inputs.len() as f32 * TICK_DT
+    }
+
+    pub fn load(path: &Path) -> Option<Self> {
+        let Some(pairs) = read_key_values(path) else {
+            println!("No se pudo leer la repetición {}", path.display());
+            return None;
+        };
+
+        let mut replay = Replay::new(0, Difficulty::Normal, 0, 0);
+        for (key, value) in pairs {
+            let valid = match key.as_str() {
+                "version" => match value.parse::<u32>() {
+                    Ok(version) if version <= REPLAY_VERSION => true,
+                    _ => {
+                        println!("Repetición con versión {} desconocida", value);
+                        return None;
+                    }
+                },
+                "tick_rate" => {
+                    if value.parse::<u32>().ok() != Some(TICK_RATE) {
+                        println!("Repetición grabada a {} ticks por segundo, se esperaban {}", value, TICK_RATE);
+                        return None;
+                    }
+                    true
+                }
+                "level" => value.parse().map(|v| replay.level_index = v).is_ok(),
+                "difficulty" => Difficulty::from_config_name(&value).map(|v| replay.difficulty = v).is_some(),
+                "maze_hash" => u64::from_str_radix(&value, 16).map(|v| replay.maze_hash = v).is_ok(),
+                "seed" => value.parse().map(|v| replay.seed = v).is_ok(),
+                "lives" => value.parse().map(|v| replay.lives = v).is_ok(),
+                // Sin una etapa válida la partida no se puede reconstruir: mejor no cargarla.
+                "endless" => {
+                    let parsed = value.split_once(' ').and_then(|(stage, time)| {
+                        Some((stage.parse::<usize>().ok()?, u32::from_str_radix(time, 16).ok()?))
+                    });
+                    match parsed {
+                        Some((stage, time)) if stage > 0 => {
+                            replay.endless = Some((stage, f32::from_bits(time)));
+                            true
+                        }
+                        _ => {
+                            println!("Repetición con etapa infinita inválida: {}", value);
+                            return None;
+                        }
+                    }
+                }
+                "input" => decode_input(&value).map(|input| replay.inputs.push(input)).is_some(),
+                _ => true,
+            };
+            if !valid {
+                println!("Entrada de repetición inválida: {} = {}", key, value);
+            }
+        }
+
+        println!("Repetición cargada: {} ({} ticks)", path.display(), replay.inputs.len());
+        Some(replay)
+    }
+
+    pub fn to_file_contents(&self) -> String {
+        let mut contents = String::from("# Repetición: semilla inicial y entrada por tick\n");
+        contents.push_str(&format!("version = {}\n", REPLAY_VERSION));
+        contents.push_str(&format!("tick_rate = {}\n", TICK_RATE));
+        contents.push_str(&format!("level = {}\n", self.level_index));
+        contents.push_str(&format!("difficulty = {}\n", self.difficulty.config_name()));
+        contents.push_str(&format!("maze_hash = {:016x}\n", self.maze_hash));
+        contents.push_str(&format!("seed = {}\n", self.seed));
+        contents.push_str(&format!("lives = {}\n", self.lives));
+        if let Some((stage, time_remaining)) = self.endless {
+            contents.push_str(&format!("endless = {} {:08x}\n", stage, time_remaining.to_bits()));
+        }
+        for input in &self.inputs {
+            contents.push_str(&format!("input = {}\n", encode_input(input)));
+        }
+        contents
+    }
+
+    pub fn save(&self, path: &Path) -> bool {
+        match write_file(path, &self.to_file_contents()) {
+            Ok(()) => true,
+            Err(e) => {
+                println!("Error guardando repetición {}: {}", path.display(), e);
+                false
+            }
+        }
+    }
+
+    pub fn save_snapshot(&self) -> Option<PathBuf> {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let path = replays_dir().join(format!("replay_{}.replay", seconds));
+        self.save(&path).then_some(path)
+    }
+}
+
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub tick: usize,
+    pub paused: bool,
+    pub speed: f32,
+    pub accumulator: f32,
+    pub finished: bool,
+    pub maze_mismatch: bool,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            tick: 0,
+            paused: false,
+            speed: 1.0,
+            accumulator: 0.0,
+            finished: false,
+            maze_mismatch: false,
+        }
+    }
+
+    pub fn rewind(&mut self) {
+        self.tick = 0;
+        self.accumulator = 0.0;
+        self.finished = false;
+    }
+
+    pub fn next_input(&mut self) -> Option<TickInput> {
+        if self.finished {
+            return None;
+        }
+        let input = *self.replay.inputs.get(self.tick)?;
+        self.tick += 1;
+        Some(input)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.finished || self.tick >= self.replay.inputs.len()
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * 2.0).min(MAX_SPEED);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / 2.0).max(MIN_SPEED);
+    }
+
+    pub fn ticks_due(&mut self, delta_time: f32) -> usize {
+        if self.paused || self.is_over() {
+            self.accumulator = 0.0;
+            return 0;
+        }
+
+        self.accumulator += delta_time.min(0.25) * self.speed;
+        let ticks = (self.accumulator / TICK_DT) as usize;
+        self.accumulator -= ticks as f32 * TICK_DT;
+        ticks
+    }
+
+    pub fn seconds(&self) -> f32 {
+        self.tick as f32 * TICK_DT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn sample_input() -> TickInput {
+        TickInput {
+            forward: 0.1 + 0.2,
+            strafe: -0.0,
+            turn: -0.731,
+            look: 1.0e-7,
+            interact: true,
+            sprint: false,
+            next_item: true,
+            use_item: true,
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}_{}.replay", name, std::process::id()))
+    }
+
+    #[test]
+    fn input_round_trip_keeps_exact_bits() {
+        let input = sample_input();
+        let decoded = decode_input(&encode_input(&input)).unwrap();
+
+        assert_eq!(decoded, input);
+        assert_eq!(decoded.forward.to_bits(), input.forward.to_bits());
+        assert_eq!(decoded.strafe.to_bits(), input.strafe.to_bits());
+    }
+
+    #[test]
+    fn decode_input_rejects_malformed_lines() {
+        assert!(decode_input("").is_none());
+        assert!(decode_input("3f800000 00000000 00000000").is_none());
+        assert!(decode_input("zz 00000000 00000000 00000000 0").is_none());
+    }
+
+    #[test]
+    fn replay_survives_save_and_load() {
+        let mut replay = Replay::new(3, Difficulty::Hard, 0xdead_beef_0123_4567, 987654321);
+        replay.endless = Some((4, 37.25));
+        replay.lives = 2;
+        replay.inputs = vec![sample_input(), TickInput::default(), sample_input()];
+
+        let path = temp_path("replay_round_trip");
+        assert!(replay.save(&path));
+        let loaded = Replay::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded.level_index, 3);
+        assert_eq!(loaded.difficulty, Difficulty::Hard);
+        assert_eq!(loaded.maze_hash, replay.maze_hash);
+        assert_eq!(loaded.seed, replay.seed);
+        assert_eq!(loaded.endless, replay.endless);
+        assert_eq!(loaded.lives, 2);
+        assert_eq!(loaded.inputs, replay.inputs);
+    }
+
+    #[test]
+    fn endless_stage_zero_is_rejected() {
+        let mut replay = Replay::new(0, Difficulty::Normal, 0, 1);
+        replay.endless = Some((0, 30.0));
+
+        let path = temp_path("replay_stage_zero");
+        assert!(replay.save(&path));
+        let loaded = Replay::load(&path);
+        let _ = fs::remove_file(&path);
+
+        assert!(loaded.is_none());
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let replay = Replay::new(0, Difficulty::Normal, 0, 1);
+        let contents = replay
+            .to_file_contents()
+            .replace(&format!("version = {}", REPLAY_VERSION), &format!("version = {}", REPLAY_VERSION + 1));
+
+        let path = temp_path("replay_future_version");
+        fs::write(&path, contents).unwrap();
+        let loaded = Replay::load(&path);
+        let _ = fs::remove_file(&path);
+
+        assert!(loaded.is_none());
+    }
+
+    #[test]
+    fn player_runs_ticks_at_playback_speed() {
+        let mut replay = Replay::new(0, Difficulty::Normal, 0, 1);
+        replay.inputs = vec![TickInput::default(); 100];
+        let mut player = ReplayPlayer::new(replay);
+
+        assert_eq!(player.ticks_due(TICK_DT * 4.5), 4);
+        player.faster();
+        assert_eq!(player.ticks_due(TICK_DT * 2.1), 4);
+        player.paused = true;
+        assert_eq!(player.ticks_due(TICK_DT * 10.0), 0);
+    }
+}
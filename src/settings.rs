@@ -0,0 +1,483 @@
+use raylib::prelude::*;
+use std::path::PathBuf;
+use crate::config::{config_dir, parse_bool, read_key_values, write_file};
+use crate::input::InputMap;
+use crate::texture::TextureFilter;
+
+pub const MAX_LIVES: u32 = 9;
+pub const MAX_RENDER_THREADS: usize = 16;
+pub const MIN_WINDOW_WIDTH: i32 = 640;
+pub const MIN_WINDOW_HEIGHT: i32 = 480;
+
+pub const RESOLUTIONS: [(i32, i32); 4] = [
+    (960, 540),
+    (1280, 720),
+    (1600, 900),
+    (1920, 1080),
+];
+
+// Resolución a la que se dibuja el mundo antes de estirarlo a la ventana; el HUD siempre va
+// a resolución nativa.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RenderScale {
+    Half,
+    ThreeQuarters,
+    Full,
+    Retro,
+}
+
+const RETRO_WIDTH: i32 = 320;
+const RENDER_SCALES: [RenderScale; 4] = [RenderScale::Half, RenderScale::ThreeQuarters, RenderScale::Full, RenderScale::Retro];
+
+impl RenderScale {
+    fn code(self) -> &'static str {
+        match self {
+            RenderScale::Half => "50",
+            RenderScale::ThreeQuarters => "75",
+            RenderScale::Full => "100",
+            RenderScale::Retro => "retro",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        RENDER_SCALES.iter().copied().find(|scale| scale.code() == code)
+    }
+
+    fn cycle(self, direction: i32) -> Self {
+        let current = RENDER_SCALES.iter().position(|&scale| scale == self).unwrap_or(2) as i32;
+        RENDER_SCALES[(current + direction).rem_euclid(RENDER_SCALES.len() as i32) as usize]
+    }
+
+    // El modo retro fija 320 columnas y saca el alto de la proporción de la ventana, así los
+    // píxeles quedan cuadrados al estirar la imagen (320x200 en 16:10, 320x180 en 16:9).
+    pub fn internal_size(self, window_width: i32, window_height: i32) -> (i32, i32) {
+        match self {
+            RenderScale::Half => (window_width / 2, window_height / 2),
+            RenderScale::ThreeQuarters => (window_width * 3 / 4, window_height * 3 / 4),
+            RenderScale::Full => (window_width, window_height),
+            RenderScale::Retro => (RETRO_WIDTH, (RETRO_WIDTH * window_height / window_width.max(1)).max(1)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Language {
+    Spanish,
+    English,
+}
+
+impl Language {
+    pub fn pick(self, spanish: &'static str, english: &'static str) -> &'static str {
+        match self {
+            Language::Spanish => spanish,
+            Language::English => english,
+        }
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            Language::Spanish => "es",
+            Language::English => "en",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "es" => Some(Language::Spanish),
+            "en" => Some(Language::English),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub window_width: i32,
+    pub window_height: i32,
+    pub fullscreen: bool,
+    pub fov_degrees: f32,
+    pub texture_filter: TextureFilter,
+    pub render_threads: usize,
+    pub render_scale: RenderScale,
+    pub smooth_upscale: bool,
+    pub mouse_sensitivity: f32,
+    pub gamepad_sensitivity: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub language: Language,
+    pub profile_name: String,
+    pub lives: u32,
+    pub controls: InputMap,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window_width: 1280,
+            window_height: 720,
+            fullscreen: false,
+            fov_degrees: 60.0,
+            texture_filter: TextureFilter::Trilinear,
+            render_threads: 0,
+            render_scale: RenderScale::Full,
+            smooth_upscale: false,
+            mouse_sensitivity: 0.003,
+            gamepad_sensitivity: 0.8,
+            music_volume: 0.4,
+            sfx_volume: 0.8,
+            language: Language::Spanish,
+            profile_name: "Jugador 1".to_string(),
+            lives: 0,
+            controls: InputMap::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn file_path() -> PathBuf {
+        config_dir().join("settings.cfg")
+    }
+
+    pub fn load() -> Self {
+        let mut settings = Settings::default();
+        let path = Self::file_path();
+
+        let Some(entries) = read_key_values(&path) else {
+            println!("No se encontró {}, usando configuración por defecto", path.display());
+            return settings;
+        };
+
+        for (key, value) in entries {
+            if !settings.apply_entry(&key, &value) {
+                println!("Valor de configuración inválido: {} = {}", key, value);
+            }
+        }
+
+        println!("Configuración cargada desde {}", path.display());
+        settings
+    }
+
+    fn apply_entry(&mut self, key: &str, value: &str) -> bool {
+        if let Some(valid) = self.controls.apply_entry(key, value) {
+            return valid;
+        }
+
+        match key {
+            "window_width" => value.parse().map(|v: i32| self.window_width = v.max(MIN_WINDOW_WIDTH)).is_ok(),
+            "window_height" => value.parse().map(|v: i32| self.window_height = v.max(MIN_WINDOW_HEIGHT)).is_ok(),
+            "fullscreen" => parse_bool(value).map(|v| self.fullscreen = v).is_some(),
+            "fov" => value.parse().map(|v: f32| self.fov_degrees = v.clamp(45.0, 110.0)).is_ok(),
+            "texture_filter" => TextureFilter::from_config_name(value).map(|v| self.texture_filter = v).is_some(),
+            "render_threads" => value.parse().map(|v: usize| self.render_threads = v.min(MAX_RENDER_THREADS)).is_ok(),
+            "render_scale" => RenderScale::from_code(value).map(|v| self.render_scale = v).is_some(),
+            "smooth_upscale" => parse_bool(value).map(|v| self.smooth_upscale = v).is_some(),
+            "mouse_sensitivity" => value.parse().map(|v| self.mouse_sensitivity = v).is_ok(),
+            "gamepad_sensitivity" => value.parse().map(|v| self.gamepad_sensitivity = v).is_ok(),
+            "music_volume" => value.parse().map(|v: f32| self.music_volume = v.clamp(0.0, 1.0)).is_ok(),
+            "sfx_volume" => value.parse().map(|v: f32| self.sfx_volume = v.clamp(0.0, 1.0)).is_ok(),
+            "language" => Language::from_code(value).map(|v| self.language = v).is_some(),
+            "lives" => value.parse().map(|v: u32| self.lives = v.min(MAX_LIVES)).is_ok(),
+            "profile" => {
+                if value.is_empty() {
+                    return false;
+                }
+                self.profile_name = value.to_string();
+                true
+            }
+            _ => true,
+        }
+    }
+
+    pub fn to_file_contents(&self) -> String {
+        let mut contents = String::from("# Tom Hiddleston's Great Escape - configuración\n");
+        contents.push_str(&format!("window_width = {}\n", self.window_width));
+        contents.push_str(&format!("window_height = {}\n", self.window_height));
+        contents.push_str(&format!("fullscreen = {}\n", self.fullscreen));
+        contents.push_str(&format!("fov = {}\n", self.fov_degrees));
+        contents.push_str(&format!("texture_filter = {}\n", self.texture_filter.config_name()));
+        contents.push_str(&format!("render_threads = {}\n", self.render_threads));
+        contents.push_str(&format!("render_scale = {}\n", self.render_scale.code()));
+        contents.push_str(&format!("smooth_upscale = {}\n", self.smooth_upscale));
+        contents.push_str(&format!("mouse_sensitivity = {}\n", self.mouse_sensitivity));
+        contents.push_str(&format!("gamepad_sensitivity = {}\n", self.gamepad_sensitivity));
+        contents.push_str(&format!("music_volume = {}\n", self.music_volume));
+        contents.push_str(&format!("sfx_volume = {}\n", self.sfx_volume));
+        contents.push_str(&format!("language = {}\n", self.language.code()));
+        contents.push_str(&format!("profile = {}\n", self.profile_name));
+        contents.push_str(&format!("lives = {}\n", self.lives));
+        contents.push_str(&self.controls.to_file_contents());
+        contents
+    }
+
+    pub fn save(&self) {
+        let path = Self::file_path();
+        match write_file(&path, &self.to_file_contents()) {
+            Ok(()) => println!("Configuración guardada en {}", path.display()),
+            Err(e) => println!("Error guardando {}: {}", path.display(), e),
+        }
+    }
+
+    pub fn fov_radians(&self) -> f32 {
+        self.fov_degrees.to_radians()
+    }
+
+    pub fn look_sign(&self) -> f32 {
+        if self.controls.invert_look_x { -1.0 } else { 1.0 }
+    }
+
+    fn cycle_resolution(&mut self, direction: i32) {
+        let current = RESOLUTIONS
+            .iter()
+            .position(|&(w, h)| w == self.window_width && h == self.window_height)
+            .unwrap_or(1) as i32;
+        let next = (current + direction).rem_euclid(RESOLUTIONS.len() as i32) as usize;
+        self.window_width = RESOLUTIONS[next].0;
+        self.window_height = RESOLUTIONS[next].1;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SettingsItem {
+    Resolution,
+    Fullscreen,
+    Fov,
+    TextureFilter,
+    RenderThreads,
+    RenderScale,
+    SmoothUpscale,
+    MouseSensitivity,
+    GamepadSensitivity,
+    InvertLookX,
+    InvertMoveY,
+    Deadzone,
+    MusicVolume,
+    SfxVolume,
+    Language,
+    Lives,
+    Controls,
+    Back,
+}
+
+const SETTINGS_ITEMS: [SettingsItem; 18] = [
+    SettingsItem::Resolution,
+    SettingsItem::Fullscreen,
+    SettingsItem::Fov,
+    SettingsItem::TextureFilter,
+    SettingsItem::RenderThreads,
+    SettingsItem::RenderScale,
+    SettingsItem::SmoothUpscale,
+    SettingsItem::MouseSensitivity,
+    SettingsItem::GamepadSensitivity,
+    SettingsItem::InvertLookX,
+    SettingsItem::InvertMoveY,
+    SettingsItem::Deadzone,
+    SettingsItem::MusicVolume,
+    SettingsItem::SfxVolume,
+    SettingsItem::Language,
+    SettingsItem::Lives,
+    SettingsItem::Controls,
+    SettingsItem::Back,
+];
+
+pub enum SettingsEvent {
+    Changed(SettingsItem),
+    OpenControls,
+    Close,
+}
+
+pub struct SettingsMenu {
+    pub selected: usize,
+}
+
+impl SettingsMenu {
+    pub fn new() -> Self {
+        Self { selected: 0 }
+    }
+
+    pub fn update(&mut self, rl: &RaylibHandle, settings: &mut Settings) -> Option<SettingsEvent> {
+        let gamepad = rl.is_gamepad_available(0);
+        let pressed = |key: KeyboardKey, button: GamepadButton| {
+            rl.is_key_pressed(key) || (gamepad && rl.is_gamepad_button_pressed(0, button))
+        };
+
+        if pressed(KeyboardKey::KEY_BACKSPACE, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT) {
+            return Some(SettingsEvent::Close);
+        }
+        if pressed(KeyboardKey::KEY_DOWN, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN) {
+            self.selected = (self.selected + 1) % SETTINGS_ITEMS.len();
+        }
+        if pressed(KeyboardKey::KEY_UP, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP) {
+            self.selected = (self.selected + SETTINGS_ITEMS.len() - 1) % SETTINGS_ITEMS.len();
+        }
+
+        let item = SETTINGS_ITEMS[self.selected];
+        let direction = if pressed(KeyboardKey::KEY_RIGHT, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT) {
+            1
+        } else if pressed(KeyboardKey::KEY_LEFT, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT) {
+            -1
+        } else if pressed(KeyboardKey::KEY_ENTER, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN) {
+            if item == SettingsItem::Back {
+                return Some(SettingsEvent::Close);
+            }
+            if item == SettingsItem::Controls {
+                return Some(SettingsEvent::OpenControls);
+            }
+            1
+        } else {
+            return None;
+        };
+
+        let step = direction as f32;
+        match item {
+            SettingsItem::Resolution => settings.cycle_resolution(direction),
+            SettingsItem::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            SettingsItem::Fov => settings.fov_degrees = (settings.fov_degrees + step * 5.0).clamp(45.0, 110.0),
+            SettingsItem::TextureFilter => settings.texture_filter = settings.texture_filter.cycle(direction),
+            SettingsItem::RenderThreads => {
+                settings.render_threads =
+                    (settings.render_threads as i32 + direction).rem_euclid(MAX_RENDER_THREADS as i32 + 1) as usize
+            }
+            SettingsItem::RenderScale => settings.render_scale = settings.render_scale.cycle(direction),
+            SettingsItem::SmoothUpscale => settings.smooth_upscale = !settings.smooth_upscale,
+            SettingsItem::MouseSensitivity => {
+                settings.mouse_sensitivity = (settings.mouse_sensitivity + step * 0.0005).clamp(0.0005, 0.02)
+            }
+            SettingsItem::GamepadSensitivity => {
+                settings.gamepad_sensitivity = (settings.gamepad_sensitivity + step * 0.1).clamp(0.1, 3.0)
+            }
+            SettingsItem::InvertLookX => settings.controls.invert_look_x = !settings.controls.invert_look_x,
+            SettingsItem::InvertMoveY => settings.controls.invert_move_y = !settings.controls.invert_move_y,
+            SettingsItem::Deadzone => {
+                settings.controls.stick_deadzone = (settings.controls.stick_deadzone + step * 0.05).clamp(0.0, 0.9)
+            }
+            SettingsItem::MusicVolume => settings.music_volume = (settings.music_volume + step * 0.1).clamp(0.0, 1.0),
+            SettingsItem::SfxVolume => settings.sfx_volume = (settings.sfx_volume + step * 0.1).clamp(0.0, 1.0),
+            SettingsItem::Language => {
+                settings.language = match settings.language {
+                    Language::Spanish => Language::English,
+                    Language::English => Language::Spanish,
+                }
+            }
+            SettingsItem::Lives => {
+                settings.lives = (settings.lives as i32 + direction).rem_euclid(MAX_LIVES as i32 + 1) as u32
+            }
+            SettingsItem::Controls | SettingsItem::Back => return None,
+        }
+
+        Some(SettingsEvent::Changed(item))
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle, settings: &Settings) {
+        let lang = settings.language;
+        let center_x = settings.window_width / 2;
+
+        d.draw_text(lang.pick("OPCIONES", "SETTINGS"), center_x - 100, 60, 48, Color::WHITE);
+
+        // Con muchas opciones la lista se comprime para caber sobre la ayuda en ventanas bajas.
+        let row_height = ((settings.window_height - 200) / SETTINGS_ITEMS.len() as i32).clamp(20, 30);
+        let font_size = row_height - 2;
+
+        for (index, item) in SETTINGS_ITEMS.iter().enumerate() {
+            let y = 120 + index as i32 * row_height;
+            let color = if index == self.selected { Color::YELLOW } else { Color::LIGHTGRAY };
+            let label = item_label(*item, lang);
+            let value = item_value(*item, settings);
+
+            if index == self.selected {
+                d.draw_text(">", center_x - 340, y, font_size, color);
+            }
+            d.draw_text(label, center_x - 300, y, font_size, color);
+            d.draw_text(&value, center_x + 120, y, font_size, color);
+        }
+
+        d.draw_text(
+            lang.pick(
+                "Flechas - Cambiar, Enter - Alternar, Retroceso - Volver",
+                "Arrows - Change, Enter - Toggle, Backspace - Back",
+            ),
+            center_x - 330,
+            settings.window_height - 60,
+            20,
+            Color::GRAY,
+        );
+    }
+}
+
+fn item_label(item: SettingsItem, lang: Language) -> &'static str {
+    match item {
+        SettingsItem::Resolution => lang.pick("Resolución", "Resolution"),
+        SettingsItem::Fullscreen => lang.pick("Pantalla completa", "Fullscreen"),
+        SettingsItem::Fov => lang.pick("Campo de visión", "Field of view"),
+        SettingsItem::TextureFilter => lang.pick("Filtrado de texturas", "Texture filtering"),
+        SettingsItem::RenderThreads => lang.pick("Hilos de dibujo", "Render threads"),
+        SettingsItem::RenderScale => lang.pick("Resolución interna", "Render resolution"),
+        SettingsItem::SmoothUpscale => lang.pick("Escalado suave", "Smooth upscaling"),
+        SettingsItem::MouseSensitivity => lang.pick("Sensibilidad del mouse", "Mouse sensitivity"),
+        SettingsItem::GamepadSensitivity => lang.pick("Sensibilidad del stick", "Stick sensitivity"),
+        SettingsItem::InvertLookX => lang.pick("Invertir mirada", "Invert look"),
+        SettingsItem::InvertMoveY => lang.pick("Invertir avance", "Invert forward"),
+        SettingsItem::Deadzone => lang.pick("Zona muerta del stick", "Stick deadzone"),
+        SettingsItem::MusicVolume => lang.pick("Volumen de música", "Music volume"),
+        SettingsItem::SfxVolume => lang.pick("Volumen de efectos", "SFX volume"),
+        SettingsItem::Language => lang.pick("Idioma", "Language"),
+        SettingsItem::Lives => lang.pick("Vidas", "Lives"),
+        SettingsItem::Controls => lang.pick("Controles...", "Controls..."),
+        SettingsItem::Back => lang.pick("Volver", "Back"),
+    }
+}
+
+fn item_value(item: SettingsItem, settings: &Settings) -> String {
+    let lang = settings.language;
+    let on_off = |value: bool| lang.pick(if value { "Sí" } else { "No" }, if value { "On" } else { "Off" }).to_string();
+
+    match item {
+        SettingsItem::Resolution => format!("{}x{}", settings.window_width, settings.window_height),
+        SettingsItem::Fullscreen => on_off(settings.fullscreen),
+        SettingsItem::Fov => format!("{:.0}°", settings.fov_degrees),
+        SettingsItem::TextureFilter => settings.texture_filter.name(lang).to_string(),
+        SettingsItem::RenderThreads if settings.render_threads == 0 => lang.pick("Automático", "Auto").to_string(),
+        SettingsItem::RenderThreads => settings.render_threads.to_string(),
+        SettingsItem::RenderScale if settings.render_scale == RenderScale::Retro => {
+            let (width, height) = settings.render_scale.internal_size(settings.window_width, settings.window_height);
+            format!("{}x{}", width, height)
+        }
+        SettingsItem::RenderScale => format!("{}%", settings.render_scale.code()),
+        SettingsItem::SmoothUpscale => on_off(settings.smooth_upscale),
+        SettingsItem::MouseSensitivity => format!("{:.4}", settings.mouse_sensitivity),
+        SettingsItem::GamepadSensitivity => format!("{:.1}", settings.gamepad_sensitivity),
+        SettingsItem::InvertLookX => on_off(settings.controls.invert_look_x),
+        SettingsItem::InvertMoveY => on_off(settings.controls.invert_move_y),
+        SettingsItem::Deadzone => format!("{:.2}", settings.controls.stick_deadzone),
+        SettingsItem::MusicVolume => format!("{:.0}%", settings.music_volume * 100.0),
+        SettingsItem::SfxVolume => format!("{:.0}%", settings.sfx_volume * 100.0),
+        SettingsItem::Language => lang.pick("Español", "English").to_string(),
+        SettingsItem::Lives if settings.lives == 0 => lang.pick("No (un golpe)", "Off (one hit)").to_string(),
+        SettingsItem::Lives => settings.lives.to_string(),
+        SettingsItem::Controls | SettingsItem::Back => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retro_keeps_window_aspect() {
+        assert_eq!(RenderScale::Retro.internal_size(1280, 800), (320, 200));
+        assert_eq!(RenderScale::Retro.internal_size(1920, 1080), (320, 180));
+        assert_eq!(RenderScale::Retro.internal_size(1024, 768), (320, 240));
+        assert_eq!(RenderScale::Half.internal_size(1280, 720), (640, 360));
+    }
+
+    #[test]
+    fn rejects_unusable_window_and_profile_entries() {
+        let mut settings = Settings::default();
+        assert!(settings.apply_entry("window_width", "0"));
+        assert!(settings.apply_entry("window_height", "-200"));
+        assert_eq!((settings.window_width, settings.window_height), (MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT));
+
+        let profile_name = settings.profile_name.clone();
+        assert!(!settings.apply_entry("profile", ""));
+        assert_eq!(settings.profile_name, profile_name);
+    }
+}
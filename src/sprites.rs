@@ -1,74 +1,150 @@
 use raylib::prelude::*;
-use crate::framebuffer::Framebuffer;
-use crate::maze::{Maze, SpritePosition};
+use std::sync::Arc;
+use crate::entities::{EntityDef, load_entities};
+use crate::maze::SpritePosition;
+use crate::inventory::{ItemKind, WorldEffect};
 use crate::player::Player;
-use crate::texture::ImageTexture;
+use crate::assets::{AssetManager, TextureHandle};
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-pub enum SpriteType {
-    GasolineCan,
-    Car,
-}
+pub const REMOTE_PLAYER_ENTITY: &str = "remote_player";
+pub const CHECKPOINT_ENTITY: &str = "checkpoint";
+pub const DECOY_ENTITY: &str = "decoy";
+pub const SMOKE_ENTITY: &str = "smoke";
 
 #[derive(Clone, Debug)]
 pub struct Sprite {
     pub x: f32,
     pub y: f32,
-    pub texture_frames: Vec<ImageTexture>,
-    pub scale: f32,
+    pub entity: Arc<EntityDef>,
+    pub texture_frames: Vec<TextureHandle>,
     pub animation_frame: usize,
     pub animation_timer: f32,
     pub collected: bool,
-    pub sprite_type: SpriteType,
 }
 
+impl Sprite {
+    pub fn is(&self, entity_name: &str) -> bool {
+        self.entity.name == entity_name
+    }
+
+    pub fn is_pickup(&self) -> bool {
+        self.entity.is_pickup()
+    }
+}
+
+// Las definiciones vienen de assets/entities.txt; cada sprite comparte los cuadros de su
+// definición a través del gestor de recursos.
 pub struct SpriteManager {
     pub sprites: Vec<Sprite>,
-    pub animation_frame_duration: f32,
-    pub gasoline_can_textures: Vec<ImageTexture>,
+    pub definitions: Vec<Arc<EntityDef>>,
+    pub textures: Vec<Vec<TextureHandle>>,
 }
 
 impl SpriteManager {
-    pub fn new() -> Self {
-        let gasoline_can_textures = vec![
-            ImageTexture::from_file("assets/gasoline_can_1.png"),
-            ImageTexture::from_file("assets/gasoline_can_2.png"),
-            ImageTexture::from_file("assets/gasoline_can_3.png"),
-        ];
-        
+    pub fn new(assets: &mut AssetManager) -> Self {
+        let definitions: Vec<Arc<EntityDef>> = load_entities().into_iter().map(Arc::new).collect();
+        let textures = definitions
+            .iter()
+            .map(|definition| definition.frames.iter().map(|frame| assets.texture(frame)).collect())
+            .collect();
+
         Self {
             sprites: Vec::new(),
-            animation_frame_duration: 0.8,
-            gasoline_can_textures,
+            definitions,
+            textures,
+        }
+    }
+
+    pub fn refresh_textures(&mut self, assets: &mut AssetManager) {
+        self.textures = self
+            .definitions
+            .iter()
+            .map(|definition| definition.frames.iter().map(|frame| assets.texture(frame)).collect())
+            .collect();
+        for sprite in &mut self.sprites {
+            if let Some(definition) = self.definitions.iter().position(|definition| definition.name == sprite.entity.name) {
+                sprite.texture_frames = self.textures[definition].clone();
+            }
         }
     }
 
-    pub fn initialize_gasoline_cans(&mut self, positions: &[SpritePosition]) {
+    pub fn definition_index(&self, entity_name: &str) -> Option<usize> {
+        self.definitions.iter().position(|definition| definition.name == entity_name)
+    }
+
+    pub fn entity_for_glyph(&self, glyph: char) -> Option<usize> {
+        self.definitions.iter().position(|definition| definition.glyph == Some(glyph))
+    }
+
+    pub fn entity_glyphs(&self) -> Vec<char> {
+        self.definitions.iter().filter_map(|definition| definition.glyph).collect()
+    }
+
+    fn spawn(&mut self, definition: usize, x: f32, y: f32) {
+        self.sprites.push(Sprite {
+            x,
+            y,
+            entity: self.definitions[definition].clone(),
+            texture_frames: self.textures[definition].clone(),
+            animation_frame: 0,
+            animation_timer: 0.0,
+            collected: false,
+        });
+    }
+
+    fn spawn_named(&mut self, entity_name: &str, x: f32, y: f32) {
+        match self.definition_index(entity_name) {
+            Some(definition) => self.spawn(definition, x, y),
+            None => println!("Entidad no definida: {}", entity_name),
+        }
+    }
+
+    pub fn initialize_entities(&mut self, positions: &[(usize, SpritePosition)]) {
         self.sprites.clear();
-        
+
+        for (definition, position) in positions {
+            self.spawn(*definition, position.x, position.y);
+        }
+
+        println!("Total de entidades: {}", self.sprites.len());
+    }
+
+    pub fn mark_checkpoint(&mut self, active: Option<usize>) {
+        let checkpoints = self
+            .sprites
+            .iter_mut()
+            .filter(|sprite| sprite.is(CHECKPOINT_ENTITY));
+        for (index, sprite) in checkpoints.enumerate() {
+            let frame = (active == Some(index)) as usize;
+            sprite.animation_frame = frame.min(sprite.texture_frames.len() - 1);
+        }
+    }
+
+    pub fn set_remote_players(&mut self, positions: &[Vector2]) {
+        self.sprites.retain(|sprite| !sprite.is(REMOTE_PLAYER_ENTITY));
+
         for position in positions {
-            let sprite = Sprite {
-                x: position.x,
-                y: position.y,
-                texture_frames: self.gasoline_can_textures.clone(),
-                scale: 0.7,
-                animation_frame: 0,
-                animation_timer: 0.0,
-                collected: false,
-                sprite_type: SpriteType::GasolineCan,
+            self.spawn_named(REMOTE_PLAYER_ENTITY, position.x, position.y);
+        }
+    }
+
+    pub fn set_world_effects(&mut self, effects: &[WorldEffect]) {
+        self.sprites.retain(|sprite| !sprite.is(DECOY_ENTITY) && !sprite.is(SMOKE_ENTITY));
+
+        for effect in effects {
+            let entity_name = match effect.kind {
+                ItemKind::SmokeBomb => SMOKE_ENTITY,
+                _ => DECOY_ENTITY,
             };
-            self.sprites.push(sprite);
-            println!("Bidón de gasolina con textura creado en ({}, {})", position.x, position.y);
+            self.spawn_named(entity_name, effect.position.x, effect.position.y);
         }
-        
-        println!("Total de bidones de gasolina: {}", self.sprites.len());
     }
 
     pub fn update(&mut self, delta_time: f32) {
         for sprite in &mut self.sprites {
-            if !sprite.collected {
+            if !sprite.collected && sprite.entity.animation_speed > 0.0 {
                 sprite.animation_timer += delta_time;
-                if sprite.animation_timer >= self.animation_frame_duration {
+                if sprite.animation_timer >= sprite.entity.animation_speed {
                     sprite.animation_timer = 0.0;
                     sprite.animation_frame = (sprite.animation_frame + 1) % sprite.texture_frames.len();
                 }
@@ -76,21 +152,21 @@ impl SpriteManager {
         }
     }
 
-
-    pub fn check_collision(&mut self, player: &Player, collision_distance: f32) -> Option<usize> {
+    // Solo lo que se recoge al pasar por encima; lo que requiere interactuar va por interaction.
+    pub fn check_collision(&mut self, player: &Player) -> Option<usize> {
         for (i, sprite) in self.sprites.iter_mut().enumerate() {
-            if !sprite.collected {
+            if !sprite.collected && sprite.is_pickup() && !sprite.entity.interact {
                 let dx = sprite.x - player.pos.x;
                 let dy = sprite.y - player.pos.y;
                 let distance = (dx * dx + dy * dy).sqrt();
-                
-                if distance < collision_distance {
+
+                if distance < sprite.entity.pickup_radius {
                     sprite.collected = true;
-                    println!("¡Bidón de gasolina recolectado en ({}, {})!", sprite.x, sprite.y);
+                    println!("¡{} recogido en ({}, {})!", sprite.entity.name, sprite.x, sprite.y);
                     return Some(i);
                 }
             }
         }
         None
     }
-}
\ No newline at end of file
+}
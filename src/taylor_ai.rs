@@ -2,7 +2,7 @@ use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::cmp::Ordering;
 use raylib::prelude::Vector2;
 use crate::maze::Maze;
-use crate::player::Player;
+use crate::collision::{self, TAYLOR_RADIUS};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct GridPos {
@@ -22,7 +22,7 @@ impl GridPos {
         }
     }
     
-    pub fn to_world_pos(&self, block_size: usize) -> Vector2 {
+    pub fn to_world_pos(self, block_size: usize) -> Vector2 {
         Vector2::new(
             (self.x * block_size as i32) as f32 + (block_size as f32 / 2.0),
             (self.y * block_size as i32) as f32 + (block_size as f32 / 2.0),
@@ -52,7 +52,6 @@ pub struct PathNode {
     pub pos: GridPos,
     pub g_cost: i32, 
     pub h_cost: i32, 
-    pub parent: Option<GridPos>,
 }
 
 impl PathNode {
@@ -104,16 +103,7 @@ impl TaylorAI {
     }
     
     pub fn is_walkable(&self, pos: &GridPos, maze: &Maze) -> bool {
-        if pos.y < 0 || pos.y >= maze.len() as i32 {
-            return false;
-        }
-        
-        let row = &maze[pos.y as usize];
-        if pos.x < 0 || pos.x >= row.len() as i32 {
-            return false;
-        }
-        
-        row[pos.x as usize] == ' '
+        collision::is_walkable(maze, pos.x, pos.y)
     }
     
     pub fn find_path_astar(&self, start: GridPos, goal: GridPos, maze: &Maze) -> Vec<GridPos> {
@@ -131,7 +121,6 @@ impl TaylorAI {
             pos: start,
             g_cost: 0,
             h_cost: start.manhattan_distance(&goal),
-            parent: None,
         });
         
         while let Some(current_node) = open_set.pop() {
@@ -167,7 +156,6 @@ impl TaylorAI {
                     pos: neighbor_pos,
                     g_cost: tentative_g_score,
                     h_cost: neighbor_pos.manhattan_distance(&goal),
-                    parent: Some(current_pos),
                 });
             }
         }
@@ -191,7 +179,7 @@ impl TaylorAI {
     pub fn update_ai(
         &mut self,
         taylor_position: &mut Vector2,
-        player: &Player,
+        target: Vector2,
         maze: &Maze,
         block_size: usize,
         delta_time: f32,
@@ -215,7 +203,7 @@ impl TaylorAI {
         
         if should_recalculate {
             let taylor_grid_pos = GridPos::from_world_pos(taylor_position.x, taylor_position.y, block_size);
-            let player_grid_pos = GridPos::from_world_pos(player.pos.x, player.pos.y, block_size);
+            let player_grid_pos = GridPos::from_world_pos(target.x, target.y, block_size);
             
             self.current_path = self.find_path_astar(taylor_grid_pos, player_grid_pos, maze);
             self.path_index = 0;
@@ -249,11 +237,17 @@ impl TaylorAI {
                     taylor_position.x = new_x;
                     taylor_position.y = new_y;
                 } else {
-                    self.try_alternative_movement(taylor_position, dir_x, dir_y, move_distance, maze, block_size);
+                    let delta = Vector2::new(dir_x * move_distance, dir_y * move_distance);
+                    let slid = collision::move_and_slide(maze, *taylor_position, delta, TAYLOR_RADIUS, block_size);
+                    if slid.distance_to(*taylor_position) > f32::EPSILON {
+                        *taylor_position = slid;
+                    } else {
+                        self.try_alternative_movement(taylor_position, dir_x, dir_y, move_distance, maze, block_size);
+                    }
                 }
             }
         } else {
-            self.direct_movement_fallback(taylor_position, player, taylor_speed, delta_time, maze, block_size);
+            self.direct_movement_fallback(taylor_position, target, taylor_speed, delta_time, maze, block_size);
         }
     }
     
@@ -289,24 +283,7 @@ impl TaylorAI {
     }
     
     fn is_position_valid(&self, x: f32, y: f32, maze: &Maze, block_size: usize) -> bool {
-        const TAYLOR_RADIUS: f32 = 15.0;
-        
-        let check_positions = [
-            (x, y),
-            (x + TAYLOR_RADIUS, y),
-            (x - TAYLOR_RADIUS, y),
-            (x, y + TAYLOR_RADIUS),
-            (x, y - TAYLOR_RADIUS),
-        ];
-        
-        for &(check_x, check_y) in &check_positions {
-            let grid_pos = GridPos::from_world_pos(check_x, check_y, block_size);
-            if !self.is_walkable(&grid_pos, maze) {
-                return false;
-            }
-        }
-        
-        true
+        collision::circle_fits(maze, Vector2::new(x, y), TAYLOR_RADIUS, block_size)
     }
     
     fn try_alternative_movement(
@@ -340,14 +317,14 @@ impl TaylorAI {
     fn direct_movement_fallback(
         &self,
         taylor_position: &mut Vector2,
-        player: &Player,
+        target: Vector2,
         taylor_speed: f32,
         delta_time: f32,
         maze: &Maze,
         block_size: usize,
     ) {
-        let dx = player.pos.x - taylor_position.x;
-        let dy = player.pos.y - taylor_position.y;
+        let dx = target.x - taylor_position.x;
+        let dy = target.y - taylor_position.y;
         let distance = (dx * dx + dy * dy).sqrt();
         
         if distance > 10.0 {
@@ -364,4 +341,68 @@ impl TaylorAI {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+pub struct Chaser {
+    pub position: Vector2,
+    pub ai: TaylorAI,
+}
+
+impl Chaser {
+    pub fn new(position: Vector2) -> Self {
+        Self {
+            position,
+            ai: TaylorAI::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    fn open_room() -> Maze {
+        maze(&["++++++++", "+      +", "+  +   +", "+  +   +", "+      +", "++++++++"])
+    }
+
+    #[test]
+    fn astar_is_deterministic() {
+        let maze = open_room();
+        let (start, goal) = (GridPos::new(1, 1), GridPos::new(6, 4));
+        let first = TaylorAI::new().find_path_astar(start, goal, &maze);
+
+        assert_eq!(first.last(), Some(&goal));
+        for _ in 0..20 {
+            assert_eq!(TaylorAI::new().find_path_astar(start, goal, &maze), first);
+        }
+    }
+
+    #[test]
+    fn astar_path_is_connected_and_walkable() {
+        let maze = open_room();
+        let ai = TaylorAI::new();
+        let start = GridPos::new(1, 3);
+        let path = ai.find_path_astar(start, GridPos::new(5, 2), &maze);
+
+        assert!(!path.is_empty());
+        let mut previous = start;
+        for step in &path {
+            assert!(ai.is_walkable(step, &maze), "{:?}", step);
+            assert!((step.x - previous.x).abs() <= 1 && (step.y - previous.y).abs() <= 1, "{:?} -> {:?}", previous, step);
+            previous = *step;
+        }
+    }
+
+    #[test]
+    fn astar_returns_empty_when_unreachable() {
+        let maze = maze(&["+++++++", "+  +  +", "+  +  +", "+++++++"]);
+        let ai = TaylorAI::new();
+
+        assert!(ai.find_path_astar(GridPos::new(1, 1), GridPos::new(5, 2), &maze).is_empty());
+        assert!(ai.find_path_astar(GridPos::new(1, 1), GridPos::new(3, 1), &maze).is_empty());
+    }
+}
@@ -0,0 +1,48 @@
+use raylib::prelude::Vector2;
+use std::f32::consts::PI;
+use crate::difficulty::SprintTuning;
+use crate::input::TickInput;
+use crate::maze::Maze;
+use crate::player::{Player, apply_input};
+use crate::TICK_DT;
+
+pub const SENSE_DURATION: f32 = 2.5;
+pub const SENSE_COOLDOWN: f32 = 15.0;
+
+// Taylor controlada por un jugador: usa el mismo movimiento que el corredor y, con la
+// acción de interactuar, un "sentido" que revela a los corredores en su minimapa.
+pub struct TaylorPlayer {
+    pub body: Player,
+    pub sense_remaining: f32,
+    pub sense_cooldown: f32,
+}
+
+impl TaylorPlayer {
+    pub fn new(spawn: Vector2, fov: f32) -> Self {
+        let mut body = Player::new(fov);
+        body.pos = spawn;
+        body.a = -PI / 2.0;
+        Self {
+            body,
+            sense_remaining: 0.0,
+            sense_cooldown: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, input: &TickInput, taylor_position: &mut Vector2, maze: &Maze, block_size: usize, sprint: &SprintTuning) {
+        self.body.pos = *taylor_position;
+        apply_input(&mut self.body, input, maze, block_size, sprint);
+        *taylor_position = self.body.pos;
+
+        self.sense_remaining = (self.sense_remaining - TICK_DT).max(0.0);
+        self.sense_cooldown = (self.sense_cooldown - TICK_DT).max(0.0);
+        if input.interact && self.sense_cooldown <= 0.0 {
+            self.sense_remaining = SENSE_DURATION;
+            self.sense_cooldown = SENSE_COOLDOWN;
+        }
+    }
+
+    pub fn is_sensing(&self) -> bool {
+        self.sense_remaining > 0.0
+    }
+}
@@ -1,66 +1,202 @@
-use image::GenericImageView;
+use crate::assets::{AssetManager, TextureHandle};
+use crate::settings::Language;
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextureFilter {
+    Nearest,
+    Bilinear,
+    Trilinear,
+}
+
+const ALL_FILTERS: [TextureFilter; 3] = [TextureFilter::Nearest, TextureFilter::Bilinear, TextureFilter::Trilinear];
+
+impl TextureFilter {
+    pub fn config_name(self) -> &'static str {
+        match self {
+            TextureFilter::Nearest => "nearest",
+            TextureFilter::Bilinear => "bilinear",
+            TextureFilter::Trilinear => "trilinear",
+        }
+    }
+
+    pub fn from_config_name(name: &str) -> Option<Self> {
+        ALL_FILTERS.iter().copied().find(|filter| filter.config_name() == name)
+    }
+
+    pub fn name(self, lang: Language) -> &'static str {
+        match self {
+            TextureFilter::Nearest => lang.pick("Ninguno", "Nearest"),
+            TextureFilter::Bilinear => lang.pick("Bilineal", "Bilinear"),
+            TextureFilter::Trilinear => lang.pick("Trilineal", "Trilinear"),
+        }
+    }
+
+    pub fn cycle(self, direction: i32) -> Self {
+        let current = ALL_FILTERS.iter().position(|&filter| filter == self).unwrap_or(0) as i32;
+        ALL_FILTERS[(current + direction).rem_euclid(ALL_FILTERS.len() as i32) as usize]
+    }
+}
+
+// Un nivel de la cadena de mipmaps, con los píxeles RGBA en un solo bloque fila por fila.
+#[derive(Clone, Debug)]
+pub struct MipLevel {
+    pub pixels: Vec<[u8; 4]>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl MipLevel {
+    fn texel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.pixels[y * self.width + x]
+    }
+
+    fn nearest(&self, tex_x: f32, tex_y: f32) -> [u8; 4] {
+        let x = ((tex_x * (self.width - 1) as f32) as usize).min(self.width - 1);
+        let y = ((tex_y * (self.height - 1) as f32) as usize).min(self.height - 1);
+        self.texel(x, y)
+    }
+
+    // En punto fijo para que filtrar cada píxel no cueste operaciones de coma flotante: las
+    // fracciones van en 1/256, los pesos suman 65536 y el resultado sale en 1/256 de canal.
+    fn bilinear(&self, tex_x: f32, tex_y: f32) -> [u32; 4] {
+        let x = (tex_x * self.width as f32 - 0.5).clamp(0.0, (self.width - 1) as f32);
+        let y = (tex_y * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let (x0, y0) = (x as usize, y as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let fx = (x.fract() * 256.0) as u32;
+        let fy = (y.fract() * 256.0) as u32;
+
+        let samples = [
+            (self.texel(x0, y0), (256 - fx) * (256 - fy)),
+            (self.texel(x1, y0), fx * (256 - fy)),
+            (self.texel(x0, y1), (256 - fx) * fy),
+            (self.texel(x1, y1), fx * fy),
+        ];
+
+        // Igual que en weighted_average, el color se pondera por alfa.
+        let mut color = [0u32; 3];
+        let mut alpha = 0;
+        for (texel, weight) in samples {
+            let alpha_weight = (weight * texel[3] as u32) >> 8;
+            for channel in 0..3 {
+                color[channel] += texel[channel] as u32 * alpha_weight;
+            }
+            alpha += alpha_weight;
+        }
+        if alpha == 0 {
+            return [0; 4];
+        }
+        let channel = |sum: u32| (((sum as u64) << 8) / alpha as u64) as u32;
+        [channel(color[0]), channel(color[1]), channel(color[2]), alpha]
+    }
+
+    fn downsample(&self) -> Self {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let (x0, y0) = ((x * 2).min(self.width - 1), (y * 2).min(self.height - 1));
+                let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+                let average = weighted_average(&[
+                    (self.texel(x0, y0), 0.25),
+                    (self.texel(x1, y0), 0.25),
+                    (self.texel(x0, y1), 0.25),
+                    (self.texel(x1, y1), 0.25),
+                ]);
+                pixels.push(average.map(|channel| channel.round() as u8));
+            }
+        }
+
+        Self { pixels, width, height }
+    }
+}
+
+// El color se pondera por alfa para que los bordes transparentes no oscurezcan la mezcla.
+fn weighted_average(samples: &[([u8; 4], f32)]) -> [f32; 4] {
+    let mut color = [0.0; 4];
+    let mut color_weight = 0.0;
+    for &(texel, weight) in samples {
+        let alpha_weight = weight * texel[3] as f32;
+        for channel in 0..3 {
+            color[channel] += texel[channel] as f32 * alpha_weight;
+        }
+        color[3] += texel[3] as f32 * weight;
+        color_weight += alpha_weight;
+    }
+    if color_weight > 0.0 {
+        for channel in color.iter_mut().take(3) {
+            *channel /= color_weight;
+        }
+    }
+    color
+}
+
+// `levels[0]` es la imagen original y cada nivel siguiente mide la mitad. `translucent` indica
+// si algún píxel no es del todo opaco, así el raycaster sabe si seguir el rayo detrás de la pared.
 #[derive(Clone, Debug)]
 pub struct ImageTexture {
-    pub data: Vec<Vec<[u8; 3]>>,
+    pub levels: Vec<MipLevel>,
     pub width: usize,
     pub height: usize,
+    pub translucent: bool,
 }
 
 impl ImageTexture {
-    pub fn from_file(file_path: &str) -> Self {
-        match image::open(file_path) {
-            Ok(img) => {
-                let img = img.to_rgb8();
-                let (width, height) = img.dimensions();
-                let mut data = vec![vec![[0, 0, 0]; width as usize]; height as usize];
-                
-                for y in 0..height {
-                    for x in 0..width {
-                        let pixel = img.get_pixel(x, y);
-                        data[y as usize][x as usize] = [pixel[0], pixel[1], pixel[2]];
-                    }
-                }
-                
-                println!("Textura cargada exitosamente: {} ({}x{})", file_path, width, height);
-                Self { data, width: width as usize, height: height as usize }
-            },
-            Err(e) => {
-                println!("Error cargando {}: {}, generando textura de fallback...", file_path, e);
-                
-                if file_path.contains("gasoline_can") {
-                    Self::generate_gasoline_can_texture(file_path)
-                } else if file_path.contains("taylor") {
-                    Self::generate_taylor_fallback()
-                } else if file_path.contains("exit") {
-                    Self::generate_exit_texture()
-                } else {
-                    Self::generate_wall_texture()
-                }
-            }
+    pub fn from_file(file_path: &str) -> Result<Self, String> {
+        let img = image::open(file_path).map_err(|e| e.to_string())?.to_rgba8();
+        let (width, height) = img.dimensions();
+        let pixels = img.pixels().map(|pixel| pixel.0).collect();
+
+        println!("Textura cargada exitosamente: {} ({}x{})", file_path, width, height);
+        Ok(Self::from_pixels(pixels, width as usize, height as usize))
+    }
+    
+    fn from_pixels(pixels: Vec<[u8; 4]>, width: usize, height: usize) -> Self {
+        let translucent = pixels.iter().any(|pixel| pixel[3] < 255);
+        let mut levels = vec![MipLevel { pixels, width, height }];
+        while let Some(last) = levels.last().filter(|level| level.width > 1 || level.height > 1) {
+            let next = last.downsample();
+            levels.push(next);
         }
+        Self { levels, width, height, translucent }
+    }
+    
+    // Las texturas de respaldo se dibujan en RGB; con `keyed` el negro puro queda transparente.
+    fn from_rgb(data: Vec<Vec<[u8; 3]>>, keyed: bool) -> Self {
+        let height = data.len();
+        let width = data.first().map_or(0, |row| row.len());
+        let pixels = data
+            .into_iter()
+            .flatten()
+            .map(|[r, g, b]| {
+                let alpha = if keyed && [r, g, b] == [0, 0, 0] { 0 } else { 255 };
+                [r, g, b, alpha]
+            })
+            .collect();
+        Self::from_pixels(pixels, width, height)
     }
     
-    fn generate_gasoline_can_texture(file_path: &str) -> Self {
+    // `frame` va de 1 a 3, cada cuadro brilla un poco más que el anterior.
+    pub fn generate_gasoline_can_texture(frame: u8) -> Self {
         let size = 64;
         let mut data = vec![vec![[0, 0, 0]; size]; size];
         
-        let (base_color, effect_color, glow_intensity) = if file_path.contains("_1") {
-            ([200, 50, 50], [140, 140, 140], 0.0) 
-        } else if file_path.contains("_2") {
-            ([220, 70, 70], [160, 160, 160], 0.3)
-        } else {
-            ([255, 100, 50], [200, 180, 100], 0.6) 
+        let (base_color, effect_color, glow_intensity) = match frame {
+            1 => ([200, 50, 50], [140, 140, 140], 0.0),
+            2 => ([220, 70, 70], [160, 160, 160], 0.3),
+            _ => ([255, 100, 50], [200, 180, 100], 0.6),
         };
         
-        for y in 0..size {
-            for x in 0..size {
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
                 let center_x = size as f32 / 2.0;
                 let center_y = size as f32 * 0.6; 
                 let dx = x as f32 - center_x;
                 let dy = y as f32 - center_y;
                 
-                if dx.abs() <= 20.0 && dy >= -25.0 && dy <= 15.0 {
+                if dx.abs() <= 20.0 && (-25.0..=15.0).contains(&dy) {
                     if y <= size / 4 {
                         let mut cap_color = effect_color;
                         if glow_intensity > 0.0 {
@@ -68,7 +204,7 @@ impl ImageTexture {
                             cap_color[1] = (cap_color[1] as f32 * (1.0 + glow_intensity)).min(255.0) as u8;
                             cap_color[2] = (cap_color[2] as f32 * (1.0 + glow_intensity * 0.5)).min(255.0) as u8;
                         }
-                        data[y][x] = cap_color;
+                        *pixel = cap_color;
                     } else {
                         let mut body_color = base_color;
                         if glow_intensity > 0.0 {
@@ -76,44 +212,44 @@ impl ImageTexture {
                             body_color[1] = (body_color[1] as f32 * (1.0 + glow_intensity * 0.8)).min(255.0) as u8;
                             body_color[2] = (body_color[2] as f32 * (1.0 + glow_intensity)).min(255.0) as u8;
                         }
-                        data[y][x] = body_color;
+                        *pixel = body_color;
                     }
                 }
                 
-                if x >= 18 && x <= 25 && y >= 8 && y <= 15 {
-                    data[y][x] = [120, 120, 120];
+                if (18..=25).contains(&x) && (8..=15).contains(&y) {
+                    *pixel = [120, 120, 120];
                 }
                 
-                if x >= 50 && x <= 55 && y >= 25 && y <= 40 {
+                if (50..=55).contains(&x) && (25..=40).contains(&y) {
                     let mut handle_color = [100, 100, 100];
                     if glow_intensity > 0.0 {
                         handle_color[0] = (handle_color[0] as f32 * (1.0 + glow_intensity * 0.5)).min(255.0) as u8;
                         handle_color[1] = (handle_color[1] as f32 * (1.0 + glow_intensity * 0.5)).min(255.0) as u8;
                         handle_color[2] = (handle_color[2] as f32 * (1.0 + glow_intensity * 0.2)).min(255.0) as u8;
                     }
-                    data[y][x] = handle_color;
+                    *pixel = handle_color;
                 }
                 
-                if dx.abs() <= 12.0 && dy >= -5.0 && dy <= 8.0 {
+                if dx.abs() <= 12.0 && (-5.0..=8.0).contains(&dy) {
                     let mut label_color = [255, 255, 255];
                     if glow_intensity > 0.4 {
                         label_color = [255, 215, 0]; 
                     }
-                    data[y][x] = label_color;
+                    *pixel = label_color;
                 }
                 
-                if dx.abs() <= 20.0 && dy >= -25.0 && dy <= 15.0 {
-                    if dx >= -20.0 && dx <= -15.0 {
-                        let current = data[y][x];
-                        data[y][x] = [
+                if dx.abs() <= 20.0 && (-25.0..=15.0).contains(&dy) {
+                    if (-20.0..=-15.0).contains(&dx) {
+                        let current = *pixel;
+                        *pixel = [
                             (current[0] as u16 + 30).min(255) as u8,
                             (current[1] as u16 + 30).min(255) as u8,
                             (current[2] as u16 + 30).min(255) as u8,
                         ];
                     }
-                    if dx >= 15.0 && dx <= 20.0 {
-                        let current = data[y][x];
-                        data[y][x] = [
+                    if (15.0..=20.0).contains(&dx) {
+                        let current = *pixel;
+                        *pixel = [
                             (current[0] as f32 * 0.7) as u8,
                             (current[1] as f32 * 0.7) as u8,
                             (current[2] as f32 * 0.7) as u8,
@@ -123,107 +259,302 @@ impl ImageTexture {
             }
         }
         
-        println!("Textura de bidón generada: {}", file_path);
-        Self { data, width: size, height: size }
+        println!("Textura de bidón generada: cuadro {}", frame);
+        Self::from_rgb(data, true)
     }
     
-    fn generate_taylor_fallback() -> Self {
+    pub fn generate_taylor_fallback() -> Self {
         let size = 64;
         let mut data = vec![vec![[0, 0, 0]; size]; size];
         
-        for y in 0..size {
-            for x in 0..size {
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
                 let center_x = size as f32 / 2.0;
                 let center_y = size as f32 / 2.0;
                 let dx = x as f32 - center_x;
                 let dy = y as f32 - center_y;
                 
-                if dy >= -25.0 && dy <= -15.0 && dx.abs() <= 8.0 {
-                    data[y][x] = [255, 220, 177];
+                if (-25.0..=-15.0).contains(&dy) && dx.abs() <= 8.0 {
+                    *pixel = [255, 220, 177];
                 }
                 
 
-                if dy >= -15.0 && dy <= 10.0 && dx.abs() <= 12.0 {
+                if (-15.0..=10.0).contains(&dy) && dx.abs() <= 12.0 {
                     if y % 3 == 0 { 
-                        data[y][x] = [200, 0, 100];
+                        *pixel = [200, 0, 100];
                     } else {
-                        data[y][x] = [150, 0, 75]; 
+                        *pixel = [150, 0, 75]; 
                     }
                 }
                 
-                if dy >= 10.0 && dy <= 25.0 {
-                    if (dx >= -8.0 && dx <= -2.0) || (dx >= 2.0 && dx <= 8.0) {
-                        data[y][x] = [50, 50, 150];
-                    }
+                if (10.0..=25.0).contains(&dy) && ((-8.0..=-2.0).contains(&dx) || (2.0..=8.0).contains(&dx)) {
+                    *pixel = [50, 50, 150];
                 }
             }
         }
         
         println!("Textura de Taylor Swift generada (fallback)");
-        Self { data, width: size, height: size }
+        Self::from_rgb(data, true)
     }
     
-    fn generate_exit_texture() -> Self {
+    pub fn generate_player_texture() -> Self {
         let size = 64;
         let mut data = vec![vec![[0, 0, 0]; size]; size];
         
-        for y in 0..size {
-            for x in 0..size {
-                let center_x = size as f32 / 2.0;
-                let center_y = size as f32 / 2.0;
-                let dx = x as f32 - center_x;
-                let dy = y as f32 - center_y;
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let dx = x as f32 - size as f32 / 2.0;
+                let dy = y as f32 - size as f32 / 2.0;
+                
+                if (dx * dx + (dy + 22.0) * (dy + 22.0)).sqrt() <= 7.0 {
+                    *pixel = if dy < -26.0 { [90, 60, 30] } else { [235, 200, 165] };
+                }
+                
+                if (-15.0..=10.0).contains(&dy) && dx.abs() <= 13.0 - (dy + 15.0) * 0.1 {
+                    *pixel = if dx.abs() <= 2.0 && dy <= -5.0 { [230, 230, 230] } else { [40, 45, 70] };
+                }
+                
+                if (-13.0..=6.0).contains(&dy) && dx.abs() >= 14.0 && dx.abs() <= 17.0 {
+                    *pixel = [40, 45, 70];
+                }
+                
+                if dy > 10.0 && dy <= 30.0 && dx.abs() >= 2.0 && dx.abs() <= 9.0 {
+                    *pixel = if dy >= 27.0 { [30, 20, 20] } else { [30, 30, 45] };
+                }
+            }
+        }
+        
+        println!("Textura de jugador remoto generada (fallback)");
+        Self::from_rgb(data, true)
+    }
+    
+    // Caja con un símbolo de color distinto para cada objeto recogible.
+    pub fn generate_item_texture(color: [u8; 3]) -> Self {
+        let size = 64;
+        let mut data = vec![vec![[0, 0, 0]; size]; size];
+        
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let dx = x as f32 - size as f32 / 2.0;
+                let dy = y as f32 - 44.0;
+                
+                if dx.abs() <= 14.0 && dy.abs() <= 14.0 {
+                    *pixel = if dx.abs() >= 12.0 || dy.abs() >= 12.0 { [60, 50, 40] } else { [110, 90, 60] };
+                }
+                if (dx * dx + dy * dy).sqrt() <= 7.0 {
+                    *pixel = color;
+                }
+            }
+        }
+        
+        println!("Textura de objeto generada (fallback)");
+        Self::from_rgb(data, true)
+    }
+    
+    pub fn generate_smoke_texture() -> Self {
+        let size = 64;
+        let mut data = vec![vec![[0, 0, 0]; size]; size];
+        let puffs = [(32.0, 30.0, 20.0), (18.0, 40.0, 14.0), (46.0, 40.0, 14.0), (32.0, 48.0, 15.0)];
+        
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                for &(cx, cy, radius) in &puffs {
+                    let distance = ((x as f32 - cx).powi(2) + (y as f32 - cy).powi(2)).sqrt();
+                    if distance <= radius {
+                        let shade = (200.0 - distance * 3.0) as u8;
+                        *pixel = [shade, shade, shade.saturating_add(8)];
+                    }
+                }
+            }
+        }
+        
+        println!("Textura de humo generada (fallback)");
+        Self::from_rgb(data, true)
+    }
+    
+    pub fn generate_checkpoint_texture(active: bool) -> Self {
+        let size = 64;
+        let mut data = vec![vec![[0, 0, 0]; size]; size];
+        let flag_color = if active { [40, 220, 90] } else { [200, 200, 200] };
+        
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                if (20..=23).contains(&x) && y >= 6 {
+                    *pixel = [150, 110, 60];
+                }
                 
+                let flag_width = 22.0 - (y as f32 - 8.0).abs() * 1.4;
+                if (8..=24).contains(&y) && x >= 24 && (x as f32) < 24.0 + flag_width {
+                    *pixel = flag_color;
+                }
+                
+                if y >= 58 && (12..=32).contains(&x) {
+                    *pixel = [90, 90, 90];
+                }
+            }
+        }
+        
+        println!("Textura de punto de control generada (fallback)");
+        Self::from_rgb(data, true)
+    }
+    
+    pub fn generate_exit_texture() -> Self {
+        let size = 64;
+        let mut data = vec![vec![[0, 0, 0]; size]; size];
+        
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
                 if x <= 2 || x >= size - 3 || y <= 2 || y >= size - 3 {
-                    data[y][x] = [100, 60, 30]; 
+                    *pixel = [100, 60, 30]; 
                 }
-                else if (x >= 6 && x <= 28 && y >= 6 && y <= 25) || 
-                        (x >= 35 && x <= 57 && y >= 6 && y <= 25) {
+                else if ((6..=28).contains(&x) && (6..=25).contains(&y)) || 
+                        ((35..=57).contains(&x) && (6..=25).contains(&y)) {
                     let grain = ((x + y * 3) % 8) as f32 / 8.0;
-                    data[y][x] = [
+                    *pixel = [
                         (120.0 + grain * 40.0) as u8,
                         (80.0 + grain * 20.0) as u8,
                         (40.0 + grain * 10.0) as u8,
                     ];
                 }
-                else if (x >= 26 && x <= 30 && y >= 15 && y <= 19) || 
-                        (x >= 33 && x <= 37 && y >= 15 && y <= 19) {
-                    data[y][x] = [200, 180, 120]; 
+                else if ((26..=30).contains(&x) && (15..=19).contains(&y)) || 
+                        ((33..=37).contains(&x) && (15..=19).contains(&y)) {
+                    *pixel = [200, 180, 120]; 
                 }
-                else if x >= 20 && x <= 43 && y >= 30 && y <= 40 {
-                    data[y][x] = [0, 150, 0];
+                else if (20..=43).contains(&x) && (30..=40).contains(&y) {
+                    *pixel = [0, 150, 0];
                 }
-                else if x >= 22 && x <= 41 && y >= 32 && y <= 38 {
+                else if (22..=41).contains(&x) && (32..=38).contains(&y) {
                     let text_pattern = match x {
-                        22..=24 | 26..=28 | 30..=32 | 34..=36 | 38..=41 => {
-                            if y >= 33 && y <= 37 { [255, 255, 255] } else { [0, 150, 0] }
-                        }
+                        22..=24 | 26..=28 | 30..=32 | 34..=36 | 38..=41 if (33..=37).contains(&y) => [255, 255, 255],
                         _ => [0, 150, 0]
                     };
-                    data[y][x] = text_pattern;
+                    *pixel = text_pattern;
                 }
-                else if x >= 29 && x <= 34 && y >= 6 && y <= 25 {
-                    data[y][x] = [60, 40, 20]; 
+                else if (29..=34).contains(&x) && (6..=25).contains(&y) {
+                    *pixel = [60, 40, 20]; 
                 }
                 else if y >= 50 {
-                    data[y][x] = [80, 80, 75];
+                    *pixel = [80, 80, 75];
                 }
                 else {
-                    data[y][x] = [90, 90, 85];
+                    *pixel = [90, 90, 85];
                 }
             }
         }
         
         println!("Textura de puerta de salida generada (fallback)");
-        Self { data, width: size, height: size }
+        Self::from_rgb(data, false)
+    }
+    
+    pub fn generate_car_texture() -> Self {
+        let size = 64;
+        let mut data = vec![vec![[0, 0, 0]; size]; size];
+        
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let body = (30..=48).contains(&y) && (4..=59).contains(&x);
+                let cabin = (16..30).contains(&y) && x >= 14 + (30 - y) / 2 && x <= 49 - (30 - y) / 2;
+                
+                if cabin {
+                    *pixel = if (18..=45).contains(&x) && y >= 19 { [120, 170, 210] } else { [170, 30, 30] };
+                }
+                if body {
+                    *pixel = if y == 38 { [120, 20, 20] } else { [200, 40, 40] };
+                    if (x <= 7 || x >= 56) && (33..=37).contains(&y) {
+                        *pixel = [250, 230, 120];
+                    }
+                }
+                for wheel_x in [16.0, 47.0] {
+                    let distance = ((x as f32 - wheel_x).powi(2) + (y as f32 - 49.0).powi(2)).sqrt();
+                    if distance <= 7.0 {
+                        *pixel = if distance <= 3.0 { [160, 160, 160] } else { [25, 25, 25] };
+                    }
+                }
+            }
+        }
+        
+        println!("Textura de auto generada (fallback)");
+        Self::from_rgb(data, true)
+    }
+    
+    pub fn generate_door_texture() -> Self {
+        let size = 64;
+        let mut data = vec![vec![[0, 0, 0]; size]; size];
+        
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                if x <= 3 || x >= size - 4 || y <= 3 {
+                    *pixel = [70, 45, 25];
+                } else if x % 14 == 0 {
+                    *pixel = [80, 50, 28];
+                } else if (48..=52).contains(&x) && (30..=34).contains(&y) {
+                    *pixel = [210, 180, 90];
+                } else {
+                    let grain = ((x * 7 + y) % 11) as f32 / 11.0;
+                    *pixel = [
+                        (130.0 + grain * 30.0) as u8,
+                        (85.0 + grain * 20.0) as u8,
+                        (45.0 + grain * 10.0) as u8,
+                    ];
+                }
+            }
+        }
+        
+        println!("Textura de puerta generada (fallback)");
+        Self::from_rgb(data, false)
     }
     
-    fn generate_wall_texture() -> Self {
+    pub fn generate_switch_texture(used: bool) -> Self {
+        let size = 64;
+        let mut data = Self::brick_pattern(size);
+        let handle_color = if used { [40, 200, 80] } else { [220, 60, 40] };
+        let lever_top = if used { 40 } else { 14 };
+        
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                if (22..=41).contains(&x) && (26..=50).contains(&y) {
+                    *pixel = [60, 60, 65];
+                }
+                if (30..=33).contains(&x) && y >= lever_top.min(36) && y <= lever_top.max(36) {
+                    *pixel = [180, 180, 185];
+                }
+                if (27..=36).contains(&x) && y + 3 >= lever_top && y <= lever_top + 3 {
+                    *pixel = handle_color;
+                }
+            }
+        }
+        
+        println!("Textura de palanca generada (fallback)");
+        Self::from_rgb(data, false)
+    }
+    
+    pub fn generate_gate_texture() -> Self {
         let size = 64;
         let mut data = vec![vec![[0, 0, 0]; size]; size];
         
-        for y in 0..size {
-            for x in 0..size {
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                if x % 10 <= 2 || y <= 4 || (30..=33).contains(&y) || y >= size - 5 {
+                    *pixel = [110, 110, 120];
+                }
+            }
+        }
+        
+        println!("Textura de reja generada (fallback)");
+        Self::from_rgb(data, true)
+    }
+    
+    pub fn generate_wall_texture() -> Self {
+        let data = Self::brick_pattern(64);
+        println!("Textura de pared generada (fallback)");
+        Self::from_rgb(data, false)
+    }
+    
+    fn brick_pattern(size: usize) -> Vec<Vec<[u8; 3]>> {
+        let mut data = vec![vec![[0, 0, 0]; size]; size];
+        
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
                 let brick_width = 16;
                 let brick_height = 8;
                 let _brick_x = x / brick_width;
@@ -234,52 +565,77 @@ impl ImageTexture {
                 
                 if adjusted_x % brick_width < brick_width - 1 && y % brick_height < brick_height - 1 {
                     let variation = ((x + y) % 16) as f32 / 16.0;
-                    data[y][x] = [
+                    *pixel = [
                         (120.0 + variation * 40.0) as u8,
                         (60.0 + variation * 20.0) as u8,
                         (40.0 + variation * 15.0) as u8,
                     ];
                 } else {
-                    data[y][x] = [90, 90, 85];
+                    *pixel = [90, 90, 85];
                 }
             }
         }
-        
-        println!("Textura de pared generada (fallback)");
-        Self { data, width: size, height: size }
+        data
     }
     
-    pub fn get_color(&self, tex_x: f32, tex_y: f32) -> [u8; 3] {
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.levels.iter().map(|level| std::mem::size_of::<MipLevel>() + level.pixels.len() * 4).sum::<usize>()
+    }
+    
+    // `texels_per_pixel` es cuántos texeles de la imagen original caen en un píxel de pantalla;
+    // de ahí sale el nivel de mipmap, así las paredes lejanas no parpadean.
+    pub fn sample(&self, tex_x: f32, tex_y: f32, texels_per_pixel: f32, filter: TextureFilter) -> [u8; 4] {
         let tex_x = tex_x.clamp(0.0, 1.0);
         let tex_y = tex_y.clamp(0.0, 1.0);
+        let lod = texels_per_pixel.max(1.0).log2().min((self.levels.len() - 1) as f32);
         
-        let x = ((tex_x * (self.width - 1) as f32) as usize).min(self.width - 1);
-        let y = ((tex_y * (self.height - 1) as f32) as usize).min(self.height - 1);
-        
-        self.data[y][x]
+        let color = match filter {
+            TextureFilter::Nearest => return self.levels[lod.round() as usize].nearest(tex_x, tex_y),
+            TextureFilter::Bilinear => self.levels[lod.round() as usize].bilinear(tex_x, tex_y),
+            TextureFilter::Trilinear => {
+                let lower = lod.floor() as usize;
+                let upper = (lower + 1).min(self.levels.len() - 1);
+                let t = (lod.fract() * 256.0) as u32;
+                let near = self.levels[lower].bilinear(tex_x, tex_y);
+                let far = self.levels[upper].bilinear(tex_x, tex_y);
+                [0, 1, 2, 3].map(|channel| (near[channel] * (256 - t) + far[channel] * t) >> 8)
+            }
+        };
+        color.map(|channel| ((channel + 128) >> 8).min(255) as u8)
     }
 }
 
 pub struct TextureManager {
-    pub wall_texture: ImageTexture,
-    pub floor_texture: ImageTexture,
-    pub ceiling_texture: ImageTexture,
-    pub exit_texture: ImageTexture,
+    pub filter: TextureFilter,
+    pub wall_texture: TextureHandle,
+    pub exit_texture: TextureHandle,
+    pub door_texture: TextureHandle,
+    pub switch_texture: TextureHandle,
+    pub used_switch_texture: TextureHandle,
+    pub gate_texture: TextureHandle,
 }
 
 impl TextureManager {
-    pub fn new() -> Self {
+    pub fn new(assets: &mut AssetManager, filter: TextureFilter) -> Self {
         Self {
-            wall_texture: ImageTexture::from_file("assets/wall.png"),
-            floor_texture: ImageTexture::from_file("assets/floor.png"),
-            ceiling_texture: ImageTexture::from_file("assets/ceiling.png"),
-            exit_texture: ImageTexture::from_file("assets/exit.png"),
+            filter,
+            wall_texture: assets.texture("assets/wall.png"),
+            exit_texture: assets.texture("assets/exit.png"),
+            door_texture: assets.texture("assets/door.png"),
+            switch_texture: assets.texture("assets/switch.png"),
+            used_switch_texture: assets.texture("assets/switch_used.png"),
+            gate_texture: assets.texture("assets/gate.png"),
         }
     }
     
     pub fn get_texture(&self, wall_char: char) -> &ImageTexture {
         match wall_char {
             'E' => &self.exit_texture,   
+            'D' => &self.door_texture,
+            'S' => &self.switch_texture,
+            's' => &self.used_switch_texture,
+            'G' => &self.gate_texture,
             '+' | '-' | '|' => &self.wall_texture,
             '1' => &self.wall_texture,     
             '2' => &self.wall_texture,    
@@ -287,4 +643,61 @@ impl TextureManager {
             _ => &self.wall_texture,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+    // Damero de 4x4: el nivel 0 alterna blanco y negro y todos los demás quedan grises.
+    fn checkerboard() -> ImageTexture {
+        let pixels = (0..16).map(|index| if (index % 4 + index / 4) % 2 == 0 { WHITE } else { BLACK }).collect();
+        ImageTexture::from_pixels(pixels, 4, 4)
+    }
+
+    #[test]
+    fn builds_mip_chain_down_to_one_pixel() {
+        let texture = checkerboard();
+        let sizes: Vec<_> = texture.levels.iter().map(|level| (level.width, level.height)).collect();
+        assert_eq!(sizes, [(4, 4), (2, 2), (1, 1)]);
+        assert_eq!(texture.levels[1].pixels, vec![[128, 128, 128, 255]; 4]);
+    }
+
+    #[test]
+    fn nearest_picks_mip_level_from_texel_density() {
+        let texture = checkerboard();
+        assert_eq!(texture.sample(0.0, 0.0, 1.0, TextureFilter::Nearest), WHITE);
+        assert_eq!(texture.sample(0.0, 0.0, 0.5, TextureFilter::Nearest), WHITE);
+        assert_eq!(texture.sample(0.0, 0.0, 2.0, TextureFilter::Nearest), [128, 128, 128, 255]);
+        assert_eq!(texture.sample(0.0, 0.0, 3.0, TextureFilter::Nearest), [128, 128, 128, 255]);
+        assert_eq!(texture.sample(0.0, 0.0, 64.0, TextureFilter::Nearest), [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn bilinear_blends_neighbours() {
+        let texture = ImageTexture::from_pixels(vec![BLACK, WHITE], 2, 1);
+        assert_eq!(texture.sample(0.0, 0.5, 1.0, TextureFilter::Bilinear), BLACK);
+        assert_eq!(texture.sample(0.5, 0.5, 1.0, TextureFilter::Bilinear), [128, 128, 128, 255]);
+        assert_eq!(texture.sample(1.0, 0.5, 1.0, TextureFilter::Bilinear), WHITE);
+    }
+
+    #[test]
+    fn bilinear_ignores_colour_of_transparent_texels() {
+        let texture = ImageTexture::from_pixels(vec![[255, 0, 0, 255], [0, 0, 0, 0]], 2, 1);
+        assert_eq!(texture.sample(0.5, 0.5, 1.0, TextureFilter::Bilinear), [255, 0, 0, 128]);
+    }
+
+    #[test]
+    fn trilinear_blends_between_levels() {
+        let texture = checkerboard();
+        let sharp = texture.sample(0.125, 0.125, 1.0, TextureFilter::Trilinear);
+        let between = texture.sample(0.125, 0.125, 2f32.sqrt(), TextureFilter::Trilinear);
+        let blurred = texture.sample(0.125, 0.125, 2.0, TextureFilter::Trilinear);
+        assert_eq!(sharp, WHITE);
+        assert_eq!(blurred, [128, 128, 128, 255]);
+        assert!(between[0] > blurred[0] && between[0] < sharp[0]);
+    }
+}